@@ -1,19 +1,66 @@
 //! The main browser struct to manage pages.
 
+use crate::constants::DEFAULT_BASE_FONT_PX;
+use crate::constants::DEFAULT_MAX_BODY_BYTES;
+use crate::constants::DEFAULT_MAX_CACHE_ENTRIES;
+use crate::constants::DEFAULT_MAX_DOM_NODES;
+use crate::constants::DEFAULT_TAB_SIZE;
+use crate::error::Error;
+use crate::http::HttpResponse;
 use crate::log::Log;
 use crate::log::LogLevel;
+use crate::rasterizer::rasterize;
+use crate::renderer::css::user_agent::DEFAULT_USER_AGENT_STYLESHEET;
+use crate::renderer::js::runtime::RuntimeValue;
 use crate::renderer::page::Page;
+use crate::url::Url;
+use alloc::format;
 use alloc::rc::Rc;
 use alloc::string::String;
+use alloc::string::ToString;
 use alloc::vec::Vec;
 use core::cell::RefCell;
 
 #[derive(Debug, Clone)]
 pub struct Browser {
-    // TODO: support multiple tabs/pages. This browser currently supports only one page.
+    // TODO: support switching tabs/pages. A new page is opened only by `navigate_in_new_page`.
     active_page_index: usize,
+    /// The pages opened so far. Only the active page is shown.
     pages: Vec<Rc<RefCell<Page>>>,
     logs: Vec<Log>,
+    /// URLs navigated so far, from the oldest to the newest.
+    history: Vec<String>,
+    /// https://www.w3.org/TR/css-cascade-4/#cascade-origin-ua
+    user_agent_stylesheet: String,
+    /// Fetches a resource for a URL. It's set by a UI and can be replaced at runtime, e.g. by a
+    /// mock in tests.
+    handle_url: Option<fn(String) -> Result<HttpResponse, Error>>,
+    /// True if the current navigation is requested to stop.
+    stopped: bool,
+    /// Returns true if a user requested to stop, e.g. by pressing Esc. It's injected by a UI
+    /// because a fetch blocks the UI's event loop, so the UI can't call `stop` until it returns.
+    stop_poller: Option<fn() -> bool>,
+    /// Returns the current time in an arbitrary unit, e.g. milliseconds. It's injected by a UI
+    /// because `no_std` has no clock.
+    clock: Option<fn() -> u64>,
+    /// The font size in px that `medium` text is rendered at.
+    base_font_px: f64,
+    /// The number of columns between tab stops in preformatted text.
+    tab_size: usize,
+    /// The maximum number of nodes in a document, to protect against huge or hostile pages.
+    max_dom_nodes: usize,
+    /// The maximum length in bytes of a response body that is rendered.
+    max_body_bytes: usize,
+    /// Successful responses fetched so far, keyed by URL. `fetch` takes `&self`, so the cache is
+    /// updated through a `RefCell`.
+    cache: RefCell<Vec<(String, HttpResponse)>>,
+    /// The maximum number of responses in `cache`. The least recently fetched one is evicted
+    /// first.
+    max_cache_entries: usize,
+    /// True if resources are served only from the cache.
+    offline: bool,
+    /// Receives every new log entry as it's added, e.g. to stream logs to an embedder.
+    log_sink: Option<fn(&Log)>,
 }
 
 impl Browser {
@@ -24,6 +71,20 @@ impl Browser {
             active_page_index: 0,
             pages: Vec::new(),
             logs: Vec::new(),
+            history: Vec::new(),
+            user_agent_stylesheet: String::from(DEFAULT_USER_AGENT_STYLESHEET),
+            handle_url: None,
+            stopped: false,
+            stop_poller: None,
+            clock: None,
+            base_font_px: DEFAULT_BASE_FONT_PX,
+            tab_size: DEFAULT_TAB_SIZE,
+            max_dom_nodes: DEFAULT_MAX_DOM_NODES,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            cache: RefCell::new(Vec::new()),
+            max_cache_entries: DEFAULT_MAX_CACHE_ENTRIES,
+            offline: false,
+            log_sink: None,
         }));
 
         page.set_browser(Rc::downgrade(&browser));
@@ -36,12 +97,305 @@ impl Browser {
         self.pages[self.active_page_index].clone()
     }
 
+    /// Returns the pages in the order they were opened.
+    pub fn pages(&self) -> Vec<Rc<RefCell<Page>>> {
+        self.pages.clone()
+    }
+
     pub fn push_url_for_subresource(&mut self, src: String) {
         self.pages[self.active_page_index]
             .borrow_mut()
             .push_url_for_subresource(src);
     }
 
+    pub fn push_history(&mut self, url: String) {
+        self.history.push(url);
+    }
+
+    pub fn history(&self) -> Vec<String> {
+        self.history.clone()
+    }
+
+    /// Returns true if `url` has been navigated before. A relative URL is resolved against the
+    /// current URL first. This is used to match the `:visited` pseudo-class.
+    /// https://html.spec.whatwg.org/multipage/semantics-other.html#selector-visited
+    pub fn is_visited(&self, url: &str) -> bool {
+        let url = self.resolve_url(url);
+        self.history.contains(&url)
+    }
+
+    /// Resolves `url`, e.g. the `href` of a link or the `action` of a form, against the current
+    /// URL. An absolute URL, and a URL which can't be resolved, is returned as is.
+    /// https://url.spec.whatwg.org/#concept-basic-url-parser
+    pub fn resolve_url(&self, url: &str) -> String {
+        if url.contains("://") {
+            return url.to_string();
+        }
+        let base = match self.current_url() {
+            Some(base) => Url::new(base).parse(),
+            None => return url.to_string(),
+        };
+        let base = match base {
+            Ok(base) => base,
+            Err(_) => return url.to_string(),
+        };
+        match base.join(url) {
+            Ok(resolved) => resolved.href(),
+            Err(_) => url.to_string(),
+        }
+    }
+
+    /// Replaces the default styles applied to all pages. It takes effect on the next layout.
+    pub fn set_user_agent_stylesheet(&mut self, css: String) {
+        self.user_agent_stylesheet = css;
+    }
+
+    pub fn user_agent_stylesheet(&self) -> String {
+        self.user_agent_stylesheet.clone()
+    }
+
+    /// Sets the font size in px that `medium` text is rendered at. Larger values move text into
+    /// larger font sizes, e.g. for accessibility. It takes effect on the next layout.
+    pub fn set_base_font_px(&mut self, px: f64) {
+        self.base_font_px = px;
+    }
+
+    pub fn base_font_px(&self) -> f64 {
+        self.base_font_px
+    }
+
+    /// Sets how many columns a tab in preformatted text advances to the next tab stop. It takes
+    /// effect on the next layout.
+    pub fn set_tab_size(&mut self, tab_size: usize) {
+        self.tab_size = tab_size;
+    }
+
+    pub fn tab_size(&self) -> usize {
+        self.tab_size
+    }
+
+    /// Sets the maximum number of nodes in a document. The HTML parser stops when it's reached
+    /// and the page shows what was parsed so far. It takes effect on the next parse.
+    pub fn set_max_dom_nodes(&mut self, max_dom_nodes: usize) {
+        self.max_dom_nodes = max_dom_nodes;
+    }
+
+    pub fn max_dom_nodes(&self) -> usize {
+        self.max_dom_nodes
+    }
+
+    /// Sets the maximum length in bytes of a response body. A longer body is replaced with an
+    /// error page. It takes effect on the next response.
+    pub fn set_max_body_bytes(&mut self, max_body_bytes: usize) {
+        self.max_body_bytes = max_body_bytes;
+    }
+
+    pub fn max_body_bytes(&self) -> usize {
+        self.max_body_bytes
+    }
+
+    /// Replaces the function used to fetch resources. It takes effect on the next fetch.
+    pub fn set_handle_url(&mut self, handle_url: fn(String) -> Result<HttpResponse, Error>) {
+        self.handle_url = Some(handle_url);
+    }
+
+    /// Switches the offline mode. In the offline mode, navigation and subresource fetches are
+    /// served only from the cache and fail with `Error::Network("offline")` otherwise.
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Sets the maximum number of responses kept in the cache for the offline mode. The least
+    /// recently fetched responses are evicted when there are more. 0 disables the cache.
+    pub fn set_max_cache_entries(&mut self, max_cache_entries: usize) {
+        self.max_cache_entries = max_cache_entries;
+        let mut cache = self.cache.borrow_mut();
+        let len = cache.len();
+        cache.drain(..len.saturating_sub(max_cache_entries));
+    }
+
+    pub fn max_cache_entries(&self) -> usize {
+        self.max_cache_entries
+    }
+
+    /// Fetches `url` with the current fetch function. A successful response is cached for the
+    /// offline mode.
+    pub fn fetch(&self, url: String) -> Result<HttpResponse, Error> {
+        if self.offline {
+            return match self.cached_response(&url) {
+                Some(response) => Ok(response),
+                None => Err(Error::Network("offline".to_string())),
+            };
+        }
+
+        let response = match self.handle_url {
+            Some(handle_url) => handle_url(url.clone())?,
+            None => return Err(Error::Network(format!("no fetcher is set to load {}", url))),
+        };
+
+        if response.is_success() && self.max_cache_entries > 0 {
+            let mut cache = self.cache.borrow_mut();
+            cache.retain(|(cached_url, _)| *cached_url != url);
+            if cache.len() >= self.max_cache_entries {
+                cache.remove(0);
+            }
+            cache.push((url, response.clone()));
+        }
+        Ok(response)
+    }
+
+    fn cached_response(&self, url: &str) -> Option<HttpResponse> {
+        self.cache
+            .borrow()
+            .iter()
+            .find(|(cached_url, _)| cached_url == url)
+            .map(|(_, response)| response.clone())
+    }
+
+    /// Resolves `url` against the current URL, fetches it with the current fetch function and
+    /// renders the response in the current page. The resolved URL is pushed to the history when
+    /// the response is received. The current page is kept as is if the navigation is stopped
+    /// during the fetch.
+    ///
+    /// This is an associated function because rendering the page borrows the browser.
+    pub fn navigate(browser: &Rc<RefCell<Browser>>, url: &str) -> Result<(), Error> {
+        let url = browser.borrow().resolve_url(url);
+        let url = url.as_str();
+        browser.borrow_mut().clear_stop();
+        let result = browser.borrow().fetch(url.to_string());
+
+        if browser.borrow_mut().poll_stop() {
+            browser
+                .borrow_mut()
+                .console_debug(format!("Navigation to {} stopped", url));
+            return Ok(());
+        }
+
+        let response = result?;
+        browser.borrow_mut().clear_logs();
+        browser.borrow_mut().push_history(url.to_string());
+
+        let page = browser.borrow().current_page();
+        page.borrow_mut().clear_display_items();
+        page.borrow_mut().receive_response(response);
+
+        browser
+            .borrow_mut()
+            .console_debug(format!("Successfully loaded page: {}", url));
+        Ok(())
+    }
+
+    /// Opens a new page, makes it the current page and navigates it to `url`, e.g. for a form
+    /// whose target is `_blank`. The other pages are kept as is. The new page is closed if the
+    /// navigation fails.
+    pub fn navigate_in_new_page(browser: &Rc<RefCell<Browser>>, url: &str) -> Result<(), Error> {
+        let mut page = Page::new();
+        page.set_browser(Rc::downgrade(browser));
+        let previous_index = browser.borrow().active_page_index;
+        {
+            let mut b = browser.borrow_mut();
+            b.pages.push(Rc::new(RefCell::new(page)));
+            b.active_page_index = b.pages.len() - 1;
+        }
+
+        let result = Browser::navigate(browser, url);
+        if result.is_err() {
+            let mut b = browser.borrow_mut();
+            b.pages.pop();
+            b.active_page_index = previous_index;
+        }
+        result
+    }
+
+    /// Runs `js` in the current page, e.g. for a console, and returns the value of the last
+    /// statement. See `Page::execute_script`.
+    ///
+    /// This is an associated function because rendering the page borrows the browser.
+    pub fn execute_script(
+        browser: &Rc<RefCell<Browser>>,
+        js: &str,
+    ) -> Result<Option<RuntimeValue>, Error> {
+        let page = browser.borrow().current_page();
+        let result = page.borrow_mut().execute_script(js);
+        result
+    }
+
+    /// Returns the URL of the last navigation, or None if nothing has been navigated yet.
+    pub fn current_url(&self) -> Option<String> {
+        self.history.last().cloned()
+    }
+
+    /// Sets the clock used to measure the rendering phases of pages.
+    pub fn set_clock(&mut self, clock: fn() -> u64) {
+        self.clock = Some(clock);
+    }
+
+    /// Returns the current time of the injected clock, or None if no clock is set.
+    pub fn now(&self) -> Option<u64> {
+        self.clock.map(|clock| clock())
+    }
+
+    /// Requests to stop the current navigation. Navigation checks the flag between steps, e.g.
+    /// before fetching each subresource, and aborts the remaining steps.
+    /// https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-window-stop
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Sets the function which tells whether a user requested to stop while a fetch blocked the
+    /// UI. See `poll_stop`.
+    pub fn set_stop_poller(&mut self, stop_poller: fn() -> bool) {
+        self.stop_poller = Some(stop_poller);
+    }
+
+    /// Asks the stop poller whether a user requested to stop and returns true if the current
+    /// navigation is requested to stop. Navigation calls it between steps, e.g. after the fetch
+    /// of the document and before fetching each subresource.
+    pub fn poll_stop(&mut self) -> bool {
+        if let Some(stop_poller) = self.stop_poller {
+            if stop_poller() {
+                self.stopped = true;
+            }
+        }
+        self.stopped
+    }
+
+    /// Clears the stop request. Call it when a new navigation starts.
+    pub fn clear_stop(&mut self) {
+        self.stopped = false;
+    }
+
+    /// Draws the current page into a `width` x `height` viewport with the software rasterizer
+    /// and returns the RGB pixels. See `rasterizer::rasterize` for the buffer layout.
+    pub fn viewport_screenshot(&self, width: i64, height: i64) -> Vec<u8> {
+        let display_items = self.current_page().borrow().display_items();
+        rasterize(&display_items, width, height)
+    }
+
+    /// Returns the text and the resolved destination of each link in the current page. See
+    /// `Page::links`.
+    pub fn find_all_links(&self) -> Vec<(String, String)> {
+        self.current_page().borrow().links()
+    }
+
+    /// Returns the DOM tree of the current page for debugging. See `Page::dump_dom`.
+    pub fn dump_dom(&self) -> String {
+        self.current_page().borrow().dump_dom()
+    }
+
+    /// Returns the layout tree of the current page for debugging. See `Page::dump_layout`.
+    pub fn dump_layout(&self) -> String {
+        self.current_page().borrow().dump_layout()
+    }
+
     pub fn logs(&self) -> Vec<Log> {
         self.logs.clone()
     }
@@ -50,15 +404,407 @@ impl Browser {
         self.logs = Vec::new();
     }
 
+    /// Sets a function which receives each new log entry. The entries are still buffered and
+    /// returned by `logs`.
+    pub fn set_log_sink(&mut self, log_sink: fn(&Log)) {
+        self.log_sink = Some(log_sink);
+    }
+
     pub fn console_debug(&mut self, log: String) {
-        self.logs.push(Log::new(LogLevel::Debug, log));
+        self.push_log(Log::new(LogLevel::Debug, log));
     }
 
     pub fn console_warning(&mut self, log: String) {
-        self.logs.push(Log::new(LogLevel::Warning, log));
+        self.push_log(Log::new(LogLevel::Warning, log));
     }
 
     pub fn console_error(&mut self, log: String) {
-        self.logs.push(Log::new(LogLevel::Error, log));
+        self.push_log(Log::new(LogLevel::Error, log));
+    }
+
+    fn push_log(&mut self, log: Log) {
+        if let Some(log_sink) = self.log_sink {
+            log_sink(&log);
+        }
+        self.logs.push(log);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display_item::DisplayItem;
+    use crate::renderer::page::ClickAction;
+    use alloc::vec;
+    use core::sync::atomic::AtomicUsize;
+    use core::sync::atomic::Ordering;
+
+    fn fetch_first(_url: String) -> Result<HttpResponse, Error> {
+        HttpResponse::new("HTTP/1.1 200 OK\nContent-Length: 5\n\nfirst".to_string())
+    }
+
+    fn fetch_second(_url: String) -> Result<HttpResponse, Error> {
+        HttpResponse::new("HTTP/1.1 200 OK\nContent-Length: 6\n\nsecond".to_string())
+    }
+
+    #[test]
+    fn test_no_fetcher() {
+        let browser = Browser::new();
+        assert!(browser
+            .borrow()
+            .fetch("http://example.com".to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn test_set_handle_url() {
+        let browser = Browser::new();
+
+        browser.borrow_mut().set_handle_url(fetch_first);
+        let response = browser
+            .borrow()
+            .fetch("http://example.com".to_string())
+            .expect("failed to fetch");
+        assert_eq!("first", response.body());
+
+        browser.borrow_mut().set_handle_url(fetch_second);
+        let response = browser
+            .borrow()
+            .fetch("http://example.com".to_string())
+            .expect("failed to fetch");
+        assert_eq!("second", response.body());
+    }
+
+    #[test]
+    fn test_dump() {
+        let browser = Browser::new();
+        assert_eq!("", browser.borrow().dump_dom());
+        assert_eq!("", browser.borrow().dump_layout());
+
+        let response = HttpResponse::new(
+            "HTTP/1.1 200 OK\nContent-Length: 37\n\n<html><body><p>text</p></body></html>"
+                .to_string(),
+        )
+        .expect("failed to parse a response");
+        let page = browser.borrow().current_page();
+        page.borrow_mut().receive_response(response);
+
+        let dom = browser.borrow().dump_dom();
+        assert!(dom.contains("kind: Html"));
+        assert!(dom.contains("kind: Body"));
+        assert!(dom.contains("kind: P"));
+        assert!(dom.contains("Text(\"text\")"));
+
+        let layout = browser.borrow().dump_layout();
+        assert!(layout.contains("Block"));
+        assert!(layout.contains("kind: P"));
+        assert!(layout.contains("Text(\"text\")"));
+    }
+
+    fn fetch_page(_url: String) -> Result<HttpResponse, Error> {
+        HttpResponse::new(
+            "HTTP/1.1 200 OK\nContent-Length: 37\n\n<html><body><p>page</p></body></html>"
+                .to_string(),
+        )
+    }
+
+    #[test]
+    fn test_navigate() {
+        let browser = Browser::new();
+        assert_eq!(None, browser.borrow().current_url());
+        assert!(Browser::navigate(&browser, "http://example.com").is_err());
+        assert!(browser.borrow().history().is_empty());
+
+        browser.borrow_mut().set_handle_url(fetch_page);
+        Browser::navigate(&browser, "http://example.com/page").expect("failed to navigate");
+
+        assert_eq!(
+            vec!["http://example.com/page".to_string()],
+            browser.borrow().history()
+        );
+        assert_eq!(
+            Some("http://example.com/page".to_string()),
+            browser.borrow().current_url()
+        );
+        let texts: Vec<String> = browser
+            .borrow()
+            .current_page()
+            .borrow()
+            .display_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["page".to_string()], texts);
+    }
+
+    fn fetch_absolute_url(url: String) -> Result<HttpResponse, Error> {
+        if !url.starts_with("http://example.com/") {
+            return Err(Error::Network(format!("failed to fetch {}", url)));
+        }
+        fetch_page(url)
+    }
+
+    #[test]
+    fn test_navigate_to_relative_url() {
+        let browser = Browser::new();
+        browser.borrow_mut().set_handle_url(fetch_absolute_url);
+        Browser::navigate(&browser, "http://example.com/dir/page").expect("failed to navigate");
+
+        Browser::navigate(&browser, "next").expect("failed to navigate");
+        Browser::navigate(&browser, "/top?q=1").expect("failed to navigate");
+        assert_eq!(
+            vec![
+                "http://example.com/dir/page".to_string(),
+                "http://example.com/dir/next".to_string(),
+                "http://example.com/top?q=1".to_string(),
+            ],
+            browser.borrow().history()
+        );
+        assert!(browser.borrow().is_visited("dir/next"));
+        assert!(browser.borrow().is_visited("http://example.com/dir/page"));
+        assert!(!browser.borrow().is_visited("other"));
+    }
+
+    fn esc_pressed() -> bool {
+        true
+    }
+
+    #[test]
+    fn test_stop_during_navigation() {
+        let browser = Browser::new();
+        browser.borrow_mut().set_handle_url(fetch_page);
+        Browser::navigate(&browser, "http://example.com/page").expect("failed to navigate");
+
+        // Esc pressed while the fetch blocked the UI keeps the current page.
+        browser.borrow_mut().set_stop_poller(esc_pressed);
+        Browser::navigate(&browser, "http://example.com/other").expect("failed to navigate");
+        assert!(browser.borrow().is_stopped());
+        assert_eq!(
+            Some("http://example.com/page".to_string()),
+            browser.borrow().current_url()
+        );
+    }
+
+    fn fetch_links(_url: String) -> Result<HttpResponse, Error> {
+        let html = "<html><body><a href=\"next\">Next\n page</a><p><a href=\"http://other.com/\">Other</a></p></body></html>";
+        HttpResponse::new(format!(
+            "HTTP/1.1 200 OK\nContent-Length: {}\n\n{}",
+            html.len(),
+            html
+        ))
+    }
+
+    #[test]
+    fn test_find_all_links() {
+        let browser = Browser::new();
+        assert!(browser.borrow().find_all_links().is_empty());
+
+        browser.borrow_mut().set_handle_url(fetch_links);
+        Browser::navigate(&browser, "http://example.com:8000/dir/page")
+            .expect("failed to navigate");
+
+        assert_eq!(
+            vec![
+                (
+                    "Next page".to_string(),
+                    "http://example.com:8000/dir/next".to_string()
+                ),
+                ("Other".to_string(), "http://other.com/".to_string()),
+            ],
+            browser.borrow().find_all_links()
+        );
+    }
+
+    #[test]
+    fn test_offline() {
+        let browser = Browser::new();
+        browser.borrow_mut().set_handle_url(fetch_page);
+        Browser::navigate(&browser, "http://example.com/cached").expect("failed to navigate");
+
+        browser.borrow_mut().set_offline(true);
+        assert!(browser.borrow().is_offline());
+        Browser::navigate(&browser, "http://example.com/cached")
+            .expect("a cached URL should load offline");
+        assert_eq!(
+            Err(Error::Network("offline".to_string())),
+            Browser::navigate(&browser, "http://example.com/uncached")
+        );
+        assert!(browser
+            .borrow()
+            .fetch("http://example.com/uncached".to_string())
+            .is_err());
+
+        browser.borrow_mut().set_offline(false);
+        Browser::navigate(&browser, "http://example.com/uncached").expect("failed to navigate");
+    }
+
+    #[test]
+    fn test_max_cache_entries() {
+        let browser = Browser::new();
+        browser.borrow_mut().set_handle_url(fetch_page);
+        browser.borrow_mut().set_max_cache_entries(2);
+        for path in ["a", "b", "a"] {
+            Browser::navigate(&browser, &format!("http://example.com/{}", path))
+                .expect("failed to navigate");
+        }
+        // Fetching "a" again makes "b" the least recently fetched.
+        Browser::navigate(&browser, "http://example.com/c").expect("failed to navigate");
+
+        browser.borrow_mut().set_offline(true);
+        assert!(Browser::navigate(&browser, "http://example.com/a").is_ok());
+        assert!(Browser::navigate(&browser, "http://example.com/b").is_err());
+        assert!(Browser::navigate(&browser, "http://example.com/c").is_ok());
+
+        browser.borrow_mut().set_max_cache_entries(1);
+        assert!(Browser::navigate(&browser, "http://example.com/a").is_err());
+        assert!(Browser::navigate(&browser, "http://example.com/c").is_ok());
+    }
+
+    fn fetch_script_page(_url: String) -> Result<HttpResponse, Error> {
+        let html = "<html><body><p id=\"x\">old</p></body></html>";
+        HttpResponse::new(format!(
+            "HTTP/1.1 200 OK\nContent-Length: {}\n\n{}",
+            html.len(),
+            html
+        ))
+    }
+
+    fn texts(browser: &Rc<RefCell<Browser>>) -> Vec<String> {
+        browser
+            .borrow()
+            .current_page()
+            .borrow()
+            .display_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_execute_script() {
+        let browser = Browser::new();
+        assert!(Browser::execute_script(&browser, "1 + 2").is_err());
+
+        browser.borrow_mut().set_handle_url(fetch_script_page);
+        Browser::navigate(&browser, "http://example.com").expect("failed to navigate");
+        assert_eq!(vec!["old".to_string()], texts(&browser));
+
+        assert_eq!(
+            Ok(Some(RuntimeValue::Number(3))),
+            Browser::execute_script(&browser, "1 + 2")
+        );
+        assert_eq!(
+            Ok(None),
+            Browser::execute_script(
+                &browser,
+                "document.getElementById(\"x\").textContent = \"new\""
+            )
+        );
+        assert_eq!(vec!["new".to_string()], texts(&browser));
+
+        // Single-quoted strings are supported, and unsupported input is an error.
+        assert_eq!(
+            Ok(None),
+            Browser::execute_script(&browser, "getElementById('x').textContent = 'newer'")
+        );
+        assert_eq!(vec!["newer".to_string()], texts(&browser));
+        assert!(Browser::execute_script(&browser, "1 * 2").is_err());
+        assert!(Browser::execute_script(&browser, "function f() {").is_err());
+        assert!(Browser::execute_script(&browser, "function f").is_err());
+        assert_eq!(Ok(None), Browser::execute_script(&browser, "missing()"));
+        assert_eq!(Ok(None), Browser::execute_script(&browser, "console.log()"));
+        assert_eq!(
+            Ok(Some(RuntimeValue::Number(3))),
+            Browser::execute_script(&browser, "1 + 2")
+        );
+    }
+
+    fn fetch_form_or_result(url: String) -> Result<HttpResponse, Error> {
+        let html = if url.starts_with("http://example.com/result") {
+            "<html><body><p>result</p></body></html>"
+        } else {
+            "<html><body><form action=\"http://example.com/result\" target=\"_blank\"><input type=\"submit\" value=\"Go\"></form></body></html>"
+        };
+        HttpResponse::new(format!(
+            "HTTP/1.1 200 OK\nContent-Length: {}\n\n{}",
+            html.len(),
+            html
+        ))
+    }
+
+    #[test]
+    fn test_submit_form_in_new_page() {
+        let browser = Browser::new();
+        browser.borrow_mut().set_handle_url(fetch_form_or_result);
+        Browser::navigate(&browser, "http://example.com/form").expect("failed to navigate");
+
+        let form_page = browser.borrow().current_page();
+        let form_items = form_page.borrow().display_items();
+        let point = form_items
+            .iter()
+            .find_map(|item| match item {
+                DisplayItem::Input { layout_point, .. } => Some(*layout_point),
+                _ => None,
+            })
+            .expect("submit button should be painted");
+        let action = form_page
+            .borrow_mut()
+            .clicked((point.x() + 1, point.y() + 1));
+        assert_eq!(
+            ClickAction::NavigateInNewPage("http://example.com/result?".to_string()),
+            action
+        );
+
+        Browser::navigate_in_new_page(&browser, "http://example.com/result?")
+            .expect("failed to navigate");
+        let pages = browser.borrow().pages();
+        assert_eq!(2, pages.len());
+        assert!(Rc::ptr_eq(&pages[0], &form_page));
+        assert!(Rc::ptr_eq(&pages[1], &browser.borrow().current_page()));
+        assert_eq!(vec!["result".to_string()], texts(&browser));
+        // The originating page is intact.
+        assert_eq!(form_items, form_page.borrow().display_items());
+
+        // A failed navigation doesn't leave a new page.
+        browser.borrow_mut().set_offline(true);
+        assert!(Browser::navigate_in_new_page(&browser, "http://example.com/uncached").is_err());
+        assert_eq!(2, browser.borrow().pages().len());
+        assert!(Rc::ptr_eq(&pages[1], &browser.borrow().current_page()));
+    }
+
+    static SINK_DEBUG_COUNT: AtomicUsize = AtomicUsize::new(0);
+    static SINK_ERROR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    fn count_sink_logs(log: &Log) {
+        if !log.message().starts_with("sink") {
+            return;
+        }
+        match log.level() {
+            LogLevel::Debug => SINK_DEBUG_COUNT.fetch_add(1, Ordering::SeqCst),
+            LogLevel::Error => SINK_ERROR_COUNT.fetch_add(1, Ordering::SeqCst),
+            LogLevel::Warning => 0,
+        };
+    }
+
+    #[test]
+    fn test_log_sink() {
+        let browser = Browser::new();
+        browser.borrow_mut().console_debug("sink pre".to_string());
+        browser.borrow_mut().set_log_sink(count_sink_logs);
+
+        browser.borrow_mut().console_debug("sink debug".to_string());
+        browser.borrow_mut().console_error("sink error".to_string());
+        browser.borrow_mut().console_error("sink error".to_string());
+
+        assert_eq!(1, SINK_DEBUG_COUNT.load(Ordering::SeqCst));
+        assert_eq!(2, SINK_ERROR_COUNT.load(Ordering::SeqCst));
+        // The entries are still buffered.
+        assert_eq!(4, browser.borrow().logs().len());
     }
 }
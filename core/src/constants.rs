@@ -5,6 +5,7 @@ pub static _RED: u32 = 0xff0000;
 pub static _GREEN: u32 = 0x00ff00;
 pub static _BLUE: u32 = 0x0000ff;
 pub static _DARKBLUE: u32 = 0x00008b;
+pub static PURPLE: u32 = 0x800080;
 pub static LIGHTGREY: u32 = 0xd3d3d3;
 pub static GREY: u32 = 0x808080;
 pub static DARKGREY: u32 = 0x5a5a5a;
@@ -32,3 +33,15 @@ pub static ADDRESSBAR_HEIGHT: i64 = 20;
 pub static CHAR_WIDTH: i64 = 8;
 pub static CHAR_HEIGHT: i64 = 16;
 pub static CHAR_HEIGHT_WITH_PADDING: i64 = CHAR_HEIGHT + 4;
+/// The default font size in px that `medium` text is rendered at.
+pub static DEFAULT_BASE_FONT_PX: f64 = 16.0;
+/// The horizontal space between the border and the label of a button.
+pub static BUTTON_PADDING: i64 = 8;
+/// The default number of columns between tab stops in preformatted text.
+pub static DEFAULT_TAB_SIZE: usize = 4;
+/// The default maximum number of nodes in a document. Parsing stops when it's reached.
+pub static DEFAULT_MAX_DOM_NODES: usize = 100_000;
+/// The default maximum length in bytes of a response body that is rendered.
+pub static DEFAULT_MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+/// The default maximum number of responses kept in the cache for the offline mode.
+pub static DEFAULT_MAX_CACHE_ENTRIES: usize = 64;
@@ -1,10 +1,84 @@
 //! This is used from UI component.
 
+use crate::renderer::layout::color::Color;
 use crate::renderer::layout::computed_style::ComputedStyle;
 use crate::renderer::layout::layout_point::LayoutPoint;
 use crate::renderer::layout::layout_size::LayoutSize;
 use alloc::string::String;
 
+/// The character drawn for each character of a password input's value.
+pub const PASSWORD_MASK: char = '\u{2022}';
+
+/// The text drawn in an input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputText {
+    /// The value as it's shown, e.g. masked in a password input.
+    Value(String),
+    /// The placeholder of an input with an empty value.
+    Placeholder(String),
+    /// An input with neither a value nor a placeholder.
+    Empty,
+}
+
+/// Returns the text drawn in an input of `input_type`. A button shows its label, and the value
+/// of a password input is masked so that only its length is visible.
+/// https://html.spec.whatwg.org/multipage/input.html#password-state-(type=password)
+pub fn input_text(
+    input_type: &str,
+    value: &Option<String>,
+    placeholder: &Option<String>,
+) -> InputText {
+    match (value, placeholder) {
+        (Some(value), _) if is_button_input(input_type) => InputText::Value(value.clone()),
+        (Some(value), _) if !value.is_empty() => InputText::Value(if input_type == "password" {
+            value.chars().map(|_| PASSWORD_MASK).collect()
+        } else {
+            value.clone()
+        }),
+        (_, Some(placeholder)) => InputText::Placeholder(placeholder.clone()),
+        _ => InputText::Empty,
+    }
+}
+
+/// Returns true if an input of `input_type` is a button. A button shows a label and is clicked
+/// instead of being typed into.
+/// https://html.spec.whatwg.org/multipage/input.html#submit-button-state-(type=submit)
+/// https://html.spec.whatwg.org/multipage/input.html#reset-button-state-(type=reset)
+/// https://html.spec.whatwg.org/multipage/input.html#button-state-(type=button)
+pub fn is_button_input(input_type: &str) -> bool {
+    matches!(input_type, "submit" | "reset" | "button")
+}
+
+/// Returns the label of a button input: its `value`, or the default label of `input_type` when
+/// it has no value.
+pub fn button_label(input_type: &str, value: Option<String>) -> String {
+    match (value, input_type) {
+        (Some(value), _) => value,
+        (None, "submit") => String::from("Submit"),
+        (None, "reset") => String::from("Reset"),
+        (None, _) => String::new(),
+    }
+}
+
+/// Returns true if an input of `input_type` is checked by a click instead of being typed into.
+/// https://html.spec.whatwg.org/multipage/input.html#checkbox-state-(type=checkbox)
+/// https://html.spec.whatwg.org/multipage/input.html#radio-button-state-(type=radio)
+pub fn is_checkable_input(input_type: &str) -> bool {
+    matches!(input_type, "checkbox" | "radio")
+}
+
+/// Returns the mark shown for a checkbox or a radio button of `input_type`, e.g. "[x]" for a
+/// checked checkbox.
+pub fn check_mark(input_type: &str, checked: bool) -> String {
+    let mark = match (input_type, checked) {
+        ("radio", true) => "(*)",
+        ("radio", false) => "( )",
+        (_, true) => "[x]",
+        (_, false) => "[ ]",
+    };
+    String::from(mark)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum DisplayItem {
     Rect {
@@ -16,17 +90,26 @@ pub enum DisplayItem {
         text: String,
         style: ComputedStyle,
         layout_point: LayoutPoint,
+        /// True if this text is a part of a link whose destination has been visited.
+        visited: bool,
+        /// A background color drawn behind the glyphs (e.g. a search match). None by default.
+        highlight: Option<Color>,
     },
     Img {
         src: String,
         style: ComputedStyle,
         layout_point: LayoutPoint,
+        /// True if the image has `loading="lazy"`. It's not fetched until it's near the viewport.
+        /// https://html.spec.whatwg.org/multipage/urls-and-fetching.html#lazy-loading-attributes
+        lazy: bool,
     },
     Input {
         input_type: String,
         name: Option<String>,
         placeholder: Option<String>,
         value: Option<String>,
+        /// The caret position in characters of `value`, or None if the input doesn't have focus.
+        caret: Option<usize>,
         style: ComputedStyle,
         layout_point: LayoutPoint,
         layout_size: LayoutSize,
@@ -52,6 +135,8 @@ impl DisplayItem {
                 text: _,
                 style: _,
                 layout_point: _,
+                visited: _,
+                highlight: _,
             }
         )
     }
@@ -64,6 +149,7 @@ impl DisplayItem {
                 name: _,
                 placeholder: _,
                 value: _,
+                caret: _,
                 style: _,
                 layout_point: _,
                 layout_size: _,
@@ -29,7 +29,11 @@ pub struct HttpResponse {
     status_code: u32,
     reason: String,
     headers: Vec<Header>,
+    raw_headers: String,
     pub body: String,
+    /// True if the received body was not valid UTF-8. Invalid sequences in `body` are replaced
+    /// with U+FFFD.
+    invalid_utf8: bool,
 }
 
 /// https://datatracker.ietf.org/doc/html/rfc7230#section-3
@@ -51,7 +55,7 @@ impl HttpResponse {
             }
         };
 
-        let (headers, body) = match remaining.split_once("\n\n") {
+        let (headers, raw_headers, body) = match remaining.split_once("\n\n") {
             Some((h, b)) => {
                 let mut headers = Vec::new();
                 for header in h.split('\n') {
@@ -61,22 +65,46 @@ impl HttpResponse {
                         String::from(splitted_header[1].trim()),
                     ));
                 }
-                (headers, b)
+                (headers, h, b)
             }
-            None => (Vec::new(), remaining),
+            None => (Vec::new(), "", remaining),
         };
 
-        let statuses: Vec<&str> = status_line.split(' ').collect();
+        // https://datatracker.ietf.org/doc/html/rfc7230#section-3.1.2
+        // status-line = HTTP-version SP status-code SP reason-phrase CRLF
+        // The reason phrase may contain spaces or be empty.
+        let mut statuses = status_line.splitn(3, ' ');
+        let version = statuses.next().unwrap_or("").to_string();
+        let status_code = statuses
+            .next()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(404);
+        let reason = statuses.next().unwrap_or("").trim().to_string();
 
         Ok(Self {
-            version: statuses[0].to_string(),
-            status_code: statuses[1].parse().unwrap_or(404),
-            reason: statuses[2].to_string(),
+            version,
+            status_code,
+            reason,
             headers,
+            raw_headers: raw_headers.to_string(),
             body: body.to_string(),
+            invalid_utf8: false,
         })
     }
 
+    /// Parses a response received as bytes. Unlike `new`, it accepts a body which is not valid
+    /// UTF-8, e.g. an image, and such a response is reported by `is_binary()`.
+    pub fn from_bytes(raw_response: &[u8]) -> Result<Self, Error> {
+        match core::str::from_utf8(raw_response) {
+            Ok(response) => Self::new(response.to_string()),
+            Err(_) => {
+                let mut response = Self::new(String::from_utf8_lossy(raw_response).into_owned())?;
+                response.invalid_utf8 = true;
+                Ok(response)
+            }
+        }
+    }
+
     pub fn version(&self) -> String {
         self.version.clone()
     }
@@ -89,14 +117,71 @@ impl HttpResponse {
         self.reason.clone()
     }
 
+    /// https://datatracker.ietf.org/doc/html/rfc7230#section-3.1.2
+    /// Returns an empty string when the status line has no reason phrase.
+    pub fn reason_phrase(&self) -> &str {
+        &self.reason
+    }
+
+    /// https://datatracker.ietf.org/doc/html/rfc7231#section-6.3
+    /// 2xx (Successful)
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status_code)
+    }
+
+    /// https://datatracker.ietf.org/doc/html/rfc7231#section-6.4
+    /// 3xx (Redirection)
+    pub fn is_redirect(&self) -> bool {
+        (300..400).contains(&self.status_code)
+    }
+
+    /// https://datatracker.ietf.org/doc/html/rfc7231#section-6.5
+    /// https://datatracker.ietf.org/doc/html/rfc7231#section-6.6
+    /// 4xx (Client Error) and 5xx (Server Error)
+    pub fn is_error(&self) -> bool {
+        (400..600).contains(&self.status_code)
+    }
+
     pub fn headers(&self) -> Vec<Header> {
         self.headers.clone()
     }
 
+    /// Returns the header section as received, without the status line and the empty line
+    /// after it. Line endings are normalized to LF. This is useful to debug header parsing.
+    pub fn raw_headers(&self) -> &str {
+        &self.raw_headers
+    }
+
     pub fn body(&self) -> String {
         self.body.clone()
     }
 
+    /// Returns true if the body can't be shown as text: the `Content-Type` is not a textual
+    /// type, or the body is not valid UTF-8 or contains a NUL character.
+    /// https://mimesniff.spec.whatwg.org/#binary-data-byte
+    pub fn is_binary(&self) -> bool {
+        if self.invalid_utf8 || self.body.contains('\0') {
+            return true;
+        }
+
+        let content_type = match self
+            .headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case("Content-Type"))
+        {
+            Some(h) => h.value.to_ascii_lowercase(),
+            // Without `Content-Type`, the body is assumed to be HTML.
+            None => return false,
+        };
+        let essence = content_type.split(';').next().unwrap_or("").trim();
+        !(essence.starts_with("text/")
+            || essence.ends_with("+xml")
+            || matches!(
+                essence,
+                "" | "application/xml" | "application/json" | "application/javascript"
+            ))
+    }
+
     pub fn header_value(&self, name: &str) -> Result<String, String> {
         for h in &self.headers {
             if h.name == name {
@@ -175,4 +260,65 @@ mod tests {
 
         assert_eq!(res.body(), "body message".to_string());
     }
+
+    #[test]
+    fn test_status_ok() {
+        let raw = "HTTP/1.1 200 OK\n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.reason_phrase(), "OK");
+        assert!(res.is_success());
+        assert!(!res.is_redirect());
+        assert!(!res.is_error());
+    }
+
+    #[test]
+    fn test_status_moved_permanently() {
+        let raw = "HTTP/1.1 301 Moved Permanently\nLocation: http://example.com/\n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.status_code(), 301);
+        assert_eq!(res.reason_phrase(), "Moved Permanently");
+        assert!(!res.is_success());
+        assert!(res.is_redirect());
+        assert!(!res.is_error());
+    }
+
+    #[test]
+    fn test_status_without_reason_phrase() {
+        let raw = "HTTP/1.1 404\n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.status_code(), 404);
+        assert_eq!(res.reason_phrase(), "");
+        assert!(!res.is_success());
+        assert!(!res.is_redirect());
+        assert!(res.is_error());
+    }
+
+    #[test]
+    fn test_raw_headers() {
+        let headers = "content-TYPE: text/html\nX-Custom:  spaced value \nContent-Length: 4";
+        let raw = format!("HTTP/1.1 200 OK\n{}\n\nbody", headers);
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(headers, res.raw_headers());
+        assert_eq!(res.header_value("X-Custom"), Ok("spaced value".to_string()));
+
+        let res = HttpResponse::new("HTTP/1.1 200 OK\n\n".to_string())
+            .expect("failed to parse http response");
+        assert_eq!("", res.raw_headers());
+    }
+
+    #[test]
+    fn test_is_binary() {
+        let raw = "HTTP/1.1 200 OK\nContent-Type: text/html; charset=utf-8\n\n<p>a</p>".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert!(!res.is_binary());
+
+        let res = HttpResponse::new("HTTP/1.1 200 OK\ncontent-type: image/png\n\nbody".to_string())
+            .expect("failed to parse http response");
+        assert!(res.is_binary());
+
+        let res = HttpResponse::from_bytes(b"HTTP/1.1 200 OK\nDate: xx\n\n\xff\xfe")
+            .expect("failed to parse http response");
+        assert!(res.is_binary());
+    }
 }
@@ -8,6 +8,7 @@ pub mod display_item;
 pub mod error;
 pub mod http;
 pub mod log;
+pub mod rasterizer;
 pub mod renderer;
 pub mod url;
 pub mod utils;
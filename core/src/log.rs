@@ -19,6 +19,14 @@ impl Log {
     pub fn new(level: LogLevel, log: String) -> Self {
         Self { level, log }
     }
+
+    pub fn level(&self) -> LogLevel {
+        self.level.clone()
+    }
+
+    pub fn message(&self) -> String {
+        self.log.clone()
+    }
 }
 
 impl ToString for Log {
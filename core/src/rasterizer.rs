@@ -0,0 +1,241 @@
+//! A software rasterizer which draws display items into an in-memory RGB pixel buffer,
+//! independently of any UI backend. It's useful for pixel-level tests of rendering.
+//!
+//! Drawing is approximate: glyphs are not rasterized, and a text is drawn as a solid block of
+//! its color covering the text's advance.
+
+use crate::constants::*;
+use crate::display_item::DisplayItem;
+use crate::renderer::layout::computed_style::FontSize;
+use crate::renderer::layout::layout_object::char_width;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The number of bytes per pixel.
+pub const BYTES_PER_PIXEL: usize = 3;
+/// The space between the left edge of an input and its text.
+const INPUT_PADDING: i64 = 5;
+
+struct Canvas {
+    width: i64,
+    height: i64,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: i64, height: i64) -> Self {
+        let width = width.max(0);
+        let height = height.max(0);
+        let mut canvas = Self {
+            width,
+            height,
+            pixels: vec![0; (width * height) as usize * BYTES_PER_PIXEL],
+        };
+        canvas.fill_rect(WHITE, 0, 0, width, height);
+        canvas
+    }
+
+    /// Fills a rectangle with `color`. The part outside the canvas is clipped.
+    fn fill_rect(&mut self, color: u32, x: i64, y: i64, width: i64, height: i64) {
+        let left = x.max(0);
+        let top = y.max(0);
+        let right = (x + width).min(self.width);
+        let bottom = (y + height).min(self.height);
+
+        for py in top..bottom {
+            for px in left..right {
+                let i = (py * self.width + px) as usize * BYTES_PER_PIXEL;
+                self.pixels[i] = (color >> 16) as u8;
+                self.pixels[i + 1] = (color >> 8) as u8;
+                self.pixels[i + 2] = color as u8;
+            }
+        }
+    }
+
+    /// Draws a 1px outline of a rectangle with `color`.
+    fn stroke_rect(&mut self, color: u32, x: i64, y: i64, width: i64, height: i64) {
+        self.fill_rect(color, x, y, width, 1);
+        self.fill_rect(color, x, y + height - 1, width, 1);
+        self.fill_rect(color, x, y, 1, height);
+        self.fill_rect(color, x + width - 1, y, 1, height);
+    }
+}
+
+/// Draws `display_items` into a `width` x `height` viewport whose background is white. The
+/// returned buffer holds pixels row by row, and each pixel is stored in the R, G, B order.
+pub fn rasterize(display_items: &[DisplayItem], width: i64, height: i64) -> Vec<u8> {
+    let mut canvas = Canvas::new(width, height);
+
+    for item in display_items {
+        match item {
+            DisplayItem::Rect {
+                style,
+                layout_point,
+                layout_size,
+            } => {
+                canvas.fill_rect(
+                    style.background_color().code_u32(),
+                    layout_point.x(),
+                    layout_point.y(),
+                    layout_size.width(),
+                    layout_size.height(),
+                );
+            }
+            DisplayItem::Text {
+                text,
+                style,
+                layout_point,
+                visited,
+                highlight,
+            } => {
+                let ratio = match style.font_size() {
+                    FontSize::Medium => 1,
+                    FontSize::XLarge => 2,
+                    FontSize::XXLarge => 3,
+                };
+                let text_width = char_width(style) * text.chars().count() as i64;
+                let text_height = CHAR_HEIGHT * ratio;
+
+                if let Some(highlight) = highlight {
+                    canvas.fill_rect(
+                        highlight.code_u32(),
+                        layout_point.x(),
+                        layout_point.y(),
+                        text_width,
+                        text_height,
+                    );
+                }
+
+                // A visited link is drawn in purple.
+                let color = if *visited {
+                    PURPLE
+                } else {
+                    style.color().code_u32()
+                };
+                canvas.fill_rect(
+                    color,
+                    layout_point.x(),
+                    layout_point.y(),
+                    text_width,
+                    text_height,
+                );
+            }
+            DisplayItem::Input {
+                caret,
+                style,
+                layout_point,
+                layout_size,
+                ..
+            } => {
+                canvas.fill_rect(
+                    WHITE,
+                    layout_point.x(),
+                    layout_point.y(),
+                    layout_size.width(),
+                    layout_size.height(),
+                );
+                canvas.stroke_rect(
+                    GREY,
+                    layout_point.x(),
+                    layout_point.y(),
+                    layout_size.width(),
+                    layout_size.height(),
+                );
+
+                // The caret is a 1px vertical bar after the `caret`th character.
+                if let Some(caret) = caret {
+                    canvas.fill_rect(
+                        BLACK,
+                        layout_point.x() + INPUT_PADDING + char_width(style) * *caret as i64,
+                        layout_point.y() + 1,
+                        1,
+                        layout_size.height() - 2,
+                    );
+                }
+            }
+            // Images are not decoded yet.
+            DisplayItem::Img { .. } => {}
+        }
+    }
+
+    canvas.pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::layout::color::Color;
+    use crate::renderer::layout::computed_style::ComputedStyle;
+    use crate::renderer::layout::computed_style::FontFamily;
+    use crate::renderer::layout::layout_point::LayoutPoint;
+    use crate::renderer::layout::layout_size::LayoutSize;
+    use alloc::string::ToString;
+
+    fn pixel(pixels: &[u8], width: i64, x: i64, y: i64) -> (u8, u8, u8) {
+        let i = (y * width + x) as usize * BYTES_PER_PIXEL;
+        (pixels[i], pixels[i + 1], pixels[i + 2])
+    }
+
+    #[test]
+    fn test_empty() {
+        let pixels = rasterize(&[], 2, 2);
+        assert_eq!(vec![0xff; 2 * 2 * BYTES_PER_PIXEL], pixels);
+    }
+
+    #[test]
+    fn test_rect() {
+        let mut style = ComputedStyle::new();
+        style.set_background_color(Color::from_name("red").expect("failed to parse a color"));
+        let item = DisplayItem::Rect {
+            style,
+            layout_point: LayoutPoint::new(2, 3),
+            layout_size: LayoutSize::new(4, 2),
+        };
+        let pixels = rasterize(&[item], 10, 10);
+
+        assert_eq!(10 * 10 * BYTES_PER_PIXEL, pixels.len());
+        assert_eq!((0xff, 0x00, 0x00), pixel(&pixels, 10, 2, 3));
+        assert_eq!((0xff, 0x00, 0x00), pixel(&pixels, 10, 5, 4));
+        assert_eq!((0xff, 0xff, 0xff), pixel(&pixels, 10, 1, 3));
+        assert_eq!((0xff, 0xff, 0xff), pixel(&pixels, 10, 6, 4));
+        assert_eq!((0xff, 0xff, 0xff), pixel(&pixels, 10, 2, 5));
+    }
+
+    #[test]
+    fn test_clip() {
+        let mut style = ComputedStyle::new();
+        style.set_background_color(Color::from_name("blue").expect("failed to parse a color"));
+        let item = DisplayItem::Rect {
+            style,
+            layout_point: LayoutPoint::new(-2, 1),
+            layout_size: LayoutSize::new(10, 10),
+        };
+        let pixels = rasterize(&[item], 4, 4);
+
+        assert_eq!((0x00, 0x00, 0xff), pixel(&pixels, 4, 0, 1));
+        assert_eq!((0x00, 0x00, 0xff), pixel(&pixels, 4, 3, 3));
+        assert_eq!((0xff, 0xff, 0xff), pixel(&pixels, 4, 3, 0));
+    }
+
+    #[test]
+    fn test_input_caret() {
+        let mut style = ComputedStyle::new();
+        style.set_font_size(FontSize::Medium);
+        style.set_font_family(FontFamily::SansSerif);
+        let item = DisplayItem::Input {
+            input_type: "text".to_string(),
+            name: None,
+            placeholder: None,
+            value: Some("ab".to_string()),
+            caret: Some(1),
+            style: style.clone(),
+            layout_point: LayoutPoint::new(0, 0),
+            layout_size: LayoutSize::new(40, 10),
+        };
+        let pixels = rasterize(&[item], 40, 10);
+
+        let x = INPUT_PADDING + char_width(&style);
+        assert_eq!((0x00, 0x00, 0x00), pixel(&pixels, 40, x, 5));
+        assert_eq!((0xff, 0xff, 0xff), pixel(&pixels, 40, x + 1, 5));
+    }
+}
@@ -7,16 +7,20 @@
 //! https://www.w3.org/TR/css-syntax-3/#parsing
 
 use crate::browser::Browser;
+use crate::error::Error;
 use crate::renderer::css::token::CssToken;
 use crate::renderer::css::token::CssTokenizer;
 use crate::utils::console_warning;
+use alloc::boxed::Box;
 use alloc::format;
 use alloc::rc::Weak;
 use alloc::string::String;
 use alloc::string::ToString;
+use alloc::vec;
 use alloc::vec::Vec;
 use core::cell::RefCell;
 use core::iter::Peekable;
+use core::str::FromStr;
 
 // e.g.
 // div {
@@ -136,10 +140,52 @@ pub enum Selector {
     ClassSelector(String),
     /// https://www.w3.org/TR/selectors-4/#id-selectors
     IdSelector(String),
+    /// A selector followed by a pseudo-class, e.g. `li:first-child`.
+    /// https://www.w3.org/TR/selectors-4/#pseudo-classes
+    PseudoClassSelector(Box<Selector>, PseudoClass),
+    /// An attribute selector following an optional selector, e.g. `input[type="text"]` or
+    /// `[hidden]`.
+    /// https://www.w3.org/TR/selectors-4/#attribute-selectors
+    AttributeSelector(Option<Box<Selector>>, String, AttributeMatcher),
     /// This is an unofficial selector.
     UnknownSelector,
 }
 
+/// https://www.w3.org/TR/selectors-4/#structural-pseudos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PseudoClass {
+    /// https://www.w3.org/TR/selectors-4/#the-first-child-pseudo
+    FirstChild,
+    /// https://www.w3.org/TR/selectors-4/#the-last-child-pseudo
+    LastChild,
+}
+
+/// https://www.w3.org/TR/selectors-4/#attribute-representation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeMatcher {
+    /// `[attr]` matches if the attribute exists.
+    Exists,
+    /// `[attr="val"]` matches if the value is exactly "val".
+    Equals(String),
+    /// `[attr~="val"]` matches if one of the whitespace-separated words of the value is "val".
+    Includes(String),
+}
+
+impl FromStr for PseudoClass {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first-child" => Ok(Self::FirstChild),
+            "last-child" => Ok(Self::LastChild),
+            _ => Err(Error::UnexpectedInput(format!(
+                "pseudo-class {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 /// https://www.w3.org/TR/css-syntax-3/#declaration
 /// https://www.w3.org/TR/cssom-1/#the-cssstyledeclaration-interface
@@ -147,6 +193,9 @@ pub enum Selector {
 pub struct Declaration {
     pub property: String,
     pub value: ComponentValue,
+    /// The component values following `value`, e.g. `line-through` in
+    /// `text-decoration: underline line-through`.
+    pub rest: Vec<ComponentValue>,
 }
 
 impl Default for Declaration {
@@ -160,6 +209,7 @@ impl Declaration {
         Self {
             property: String::new(),
             value: ComponentValue::Ident(String::new()),
+            rest: Vec::new(),
         }
     }
 
@@ -170,6 +220,17 @@ impl Declaration {
     pub fn set_value(&mut self, value: ComponentValue) {
         self.value = value;
     }
+
+    pub fn push_value(&mut self, value: ComponentValue) {
+        self.rest.push(value);
+    }
+
+    /// Returns all component values of this declaration.
+    pub fn values(&self) -> Vec<ComponentValue> {
+        let mut values = vec![self.value.clone()];
+        values.extend(self.rest.iter().cloned());
+        values
+    }
 }
 
 /// https://www.w3.org/TR/css-syntax-3/#component-value
@@ -222,25 +283,34 @@ impl CssParser {
 
         match token {
             // TODO: support tag.class and tag#id
-            CssToken::HashToken(value) => Selector::IdSelector(value[1..].to_string()),
+            CssToken::HashToken(value) => {
+                let selector = Selector::IdSelector(value[1..].to_string());
+                let selector = self.consume_attribute_selectors(selector);
+                self.consume_pseudo_class(selector)
+            }
             CssToken::Delim(delim) => {
-                if delim == '.' {
-                    return Selector::ClassSelector(self.consume_ident());
+                if delim == '.' && matches!(self.t.peek(), Some(CssToken::Ident(_))) {
+                    let selector = Selector::ClassSelector(self.consume_ident());
+                    let selector = self.consume_attribute_selectors(selector);
+                    return self.consume_pseudo_class(selector);
                 }
-                panic!("Parse error: {:?} is an unexpected token.", token);
+                // TODO: support the universal selector and combinators such as '>'.
+                console_warning(&self.browser, format!("unexpected token {:?}", token));
+                Selector::UnknownSelector
             }
             CssToken::Ident(ident) => {
-                // TODO: fix this. Skip pseudo-classes such as :link and :visited
-                if self.t.peek() == Some(&CssToken::Colon) {
-                    while self.t.peek() != Some(&CssToken::OpenCurly) {
-                        self.t.next();
-                    }
-                }
-                Selector::TypeSelector(ident.to_string())
+                let selector = Selector::TypeSelector(ident.to_string());
+                let selector = self.consume_attribute_selectors(selector);
+                self.consume_pseudo_class(selector)
+            }
+            CssToken::OpenSquareBracket => {
+                let selector = self.consume_attribute_selector(None);
+                let selector = self.consume_attribute_selectors(selector);
+                self.consume_pseudo_class(selector)
             }
             CssToken::AtKeyword(_keyword) => {
                 // skip until "{" comes
-                while self.t.peek() != Some(&CssToken::OpenCurly) {
+                while !matches!(self.t.peek(), Some(CssToken::OpenCurly) | None) {
                     self.t.next();
                 }
                 Selector::UnknownSelector
@@ -253,6 +323,92 @@ impl CssParser {
         }
     }
 
+    /// Consumes attribute selectors following `selector` such as `input[type="text"]`.
+    fn consume_attribute_selectors(&mut self, mut selector: Selector) -> Selector {
+        while self.t.peek() == Some(&CssToken::OpenSquareBracket) {
+            // consume '['
+            self.t.next();
+            selector = self.consume_attribute_selector(Some(Box::new(selector)));
+        }
+        selector
+    }
+
+    /// Consumes the rest of an attribute selector after '['. An unsupported attribute selector
+    /// is skipped until ']' and never matches.
+    /// https://www.w3.org/TR/selectors-4/#attribute-selectors
+    fn consume_attribute_selector(&mut self, selector: Option<Box<Selector>>) -> Selector {
+        let name = match self.t.next() {
+            Some(CssToken::Ident(name)) => name,
+            token => return self.skip_attribute_selector(token),
+        };
+
+        let matcher = match self.t.next() {
+            Some(CssToken::CloseSquareBracket) => {
+                return Selector::AttributeSelector(selector, name, AttributeMatcher::Exists);
+            }
+            Some(CssToken::Delim('=')) => AttributeMatcher::Equals,
+            Some(CssToken::Delim('~')) => match self.t.next() {
+                Some(CssToken::Delim('=')) => AttributeMatcher::Includes,
+                token => return self.skip_attribute_selector(token),
+            },
+            token => return self.skip_attribute_selector(token),
+        };
+
+        let value = match self.t.next() {
+            Some(CssToken::StringToken(value)) | Some(CssToken::Ident(value)) => value,
+            token => return self.skip_attribute_selector(token),
+        };
+
+        match self.t.next() {
+            Some(CssToken::CloseSquareBracket) => {
+                Selector::AttributeSelector(selector, name, matcher(value))
+            }
+            token => self.skip_attribute_selector(token),
+        }
+    }
+
+    /// Skips the tokens of an attribute selector until ']' after an unexpected `token`.
+    fn skip_attribute_selector(&mut self, token: Option<CssToken>) -> Selector {
+        console_warning(
+            &self.browser,
+            format!("unexpected token {:?} in an attribute selector", token),
+        );
+        let mut token = token;
+        while !matches!(token, Some(CssToken::CloseSquareBracket) | None) {
+            token = self.t.next();
+        }
+        Selector::UnknownSelector
+    }
+
+    /// Consumes pseudo-classes following `selector` such as `li:first-child`.
+    /// https://www.w3.org/TR/selectors-4/#pseudo-classes
+    fn consume_pseudo_class(&mut self, selector: Selector) -> Selector {
+        if self.t.peek() != Some(&CssToken::Colon) {
+            return selector;
+        }
+        // consume ':'
+        self.t.next();
+
+        let pseudo_class = match self.t.peek() {
+            Some(CssToken::Ident(name)) => PseudoClass::from_str(name).ok(),
+            _ => None,
+        };
+        match pseudo_class {
+            Some(pseudo_class) => {
+                self.t.next();
+                let selector = Selector::PseudoClassSelector(Box::new(selector), pseudo_class);
+                self.consume_pseudo_class(selector)
+            }
+            None => {
+                // TODO: fix this. Skip other pseudo-classes such as :link and :visited
+                while !matches!(self.t.peek(), Some(CssToken::OpenCurly) | None) {
+                    self.t.next();
+                }
+                selector
+            }
+        }
+    }
+
     /// https://www.w3.org/TR/css-syntax-3/#consume-a-declaration
     fn consume_declaration(&mut self) -> Option<Declaration> {
         // Create a new declaration with its name set to the value of the current input token.
@@ -270,8 +426,16 @@ impl CssParser {
 
         // "4. As long as the next input token is anything other than an <EOF-token>, consume a
         // component value and append it to the declaration’s value."
-        // TODO: support multiple values in one declaration.
         declaration.set_value(self.consume_component_value());
+        while let Some(token) = self.t.peek() {
+            match token {
+                CssToken::SemiColon | CssToken::CloseCurly => break,
+                _ => {
+                    let value = self.consume_component_value();
+                    declaration.push_value(value);
+                }
+            }
+        }
 
         Some(declaration)
     }
@@ -414,6 +578,44 @@ impl CssParser {
         }
     }
 
+    /// Parses all the tokens as one compound selector, e.g. for `Element::matches`. Returns None
+    /// if the tokens aren't a compound selector which is supported, such as a selector list, a
+    /// complex selector with combinators or an unknown pseudo-class.
+    /// https://drafts.csswg.org/selectors-4/#parse-selector
+    /// https://www.w3.org/TR/selectors-4/#compound
+    pub fn parse_selector(&mut self) -> Option<Selector> {
+        let mut selector = match self.t.next()? {
+            CssToken::Ident(name) => Selector::TypeSelector(name),
+            CssToken::HashToken(value) => Selector::IdSelector(value[1..].to_string()),
+            CssToken::Delim('.') => match self.t.next()? {
+                CssToken::Ident(name) => Selector::ClassSelector(name),
+                _ => return None,
+            },
+            CssToken::OpenSquareBracket => self.consume_attribute_selector(None),
+            _ => return None,
+        };
+
+        loop {
+            if selector == Selector::UnknownSelector {
+                return None;
+            }
+            match self.t.next() {
+                None => return Some(selector),
+                Some(CssToken::OpenSquareBracket) => {
+                    selector = self.consume_attribute_selector(Some(Box::new(selector)));
+                }
+                Some(CssToken::Colon) => {
+                    let pseudo_class = match self.t.next()? {
+                        CssToken::Ident(name) => PseudoClass::from_str(&name).ok()?,
+                        _ => return None,
+                    };
+                    selector = Selector::PseudoClassSelector(Box::new(selector), pseudo_class);
+                }
+                Some(_) => return None,
+            }
+        }
+    }
+
     /// https://www.w3.org/TR/css-syntax-3/#parse-stylesheet
     pub fn parse_stylesheet(&mut self) -> StyleSheet {
         // 1. Create a new stylesheet.
@@ -517,6 +719,72 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pseudo_class_selector() {
+        let browser = Browser::new();
+        let style = "li:first-child { color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(Rc::downgrade(&browser), t).parse_stylesheet();
+
+        let mut rule = QualifiedRule::default();
+        rule.set_selector(Selector::PseudoClassSelector(
+            Box::new(Selector::TypeSelector("li".to_string())),
+            PseudoClass::FirstChild,
+        ));
+        let mut declaration = Declaration::default();
+        declaration.set_property("color".to_string());
+        declaration.set_value(ComponentValue::Ident("red".to_string()));
+        rule.set_declarations(vec![declaration]);
+
+        assert_eq!(vec![rule], cssom.rules);
+    }
+
+    #[test]
+    fn test_attribute_selector() {
+        let browser = Browser::new();
+        let style =
+            "input[type=\"text\"] { color: red; } [hidden] { color: red; } p[class~=\"a\"] { color: red; }"
+                .to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(Rc::downgrade(&browser), t).parse_stylesheet();
+
+        let selectors: Vec<Selector> = cssom.rules.into_iter().map(|r| r.selector).collect();
+        assert_eq!(
+            vec![
+                Selector::AttributeSelector(
+                    Some(Box::new(Selector::TypeSelector("input".to_string()))),
+                    "type".to_string(),
+                    AttributeMatcher::Equals("text".to_string()),
+                ),
+                Selector::AttributeSelector(None, "hidden".to_string(), AttributeMatcher::Exists),
+                Selector::AttributeSelector(
+                    Some(Box::new(Selector::TypeSelector("p".to_string()))),
+                    "class".to_string(),
+                    AttributeMatcher::Includes("a".to_string()),
+                ),
+            ],
+            selectors
+        );
+    }
+
+    #[test]
+    fn test_unsupported_selectors() {
+        let browser = Browser::new();
+        let style = "* { color: red; } div > p { color: red; } .a { color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(Rc::downgrade(&browser), t).parse_stylesheet();
+
+        let selectors: Vec<Selector> = cssom.rules.into_iter().map(|r| r.selector).collect();
+        assert_eq!(
+            vec![
+                Selector::UnknownSelector,
+                Selector::TypeSelector("p".to_string()),
+                Selector::ClassSelector("a".to_string()),
+            ],
+            selectors
+        );
+    }
+
     #[test]
     fn test_font_size() {
         let browser = Browser::new();
@@ -598,4 +866,49 @@ mod tests {
             i += 1;
         }
     }
+
+    #[test]
+    fn test_comment() {
+        let browser = Browser::new();
+        let style = "p { color: red } /* note */ a { color: blue }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(Rc::downgrade(&browser), t).parse_stylesheet();
+
+        let mut rule1 = QualifiedRule::default();
+        rule1.set_selector(Selector::TypeSelector("p".to_string()));
+        let mut declaration1 = Declaration::default();
+        declaration1.set_property("color".to_string());
+        declaration1.set_value(ComponentValue::Ident("red".to_string()));
+        rule1.set_declarations(vec![declaration1]);
+
+        let mut rule2 = QualifiedRule::default();
+        rule2.set_selector(Selector::TypeSelector("a".to_string()));
+        let mut declaration2 = Declaration::default();
+        declaration2.set_property("color".to_string());
+        declaration2.set_value(ComponentValue::Ident("blue".to_string()));
+        rule2.set_declarations(vec![declaration2]);
+
+        assert_eq!(vec![rule1, rule2], cssom.rules);
+    }
+
+    #[test]
+    fn test_multiple_values() {
+        let browser = Browser::new();
+        let style = "p { text-decoration: underline line-through; color: red }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(Rc::downgrade(&browser), t).parse_stylesheet();
+
+        let mut rule = QualifiedRule::default();
+        rule.set_selector(Selector::TypeSelector("p".to_string()));
+        let mut declaration1 = Declaration::default();
+        declaration1.set_property("text-decoration".to_string());
+        declaration1.set_value(ComponentValue::Ident("underline".to_string()));
+        declaration1.push_value(ComponentValue::Ident("line-through".to_string()));
+        let mut declaration2 = Declaration::default();
+        declaration2.set_property("color".to_string());
+        declaration2.set_value(ComponentValue::Ident("red".to_string()));
+        rule.set_declarations(vec![declaration1, declaration2]);
+
+        assert_eq!(vec![rule], cssom.rules);
+    }
 }
@@ -1,2 +1,3 @@
 pub mod cssom;
 pub mod token;
+pub mod user_agent;
@@ -17,6 +17,8 @@ pub enum CssToken {
     Delim(char),
     /// https://www.w3.org/TR/css-syntax-3/#typedef-number-token
     Number(f64),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-percentage-token
+    Percentage(f64),
     /// https://www.w3.org/TR/css-syntax-3/#typedef-colon-token
     Colon,
     /// https://www.w3.org/TR/css-syntax-3/#typedef-semicolon-token
@@ -25,6 +27,10 @@ pub enum CssToken {
     OpenParenthesis,
     /// https://www.w3.org/TR/css-syntax-3/#tokendef-close-paren
     CloseParenthesis,
+    /// https://www.w3.org/TR/css-syntax-3/#tokendef-open-square
+    OpenSquareBracket,
+    /// https://www.w3.org/TR/css-syntax-3/#tokendef-close-square
+    CloseSquareBracket,
     /// https://www.w3.org/TR/css-syntax-3/#tokendef-open-curly
     OpenCurly,
     /// https://www.w3.org/TR/css-syntax-3/#tokendef-close-curly
@@ -58,6 +64,10 @@ impl CssTokenizer {
 
         loop {
             self.pos += 1;
+            // An ident can end the input, e.g. in a selector string.
+            if self.pos >= self.input.len() {
+                break;
+            }
             let c = self.input[self.pos];
             match c {
                 'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => {
@@ -90,6 +100,26 @@ impl CssTokenizer {
         s
     }
 
+    /// Skips a comment starting at the current position. An unterminated comment consumes the
+    /// rest of the input.
+    /// https://www.w3.org/TR/css-syntax-3/#consume-comment
+    fn consume_comment(&mut self) {
+        // skip "/*"
+        self.pos += 2;
+
+        while self.pos < self.input.len() {
+            if self.input[self.pos] == '*'
+                && self.pos + 1 < self.input.len()
+                && self.input[self.pos + 1] == '/'
+            {
+                // skip "*/"
+                self.pos += 2;
+                return;
+            }
+            self.pos += 1;
+        }
+    }
+
     /// https://www.w3.org/TR/css-syntax-3/#consume-number
     /// https://www.w3.org/TR/css-syntax-3/#consume-a-numeric-token
     fn consume_numeric_token(&mut self) -> f64 {
@@ -152,9 +182,25 @@ impl Iterator for CssTokenizer {
                     self.pos -= 1;
                     CssToken::HashToken(value)
                 }
+                // "If the next two input code point are U+002F SOLIDUS (/) followed by a U+002A
+                // ASTERISK (*), consume them and all following code points up to and including the
+                // first U+002A ASTERISK (*) followed by a U+002F SOLIDUS (/), or up to an EOF code
+                // point."
+                // https://www.w3.org/TR/css-syntax-3/#consume-comment
+                '/' => {
+                    if self.pos + 1 < self.input.len() && self.input[self.pos + 1] == '*' {
+                        self.consume_comment();
+                        continue;
+                    }
+                    CssToken::Delim('/')
+                }
                 '(' => CssToken::OpenParenthesis,
                 ')' => CssToken::CloseParenthesis,
+                '[' => CssToken::OpenSquareBracket,
+                ']' => CssToken::CloseSquareBracket,
                 ',' => CssToken::Delim(','),
+                '=' => CssToken::Delim('='),
+                '~' => CssToken::Delim('~'),
                 // TODO: support minus number with hyphen.
                 // "If the input stream starts with a number, reconsume the current input code
                 // point, consume a numeric token, and return it."
@@ -175,7 +221,8 @@ impl Iterator for CssTokenizer {
                     // If the next 3 input code points would start an ident sequence, consume an
                     // ident sequence, create an <at-keyword-token> with its value set to the
                     // returned value, and return it.
-                    if self.input[self.pos + 1].is_ascii_alphabetic()
+                    if self.pos + 3 < self.input.len()
+                        && self.input[self.pos + 1].is_ascii_alphabetic()
                         && self.input[self.pos + 2].is_alphanumeric()
                         && self.input[self.pos + 3].is_alphanumeric()
                     {
@@ -193,9 +240,17 @@ impl Iterator for CssTokenizer {
                 // digit
                 // Reconsume the current input code point, consume a numeric token, and return it.
                 '0'..='9' => {
-                    let t = CssToken::Number(self.consume_numeric_token());
-                    self.pos -= 1;
-                    t
+                    let num = self.consume_numeric_token();
+                    // "Otherwise, if the next input code point is U+0025 PERCENTAGE SIGN (%),
+                    // consume it. Create a <percentage-token> with the same value as number, and
+                    // return it."
+                    // https://www.w3.org/TR/css-syntax-3/#consume-a-numeric-token
+                    if self.pos < self.input.len() && self.input[self.pos] == '%' {
+                        CssToken::Percentage(num)
+                    } else {
+                        self.pos -= 1;
+                        CssToken::Number(num)
+                    }
                 }
                 // ident-start code point
                 // Reconsume the current input code point, consume an ident-like token, and return
@@ -212,17 +267,9 @@ impl Iterator for CssTokenizer {
                     self.pos += 1;
                     continue;
                 }
-                _ => {
-                    /*
-                    console_error(
-                        self.browser.clone(),
-                        format!("char {} is not supported yet", c),
-                    );
-                    self.pos += 1;
-                    continue;
-                    */
-                    panic!("char {} is not supported yet", c);
-                }
+                // "anything else: Return a <delim-token> with its value set to the current input
+                // code point."
+                _ => CssToken::Delim(c),
             };
 
             self.pos += 1;
@@ -350,4 +397,42 @@ mod tests {
         }
         assert!(t.next().is_none());
     }
+
+    #[test]
+    fn test_percentage() {
+        let style = "div { max-width: 50%; }".to_string();
+        let mut t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Ident("div".to_string()),
+            CssToken::OpenCurly,
+            CssToken::Ident("max-width".to_string()),
+            CssToken::Colon,
+            CssToken::Percentage(50.0),
+            CssToken::SemiColon,
+            CssToken::CloseCurly,
+        ];
+        for e in expected {
+            assert_eq!(Some(e.clone()), t.next());
+        }
+        assert!(t.next().is_none());
+    }
+
+    #[test]
+    fn test_comment() {
+        let style = "/* a\ncomment */p { color: /* inside */ red; } /* unterminated".to_string();
+        let mut t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Ident("p".to_string()),
+            CssToken::OpenCurly,
+            CssToken::Ident("color".to_string()),
+            CssToken::Colon,
+            CssToken::Ident("red".to_string()),
+            CssToken::SemiColon,
+            CssToken::CloseCurly,
+        ];
+        for e in expected {
+            assert_eq!(Some(e.clone()), t.next());
+        }
+        assert!(t.next().is_none());
+    }
 }
@@ -0,0 +1,133 @@
+//! The default style sheet of this browser.
+//! https://www.w3.org/TR/css-cascade-4/#cascade-origin-ua
+//! https://html.spec.whatwg.org/multipage/rendering.html#rendering
+//!
+//! https://source.chromium.org/chromium/chromium/src/+/main:third_party/blink/renderer/core/html/resources/html.css
+
+/// User-agent declarations are applied before author declarations, so a page can override any
+/// of them. `Browser::set_user_agent_stylesheet` replaces this string.
+pub static DEFAULT_USER_AGENT_STYLESHEET: &str = r#"
+body {
+  display: block;
+}
+script {
+  display: none;
+}
+style {
+  display: none;
+}
+h1 {
+  display: block;
+  font-size: 24px;
+  font-weight: bold;
+}
+h2 {
+  display: block;
+  font-size: 18px;
+  font-weight: bold;
+}
+p {
+  display: block;
+}
+pre {
+  display: block;
+  font-family: monospace;
+  white-space: pre;
+}
+ul {
+  display: block;
+}
+li {
+  display: block;
+}
+dl {
+  display: block;
+}
+dt {
+  display: block;
+}
+dd {
+  display: block;
+  margin-left: 40px;
+}
+div {
+  display: block;
+}
+main {
+  display: block;
+}
+article {
+  display: block;
+}
+section {
+  display: block;
+}
+nav {
+  display: block;
+}
+header {
+  display: block;
+}
+footer {
+  display: block;
+}
+form {
+  display: block;
+}
+center {
+  display: block;
+  text-align: center;
+}
+table {
+  display: table;
+}
+thead {
+  display: block;
+}
+tbody {
+  display: block;
+}
+tfoot {
+  display: block;
+}
+tr {
+  display: table-row;
+}
+td {
+  display: table-cell;
+  border: 1px solid lightgray;
+}
+th {
+  display: table-cell;
+  border: 1px solid lightgray;
+  font-weight: bold;
+  text-align: center;
+}
+a {
+  text-decoration: underline;
+}
+strong {
+  font-weight: bold;
+}
+b {
+  font-weight: bold;
+}
+em {
+  font-style: italic;
+}
+i {
+  font-style: italic;
+}
+code {
+  font-family: monospace;
+}
+s {
+  text-decoration: line-through;
+}
+strike {
+  text-decoration: line-through;
+}
+del {
+  text-decoration: line-through;
+}
+"#;
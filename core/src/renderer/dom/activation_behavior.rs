@@ -15,7 +15,7 @@ pub type ActivationBehavior = fn(node: Rc<RefCell<Node>>, e: Event);
 
 pub fn get_activation_behavior(node_kind: &NodeKind) -> Option<ActivationBehavior> {
     match node_kind {
-        NodeKind::Document | NodeKind::Text(_) => None,
+        NodeKind::Document | NodeKind::Text(_) | NodeKind::Comment(_) => None,
         NodeKind::Element(e) => {
             match e.kind() {
                 ElementKind::A => {
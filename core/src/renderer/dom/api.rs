@@ -8,6 +8,17 @@ use alloc::string::ToString;
 use alloc::vec::Vec;
 use core::cell::RefCell;
 
+/// Returns the first child of `node`, or None if `node` is a template element. The contents of a
+/// template are inert: their scripts don't run, their styles don't apply and DOM lookups don't
+/// find them.
+/// https://html.spec.whatwg.org/multipage/scripting.html#the-template-element
+pub fn non_inert_first_child(node: &Rc<RefCell<Node>>) -> Option<Rc<RefCell<Node>>> {
+    if node.borrow().element_kind() == Some(ElementKind::Template) {
+        return None;
+    }
+    node.borrow().first_child()
+}
+
 pub fn get_element_by_id(
     node: Option<Rc<RefCell<Node>>>,
     id_name: &String,
@@ -22,7 +33,7 @@ pub fn get_element_by_id(
                 }
             }
 
-            let result1 = get_element_by_id(n.borrow().first_child(), id_name);
+            let result1 = get_element_by_id(non_inert_first_child(&n), id_name);
             let result2 = get_element_by_id(n.borrow().next_sibling(), id_name);
             if result1.is_none() {
                 return result2;
@@ -34,6 +45,85 @@ pub fn get_element_by_id(
     }
 }
 
+/// An option in the list of options of a select element.
+/// https://html.spec.whatwg.org/multipage/form-elements.html#concept-select-option-list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectOption {
+    value: String,
+    text: String,
+    group: Option<String>,
+}
+
+impl SelectOption {
+    /// https://html.spec.whatwg.org/multipage/form-elements.html#concept-option-value
+    pub fn value(&self) -> String {
+        self.value.clone()
+    }
+
+    /// https://html.spec.whatwg.org/multipage/form-elements.html#dom-option-text
+    pub fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    /// Returns the label of the optgroup element that the option is in, if any.
+    pub fn group(&self) -> Option<String> {
+        self.group.clone()
+    }
+}
+
+// TODO: a select element is rendered as the text of its options, not as a control yet. Once it
+// is, wasabi should cycle through these options on a click, which skips optgroup labels.
+/// Returns the selectable options of `select` in tree order. An optgroup label is not
+/// selectable, so it's kept only as the group of the options in it.
+pub fn get_select_options(select: &Rc<RefCell<Node>>) -> Vec<SelectOption> {
+    let mut options = Vec::new();
+    collect_select_options(select.borrow().first_child(), None, &mut options);
+    options
+}
+
+fn collect_select_options(
+    node: Option<Rc<RefCell<Node>>>,
+    group: Option<String>,
+    options: &mut Vec<SelectOption>,
+) {
+    let mut child = node;
+    while let Some(n) = child {
+        if let Some(e) = n.borrow().get_element() {
+            match e.kind() {
+                ElementKind::Option => {
+                    let mut text = String::new();
+                    collect_text_content(n.borrow().first_child(), &mut text);
+                    // "stripping and collapsing ASCII whitespace"
+                    let text = text.split_ascii_whitespace().collect::<Vec<_>>().join(" ");
+                    options.push(SelectOption {
+                        value: e.get_attribute("value").unwrap_or_else(|| text.clone()),
+                        text,
+                        group: group.clone(),
+                    });
+                }
+                ElementKind::Optgroup => collect_select_options(
+                    n.borrow().first_child(),
+                    Some(e.get_attribute("label").unwrap_or_default()),
+                    options,
+                ),
+                _ => {}
+            }
+        }
+        child = n.borrow().next_sibling();
+    }
+}
+
+/// Appends the text of `node`, its descendants and its following siblings to `text`.
+fn collect_text_content(node: Option<Rc<RefCell<Node>>>, text: &mut String) {
+    if let Some(n) = node {
+        if let NodeKind::Text(s) = n.borrow().kind() {
+            text.push_str(&s);
+        }
+        collect_text_content(n.borrow().first_child(), text);
+        collect_text_content(n.borrow().next_sibling(), text);
+    }
+}
+
 // TODO: return an array of Node instead of one Node.
 pub fn get_target_element_node(
     node: Option<Rc<RefCell<Node>>>,
@@ -57,34 +147,107 @@ pub fn get_target_element_node(
     }
 }
 
+/// Returns the `content` attribute of the first `<meta name="viewport">` element in tree order.
+/// https://drafts.csswg.org/css-viewport/#viewport-meta
+pub fn get_viewport_content(node: Option<Rc<RefCell<Node>>>) -> Option<String> {
+    let n = node?;
+    if let NodeKind::Element(e) = n.borrow().kind() {
+        let is_viewport = e
+            .get_attribute("name")
+            .is_some_and(|name| name.eq_ignore_ascii_case("viewport"));
+        if e.kind() == ElementKind::Meta && is_viewport {
+            if let Some(content) = e.get_attribute("content") {
+                return Some(content);
+            }
+        }
+    }
+
+    let first_child = non_inert_first_child(&n);
+    let next_sibling = n.borrow().next_sibling();
+    get_viewport_content(first_child).or_else(|| get_viewport_content(next_sibling))
+}
+
+/// Returns the contents of all style elements, wherever they appear in the document, joined
+/// in tree order.
 pub fn get_style_content(root: Rc<RefCell<Node>>) -> String {
-    let style_node = match get_target_element_node(Some(root), ElementKind::Style) {
-        Some(node) => node,
-        None => return "".to_string(),
-    };
-    let text_node = match style_node.borrow().first_child() {
-        Some(node) => node,
-        None => return "".to_string(),
-    };
-    let content = match &text_node.borrow().kind() {
-        NodeKind::Text(ref s) => s.clone(),
-        _ => "".to_string(),
+    get_style_contents(root).join("\n")
+}
+
+/// Returns the contents of all style elements in tree order, so that a later
+/// stylesheet wins over an earlier one in the cascade.
+pub fn get_style_contents(root: Rc<RefCell<Node>>) -> Vec<String> {
+    let mut contents = Vec::new();
+    collect_style_contents(Some(root), &mut contents);
+    contents
+}
+
+fn collect_style_contents(node: Option<Rc<RefCell<Node>>>, contents: &mut Vec<String>) {
+    let n = match node {
+        Some(n) => n,
+        None => return,
     };
-    content
+
+    if let NodeKind::Element(e) = n.borrow().kind() {
+        if e.kind() == ElementKind::Style {
+            if let Some(text_node) = n.borrow().first_child() {
+                if let NodeKind::Text(ref s) = text_node.borrow().kind() {
+                    contents.push(s.clone());
+                }
+            }
+        }
+    }
+
+    collect_style_contents(non_inert_first_child(&n), contents);
+    collect_style_contents(n.borrow().next_sibling(), contents);
 }
 
+/// Returns the contents of all scripts that this browser executes, joined in tree order. A
+/// module script is executed like a classic script, and a data block such as
+/// `type="application/json"` is skipped.
+/// https://html.spec.whatwg.org/multipage/scripting.html#prepare-the-script-element
 pub fn get_js_content(root: Rc<RefCell<Node>>) -> String {
-    let js_node = match get_target_element_node(Some(root), ElementKind::Script) {
-        Some(node) => node,
-        None => return "".to_string(),
-    };
-    let text_node = match js_node.borrow().first_child() {
-        Some(node) => node,
-        None => return "".to_string(),
+    let mut contents = Vec::new();
+    collect_js_contents(Some(root), &mut contents);
+    contents.join("\n")
+}
+
+fn collect_js_contents(node: Option<Rc<RefCell<Node>>>, contents: &mut Vec<String>) {
+    let n = match node {
+        Some(n) => n,
+        None => return,
     };
-    let content = match &text_node.borrow().kind() {
-        NodeKind::Text(ref s) => s.clone(),
-        _ => "".to_string(),
+
+    if let NodeKind::Element(e) = n.borrow().kind() {
+        if e.kind() == ElementKind::Script && is_executable_script_type(e.get_attribute("type")) {
+            if let Some(text_node) = n.borrow().first_child() {
+                if let NodeKind::Text(ref s) = text_node.borrow().kind() {
+                    contents.push(s.clone());
+                }
+            }
+        }
+    }
+
+    collect_js_contents(non_inert_first_child(&n), contents);
+    collect_js_contents(n.borrow().next_sibling(), contents);
+}
+
+/// Returns true if a script with the `type` attribute is a classic or module script. Other
+/// types are data blocks, which are not executed.
+/// https://html.spec.whatwg.org/multipage/scripting.html#attr-script-type
+fn is_executable_script_type(script_type: Option<String>) -> bool {
+    let script_type = match script_type {
+        Some(t) => t.trim().to_ascii_lowercase(),
+        None => return true,
     };
-    content
+    // https://mimesniff.spec.whatwg.org/#javascript-mime-type
+    matches!(
+        script_type.as_str(),
+        "" | "module"
+            | "text/javascript"
+            | "application/javascript"
+            | "application/ecmascript"
+            | "application/x-javascript"
+            | "text/ecmascript"
+            | "text/jscript"
+    )
 }
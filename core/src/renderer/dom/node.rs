@@ -3,6 +3,11 @@
 //!
 //! https://source.chromium.org/chromium/chromium/src/+/main:third_party/blink/renderer/core/dom/node.h
 
+use crate::renderer::css::cssom::AttributeMatcher;
+use crate::renderer::css::cssom::CssParser;
+use crate::renderer::css::cssom::PseudoClass;
+use crate::renderer::css::cssom::Selector;
+use crate::renderer::css::token::CssTokenizer;
 use crate::renderer::dom::activation_behavior::get_activation_behavior;
 use crate::renderer::dom::activation_behavior::ActivationBehavior;
 use crate::renderer::dom::event::Event;
@@ -14,6 +19,7 @@ use crate::renderer::html::attribute::Attribute;
 use alloc::format;
 use alloc::rc::{Rc, Weak};
 use alloc::string::String;
+use alloc::string::ToString;
 use alloc::vec::Vec;
 use core::cell::RefCell;
 use core::fmt::Display;
@@ -93,14 +99,14 @@ impl Node {
 
     pub fn get_element(&self) -> Option<Element> {
         match self.kind {
-            NodeKind::Document | NodeKind::Text(_) => None,
+            NodeKind::Document | NodeKind::Text(_) | NodeKind::Comment(_) => None,
             NodeKind::Element(ref e) => Some(e.clone()),
         }
     }
 
     pub fn element_kind(&self) -> Option<ElementKind> {
         match self.kind {
-            NodeKind::Document | NodeKind::Text(_) => None,
+            NodeKind::Document | NodeKind::Text(_) | NodeKind::Comment(_) => None,
             NodeKind::Element(ref e) => Some(e.kind()),
         }
     }
@@ -144,6 +150,46 @@ impl Node {
     pub fn next_sibling(&self) -> Option<Rc<RefCell<Node>>> {
         self.next_sibling.as_ref().cloned()
     }
+
+    /// https://dom.spec.whatwg.org/#dom-nondocumenttypechildnode-previouselementsibling
+    pub fn previous_element_sibling(&self) -> Option<Rc<RefCell<Node>>> {
+        let mut current = self.previous_sibling().upgrade();
+        while let Some(node) = current {
+            if matches!(node.borrow().kind(), NodeKind::Element(_)) {
+                return Some(node);
+            }
+            current = node.borrow().previous_sibling().upgrade();
+        }
+        None
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-nondocumenttypechildnode-nextelementsibling
+    pub fn next_element_sibling(&self) -> Option<Rc<RefCell<Node>>> {
+        let mut current = self.next_sibling();
+        while let Some(node) = current {
+            if matches!(node.borrow().kind(), NodeKind::Element(_)) {
+                return Some(node);
+            }
+            current = node.borrow().next_sibling();
+        }
+        None
+    }
+
+    /// Returns the first inclusive ancestor of `node` that matches `selector`. Only a simple
+    /// selector such as `a`, `.class` or `#id` is supported.
+    /// https://dom.spec.whatwg.org/#dom-element-closest
+    pub fn closest(node: &Rc<RefCell<Node>>, selector: &str) -> Option<Rc<RefCell<Node>>> {
+        let mut current = Some(node.clone());
+        while let Some(n) = current {
+            if let NodeKind::Element(ref e) = n.borrow().kind {
+                if e.matches_simple_selector(selector) {
+                    return Some(n.clone());
+                }
+            }
+            current = n.borrow().parent().upgrade();
+        }
+        None
+    }
 }
 
 /// https://dom.spec.whatwg.org/#interface-eventtarget
@@ -221,6 +267,8 @@ pub enum NodeKind {
     Element(Element),
     /// https://dom.spec.whatwg.org/#interface-text
     Text(String),
+    /// https://dom.spec.whatwg.org/#interface-comment
+    Comment(String),
 }
 
 impl PartialEq for NodeKind {
@@ -229,6 +277,7 @@ impl PartialEq for NodeKind {
             (Self::Document, Self::Document) => true,
             (Self::Element(e1), Self::Element(e2)) => e1.kind == e2.kind,
             (Self::Text(t1), Self::Text(t2)) => t1 == t2,
+            (Self::Comment(c1), Self::Comment(c2)) => c1 == c2,
             _ => false,
         }
     }
@@ -238,26 +287,49 @@ impl PartialEq for NodeKind {
 #[derive(Debug, Clone)]
 pub struct Element {
     kind: ElementKind,
+    /// The lowercase tag name. It's kept to serialize and match an unknown element.
+    local_name: String,
     attributes: Vec<Attribute>,
     /// Dynamic value for form elements (e.g., input)
     dynamic_value: Rc<RefCell<Option<String>>>,
+    /// Dynamic checkedness for checkbox and radio inputs. None until it's changed.
+    dynamic_checked: Rc<RefCell<Option<bool>>>,
+    /// The caret position in characters of the value while the element has focus.
+    caret: Rc<RefCell<Option<usize>>>,
 }
 
 impl PartialEq for Element {
     fn eq(&self, other: &Self) -> bool {
-        self.kind == other.kind && self.attributes == other.attributes
+        self.kind == other.kind
+            && self.local_name == other.local_name
+            && self.attributes == other.attributes
     }
 }
 
 impl Eq for Element {}
 
 impl Element {
+    /// Creates an element whose attributes keep the source order. When an attribute name is
+    /// duplicated, only the first one is kept.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#attribute-name-state
     pub fn new(element_name: &str, attributes: Vec<Attribute>) -> Self {
+        let mut unique_attributes: Vec<Attribute> = Vec::new();
+        for attr in attributes {
+            if !unique_attributes.iter().any(|a| a.name() == attr.name()) {
+                unique_attributes.push(attr);
+            }
+        }
+
+        // An unsupported element is kept in the tree with its tag name so that its content is
+        // still rendered.
+        let local_name = element_name.to_ascii_lowercase();
         Self {
-            kind: ElementKind::from_str(element_name)
-                .expect("failed to convert string to ElementKind"),
-            attributes,
+            kind: ElementKind::from_str(&local_name).unwrap_or(ElementKind::Unknown),
+            local_name,
+            attributes: unique_attributes,
             dynamic_value: Rc::new(RefCell::new(None)),
+            dynamic_checked: Rc::new(RefCell::new(None)),
+            caret: Rc::new(RefCell::new(None)),
         }
     }
 
@@ -265,6 +337,22 @@ impl Element {
         self.kind
     }
 
+    /// https://dom.spec.whatwg.org/#concept-element-local-name
+    pub(crate) fn local_name(&self) -> &str {
+        &self.local_name
+    }
+
+    /// Returns the tag name in lowercase, e.g. "div". An unknown element returns the name it was
+    /// created with.
+    /// https://dom.spec.whatwg.org/#dom-element-tagname
+    pub fn tag_name(&self) -> String {
+        match self.kind {
+            ElementKind::Unknown => self.local_name.clone(),
+            kind => kind.to_string(),
+        }
+    }
+
+    /// Returns the attributes in the source order.
     pub fn attributes(&self) -> Vec<Attribute> {
         self.attributes.clone()
     }
@@ -279,12 +367,124 @@ impl Element {
         None
     }
 
+    /// Returns true if this element matches a simple selector: a type selector (`a`), a class
+    /// selector (`.class`) or an ID selector (`#id`).
+    fn matches_simple_selector(&self, selector: &str) -> bool {
+        if let Some(class_name) = selector.strip_prefix('.') {
+            return self
+                .get_attribute("class")
+                .is_some_and(|classes| classes.split_ascii_whitespace().any(|c| c == class_name));
+        }
+        if let Some(id) = selector.strip_prefix('#') {
+            return self.get_attribute("id").as_deref() == Some(id);
+        }
+        self.local_name.eq_ignore_ascii_case(selector)
+    }
+
+    /// Returns true if this element matches `selector`, e.g. `input[type="text"]` or
+    /// `li:first-child`. `node` is the node of this element, which gives the context such as
+    /// siblings. Only one compound selector is supported, and any other selector matches nothing.
+    /// https://dom.spec.whatwg.org/#dom-element-matches
+    pub fn matches(&self, selector: &str, node: &Rc<RefCell<Node>>) -> bool {
+        let t = CssTokenizer::new(selector.to_string());
+        CssParser::new(Weak::new(), t)
+            .parse_selector()
+            .is_some_and(|selector| self.matches_selector(&selector, node))
+    }
+
+    /// Returns true if this element matches a parsed `selector`. `node` is the node of this
+    /// element.
+    /// https://www.w3.org/TR/selectors-4/#match-against-element
+    pub fn matches_selector(&self, selector: &Selector, node: &Rc<RefCell<Node>>) -> bool {
+        match selector {
+            Selector::TypeSelector(type_name) => self.local_name == *type_name,
+            Selector::ClassSelector(class_name) => {
+                // The class attribute is a set of space-separated tokens.
+                // https://html.spec.whatwg.org/multipage/dom.html#classes
+                self.get_attribute("class").is_some_and(|classes| {
+                    classes.split_ascii_whitespace().any(|c| c == class_name)
+                })
+            }
+            Selector::IdSelector(id_name) => self.get_attribute("id").as_ref() == Some(id_name),
+            Selector::PseudoClassSelector(selector, pseudo_class) => {
+                if !self.matches_selector(selector, node) {
+                    return false;
+                }
+                match pseudo_class {
+                    PseudoClass::FirstChild => node.borrow().previous_element_sibling().is_none(),
+                    PseudoClass::LastChild => node.borrow().next_element_sibling().is_none(),
+                }
+            }
+            Selector::AttributeSelector(selector, name, matcher) => {
+                if let Some(selector) = selector {
+                    if !self.matches_selector(selector, node) {
+                        return false;
+                    }
+                }
+                let value = match self.get_attribute(name) {
+                    Some(value) => value,
+                    None => return false,
+                };
+                match matcher {
+                    AttributeMatcher::Exists => true,
+                    AttributeMatcher::Equals(expected) => value == *expected,
+                    AttributeMatcher::Includes(word) => {
+                        value.split_ascii_whitespace().any(|w| w == word)
+                    }
+                }
+            }
+            Selector::UnknownSelector => false,
+        }
+    }
+
+    /// Sets the value of an attribute `name`. A new attribute is appended after the existing
+    /// ones. Returns true if the value is changed.
+    /// https://dom.spec.whatwg.org/#dom-element-setattribute
+    pub fn set_attribute(&mut self, name: &str, value: &str) -> bool {
+        if self.get_attribute(name).as_deref() == Some(value) {
+            return false;
+        }
+
+        let mut attr = Attribute::new();
+        for c in name.chars() {
+            attr.add_char(c, true);
+        }
+        for c in value.chars() {
+            attr.add_char(c, false);
+        }
+
+        match self.attributes.iter().position(|a| a.name() == name) {
+            Some(i) => self.attributes[i] = attr,
+            None => self.attributes.push(attr),
+        }
+        true
+    }
+
+    /// Returns the link types in the `rel` attribute. Link types are ASCII case-insensitive, so
+    /// they are lowercased, and duplicates are removed.
+    /// https://html.spec.whatwg.org/multipage/links.html#attr-hyperlink-rel
+    pub fn rel_tokens(&self) -> Vec<String> {
+        let mut tokens: Vec<String> = Vec::new();
+        if let Some(rel) = self.get_attribute("rel") {
+            for token in rel.split_ascii_whitespace() {
+                let token = token.to_ascii_lowercase();
+                if !tokens.contains(&token) {
+                    tokens.push(token);
+                }
+            }
+        }
+        tokens
+    }
+
     /// Sets the dynamic value for form elements
     pub fn set_value(&self, value: String) {
         *self.dynamic_value.borrow_mut() = Some(value);
     }
 
     /// Gets the dynamic value for form elements (returns current value or attribute value)
+    /// The `value` attribute is the default value, so an input starts with it and is edited from
+    /// there. The attribute itself is not changed by editing.
+    /// https://html.spec.whatwg.org/multipage/input.html#attr-input-value
     pub fn get_value(&self) -> Option<String> {
         // First check dynamic value
         if let Some(value) = self.dynamic_value.borrow().clone() {
@@ -294,6 +494,41 @@ impl Element {
         self.get_attribute("value")
     }
 
+    pub fn set_checked(&self, checked: bool) {
+        *self.dynamic_checked.borrow_mut() = Some(checked);
+    }
+
+    /// Returns the checkedness of a checkbox or radio input. It's given by the `checked`
+    /// attribute until it's changed.
+    /// https://html.spec.whatwg.org/multipage/input.html#concept-input-checked-dirty-flag
+    pub fn is_checked(&self) -> bool {
+        match *self.dynamic_checked.borrow() {
+            Some(checked) => checked,
+            None => self.get_attribute("checked").is_some(),
+        }
+    }
+
+    /// Sets the caret position in characters of the value. None removes the caret, e.g. when the
+    /// element loses focus.
+    pub fn set_caret(&self, caret: Option<usize>) {
+        *self.caret.borrow_mut() = caret;
+    }
+
+    /// Returns the caret position. It's clamped to the length of the value because the value can
+    /// be changed without moving the caret, e.g. by a script.
+    pub fn caret(&self) -> Option<usize> {
+        let len = self.get_value().unwrap_or_default().chars().count();
+        self.caret.borrow().map(|caret| caret.min(len))
+    }
+
+    /// Discards the changes to the value and the checkedness, so that they're given by the
+    /// `value` and `checked` attributes parsed from the document again.
+    /// https://html.spec.whatwg.org/multipage/input.html#the-input-element:concept-form-reset-control
+    pub fn reset_value(&self) {
+        *self.dynamic_value.borrow_mut() = None;
+        *self.dynamic_checked.borrow_mut() = None;
+    }
+
     /// return true if this element is a block element
     pub fn is_block_element(&self) -> bool {
         match self.kind {
@@ -357,14 +592,84 @@ pub enum ElementKind {
     Ul,
     /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-li-element
     Li,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-dl-element
+    Dl,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-dt-element
+    Dt,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-dd-element
+    Dd,
     /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-div-element
     Div,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-main-element
+    Main,
+    /// https://html.spec.whatwg.org/multipage/sections.html#the-article-element
+    Article,
+    /// https://html.spec.whatwg.org/multipage/sections.html#the-section-element
+    Section,
+    /// https://html.spec.whatwg.org/multipage/sections.html#the-nav-element
+    Nav,
+    /// https://html.spec.whatwg.org/multipage/sections.html#the-header-element
+    Header,
+    /// https://html.spec.whatwg.org/multipage/sections.html#the-footer-element
+    Footer,
+    /// https://html.spec.whatwg.org/multipage/obsolete.html#center
+    Center,
     /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-a-element
     A,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-strong-element
+    Strong,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-b-element
+    B,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-em-element
+    Em,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-i-element
+    I,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-code-element
+    Code,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-s-element
+    S,
+    /// https://html.spec.whatwg.org/multipage/obsolete.html#strike
+    Strike,
+    /// https://html.spec.whatwg.org/multipage/edits.html#the-del-element
+    Del,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-q-element
+    Q,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-abbr-element
+    Abbr,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-span-element
+    Span,
+    /// https://html.spec.whatwg.org/multipage/obsolete.html#font
+    Font,
     /// https://html.spec.whatwg.org/multipage/embedded-content.html#the-img-element
     IMG,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-table-element
+    Table,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-tr-element
+    Tr,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-th-element
+    Th,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-td-element
+    Td,
+    /// https://html.spec.whatwg.org/multipage/forms.html#the-form-element
+    Form,
+    /// https://html.spec.whatwg.org/multipage/forms.html#the-label-element
+    Label,
     /// https://html.spec.whatwg.org/multipage/forms.html#the-input-element
     Input,
+    /// https://html.spec.whatwg.org/multipage/form-elements.html#the-select-element
+    Select,
+    /// https://html.spec.whatwg.org/multipage/form-elements.html#the-optgroup-element
+    Optgroup,
+    /// https://html.spec.whatwg.org/multipage/form-elements.html#the-option-element
+    Option,
+    /// https://html.spec.whatwg.org/multipage/scripting.html#the-template-element
+    Template,
+    /// https://html.spec.whatwg.org/multipage/semantics.html#the-meta-element
+    Meta,
+    /// An element whose tag name is not supported, e.g. a custom element. It's displayed as an
+    /// inline element. The tag name is kept in `Element`.
+    /// https://html.spec.whatwg.org/multipage/dom.html#htmlunknownelement
+    Unknown,
 }
 
 impl Display for ElementKind {
@@ -381,10 +686,44 @@ impl Display for ElementKind {
             ElementKind::Pre => "pre",
             ElementKind::Ul => "ul",
             ElementKind::Li => "li",
+            ElementKind::Dl => "dl",
+            ElementKind::Dt => "dt",
+            ElementKind::Dd => "dd",
             ElementKind::Div => "div",
+            ElementKind::Main => "main",
+            ElementKind::Article => "article",
+            ElementKind::Section => "section",
+            ElementKind::Nav => "nav",
+            ElementKind::Header => "header",
+            ElementKind::Footer => "footer",
+            ElementKind::Center => "center",
             ElementKind::A => "a",
+            ElementKind::Strong => "strong",
+            ElementKind::B => "b",
+            ElementKind::Em => "em",
+            ElementKind::I => "i",
+            ElementKind::Code => "code",
+            ElementKind::S => "s",
+            ElementKind::Strike => "strike",
+            ElementKind::Del => "del",
+            ElementKind::Q => "q",
+            ElementKind::Abbr => "abbr",
+            ElementKind::Span => "span",
+            ElementKind::Font => "font",
             ElementKind::IMG => "img",
+            ElementKind::Table => "table",
+            ElementKind::Tr => "tr",
+            ElementKind::Th => "th",
+            ElementKind::Td => "td",
+            ElementKind::Form => "form",
+            ElementKind::Label => "label",
             ElementKind::Input => "input",
+            ElementKind::Select => "select",
+            ElementKind::Optgroup => "optgroup",
+            ElementKind::Option => "option",
+            ElementKind::Template => "template",
+            ElementKind::Meta => "meta",
+            ElementKind::Unknown => "unknown",
         };
         write!(f, "{}", s)
     }
@@ -406,10 +745,43 @@ impl FromStr for ElementKind {
             "pre" => Ok(ElementKind::Pre),
             "ul" => Ok(ElementKind::Ul),
             "li" => Ok(ElementKind::Li),
+            "dl" => Ok(ElementKind::Dl),
+            "dt" => Ok(ElementKind::Dt),
+            "dd" => Ok(ElementKind::Dd),
             "div" => Ok(ElementKind::Div),
+            "main" => Ok(ElementKind::Main),
+            "article" => Ok(ElementKind::Article),
+            "section" => Ok(ElementKind::Section),
+            "nav" => Ok(ElementKind::Nav),
+            "header" => Ok(ElementKind::Header),
+            "footer" => Ok(ElementKind::Footer),
+            "center" => Ok(ElementKind::Center),
             "a" => Ok(ElementKind::A),
+            "strong" => Ok(ElementKind::Strong),
+            "b" => Ok(ElementKind::B),
+            "em" => Ok(ElementKind::Em),
+            "i" => Ok(ElementKind::I),
+            "code" => Ok(ElementKind::Code),
+            "s" => Ok(ElementKind::S),
+            "strike" => Ok(ElementKind::Strike),
+            "del" => Ok(ElementKind::Del),
+            "q" => Ok(ElementKind::Q),
+            "abbr" => Ok(ElementKind::Abbr),
+            "span" => Ok(ElementKind::Span),
+            "font" => Ok(ElementKind::Font),
             "img" => Ok(ElementKind::IMG),
+            "table" => Ok(ElementKind::Table),
+            "tr" => Ok(ElementKind::Tr),
+            "th" => Ok(ElementKind::Th),
+            "td" => Ok(ElementKind::Td),
+            "form" => Ok(ElementKind::Form),
+            "label" => Ok(ElementKind::Label),
             "input" => Ok(ElementKind::Input),
+            "select" => Ok(ElementKind::Select),
+            "optgroup" => Ok(ElementKind::Optgroup),
+            "option" => Ok(ElementKind::Option),
+            "template" => Ok(ElementKind::Template),
+            "meta" => Ok(ElementKind::Meta),
             _ => Err(format!("unimplemented element name {:?}", s)),
         }
     }
@@ -478,4 +850,88 @@ mod tests {
             assert!(!element.is_block_element()); // input is an inline element
         }
     }
+
+    #[test]
+    fn test_closest() {
+        let mut anchor = Element::new("a", Vec::new());
+        anchor.set_attribute("class", "link nav");
+        let a = Rc::new(RefCell::new(Node::new(NodeKind::Element(anchor))));
+        let span = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+            "span",
+            Vec::new(),
+        )))));
+        let text = Rc::new(RefCell::new(Node::new(NodeKind::Text("text".to_string()))));
+        a.borrow_mut().set_first_child(Some(span.clone()));
+        span.borrow_mut().set_parent(Rc::downgrade(&a));
+        span.borrow_mut().set_first_child(Some(text.clone()));
+        text.borrow_mut().set_parent(Rc::downgrade(&span));
+
+        assert_eq!(Some(a.clone()), Node::closest(&text, "a"));
+        assert_eq!(Some(a.clone()), Node::closest(&span, ".nav"));
+        assert_eq!(Some(span.clone()), Node::closest(&span, "span"));
+        assert_eq!(None, Node::closest(&text, "#main"));
+    }
+
+    #[test]
+    fn test_matches() {
+        let mut input = Element::new("input", Vec::new());
+        input.set_attribute("id", "name");
+        input.set_attribute("class", "field wide");
+        input.set_attribute("type", "text");
+        input.set_attribute("required", "");
+        let first = Rc::new(RefCell::new(Node::new(NodeKind::Element(input.clone()))));
+        let p = Element::new("p", Vec::new());
+        let second = Rc::new(RefCell::new(Node::new(NodeKind::Element(p.clone()))));
+        first.borrow_mut().set_next_sibling(Some(second.clone()));
+        second
+            .borrow_mut()
+            .set_previous_sibling(Rc::downgrade(&first));
+
+        assert!(input.matches("input", &first));
+        assert!(!input.matches("p", &first));
+        assert!(input.matches("#name", &first));
+        assert!(!input.matches("#other", &first));
+        assert!(input.matches(".wide", &first));
+        assert!(!input.matches(".narrow", &first));
+        assert!(input.matches("[required]", &first));
+        assert!(!input.matches("[disabled]", &first));
+        assert!(input.matches("input[type=\"text\"]", &first));
+        assert!(!input.matches("input[type=\"password\"]", &first));
+        assert!(!input.matches("p[type=\"text\"]", &first));
+        assert!(input.matches("[class~=\"field\"]", &first));
+        assert!(!input.matches("[class~=\"field wide\"]", &first));
+        assert!(input.matches("input:first-child", &first));
+        assert!(!input.matches("input:last-child", &first));
+        assert!(p.matches("p:last-child", &second));
+        assert!(!p.matches("p:first-child", &second));
+        assert!(!input.matches("", &first));
+
+        // Anything other than one compound selector matches nothing.
+        for selector in [
+            "*",
+            ",",
+            "p, div",
+            "input, div",
+            "div > input",
+            "div input",
+            "input.",
+            "@x",
+            "input:hover",
+            "input:",
+            "input[type=",
+            "input#name!",
+        ] {
+            assert!(!input.matches(selector, &first), "{}", selector);
+        }
+    }
+
+    #[test]
+    fn test_tag_name() {
+        assert_eq!("div", Element::new("div", Vec::new()).tag_name());
+        assert_eq!("div", Element::new("DIV", Vec::new()).tag_name());
+        assert_eq!(
+            "my-element",
+            Element::new("my-element", Vec::new()).tag_name()
+        );
+    }
 }
@@ -1,6 +1,6 @@
 //! This is a helper function to construct HTML string from DOM tree.
 
-use crate::alloc::string::ToString;
+use crate::renderer::dom::node::ElementKind;
 use crate::renderer::dom::node::Node;
 use crate::renderer::dom::node::NodeKind;
 use alloc::rc::Rc;
@@ -13,6 +13,24 @@ pub fn dom_to_html(root: &Option<Rc<RefCell<Node>>>) -> String {
     html
 }
 
+/// Escapes a text, or an attribute value if `attribute_mode` is true, so that it's parsed back
+/// into the same string.
+/// https://html.spec.whatwg.org/multipage/parsing.html#escapingString
+fn escape_string(s: &str, attribute_mode: bool) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '\u{00A0}' => escaped.push_str("&nbsp;"),
+            '"' if attribute_mode => escaped.push_str("&quot;"),
+            '<' if !attribute_mode => escaped.push_str("&lt;"),
+            '>' if !attribute_mode => escaped.push_str("&gt;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 fn dom_to_html_internal(node: &Option<Rc<RefCell<Node>>>, html: &mut String) {
     match node {
         Some(n) => {
@@ -21,16 +39,36 @@ fn dom_to_html_internal(node: &Option<Rc<RefCell<Node>>>, html: &mut String) {
                 NodeKind::Document => {}
                 NodeKind::Element(ref e) => {
                     html.push('<');
-                    html.push_str(&e.kind().to_string());
+                    html.push_str(e.local_name());
                     for attr in e.attributes() {
                         html.push(' ');
                         html.push_str(&attr.name());
-                        html.push('=');
-                        html.push_str(&attr.value());
+                        // Quote the value so that a value with spaces (e.g. a class list)
+                        // survives re-parsing.
+                        html.push_str("=\"");
+                        html.push_str(&escape_string(&attr.value(), true));
+                        html.push('"');
                     }
                     html.push('>');
                 }
-                NodeKind::Text(ref s) => html.push_str(s),
+                NodeKind::Text(ref s) => {
+                    // The text of a script or a style is not parsed for character references,
+                    // so it's kept as is.
+                    let parent_kind = n
+                        .borrow()
+                        .parent()
+                        .upgrade()
+                        .and_then(|p| p.borrow().element_kind());
+                    match parent_kind {
+                        Some(ElementKind::Script) | Some(ElementKind::Style) => html.push_str(s),
+                        _ => html.push_str(&escape_string(s, false)),
+                    }
+                }
+                NodeKind::Comment(ref s) => {
+                    html.push_str("<!--");
+                    html.push_str(s);
+                    html.push_str("-->");
+                }
             }
 
             dom_to_html_internal(&n.borrow().first_child(), html);
@@ -40,10 +78,11 @@ fn dom_to_html_internal(node: &Option<Rc<RefCell<Node>>>, html: &mut String) {
                 NodeKind::Document => {}
                 NodeKind::Element(ref e) => {
                     html.push_str("</");
-                    html.push_str(&e.kind().to_string());
+                    html.push_str(e.local_name());
                     html.push('>');
                 }
                 NodeKind::Text(_s) => {}
+                NodeKind::Comment(_s) => {}
             }
 
             dom_to_html_internal(&n.borrow().next_sibling(), html);
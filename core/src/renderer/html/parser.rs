@@ -2,6 +2,8 @@
 //! https://html.spec.whatwg.org/multipage/parsing.html#tree-construction
 
 use crate::browser::Browser;
+use crate::constants::DEFAULT_MAX_DOM_NODES;
+use crate::error::Error;
 use crate::renderer::dom::node::Element;
 use crate::renderer::dom::node::ElementKind;
 use crate::renderer::dom::node::Node;
@@ -9,6 +11,7 @@ use crate::renderer::dom::node::NodeKind;
 use crate::renderer::dom::window::Window;
 use crate::renderer::html::attribute::Attribute;
 use crate::renderer::html::token::{HtmlToken, HtmlTokenizer, State};
+use crate::utils::console_debug;
 use crate::utils::console_warning;
 use alloc::format;
 use alloc::rc::{Rc, Weak};
@@ -41,10 +44,28 @@ pub struct HtmlParser {
     /// https://html.spec.whatwg.org/multipage/parsing.html#the-stack-of-open-elements
     stack_of_open_elements: Vec<Rc<RefCell<Node>>>,
     t: HtmlTokenizer,
+    /// The number of nodes inserted so far, excluding the document.
+    node_count: usize,
+    /// The maximum number of nodes. It's read from the browser.
+    max_nodes: usize,
+    /// Set when parsing is aborted, e.g. because the document is too large.
+    error: Option<Error>,
+    /// The number of tokens consumed so far.
+    token_count: usize,
+    /// Called with `token_count` every `progress_interval` tokens.
+    progress_callback: Option<fn(usize)>,
+    progress_interval: usize,
+    /// The `token_count` at which `progress_callback` is called next.
+    next_progress: usize,
 }
 
 impl HtmlParser {
     pub fn new(browser: Weak<RefCell<Browser>>, t: HtmlTokenizer) -> Self {
+        let max_nodes = match browser.upgrade() {
+            Some(browser) => browser.borrow().max_dom_nodes(),
+            None => DEFAULT_MAX_DOM_NODES,
+        };
+
         Self {
             browser: browser.clone(),
             window: Rc::new(RefCell::new(Window::new(browser))),
@@ -52,7 +73,57 @@ impl HtmlParser {
             original_insertion_mode: InsertionMode::Initial,
             stack_of_open_elements: Vec::new(),
             t,
+            node_count: 0,
+            max_nodes,
+            error: None,
+            token_count: 0,
+            progress_callback: None,
+            progress_interval: 0,
+            next_progress: 0,
+        }
+    }
+
+    /// Makes `construct_tree` call `callback` with the number of tokens consumed so far every
+    /// `interval` tokens, e.g. for a UI to show the progress of a large page.
+    pub fn with_progress_callback(mut self, interval: usize, callback: fn(usize)) -> Self {
+        self.progress_interval = interval.max(1);
+        self.next_progress = self.progress_interval;
+        self.progress_callback = Some(callback);
+        self
+    }
+
+    /// Consumes the next token and reports the progress if it's requested.
+    fn next_token(&mut self) -> Option<HtmlToken> {
+        self.token_count += 1;
+        if let Some(callback) = self.progress_callback {
+            if self.token_count == self.next_progress {
+                callback(self.token_count);
+                self.next_progress += self.progress_interval;
+            }
         }
+        self.t.next()
+    }
+
+    /// Returns the error that aborted parsing, or None if the whole document was parsed.
+    pub fn error(&self) -> Option<Error> {
+        self.error.clone()
+    }
+
+    /// Counts a new node. Returns false and aborts parsing if the document has too many nodes.
+    fn reserve_node(&mut self) -> bool {
+        if self.node_count >= self.max_nodes {
+            if self.error.is_none() {
+                let message = format!(
+                    "The document exceeds the limit of {} nodes. Parsing stopped",
+                    self.max_nodes
+                );
+                console_warning(&self.browser, message.clone());
+                self.error = Some(Error::UnexpectedInput(message));
+            }
+            return false;
+        }
+        self.node_count += 1;
+        true
     }
 
     /// Creates a char node.
@@ -71,6 +142,10 @@ impl HtmlParser {
     /// a node. Put the new node in the stack of open elements.
     /// https://html.spec.whatwg.org/multipage/parsing.html#insert-a-foreign-element
     fn insert_element(&mut self, tag: &str, attributes: Vec<Attribute>) {
+        if !self.reserve_node() {
+            return;
+        }
+
         let window = self.window.borrow();
         let current = match self.stack_of_open_elements.last() {
             Some(n) => n.clone(),
@@ -119,12 +194,14 @@ impl HtmlParser {
             None => return,
         };
 
-        // When the current node is Text, add a character to the current node.
-        // Do not access by current.borrow().kind(), otherwise, you can't add a next char to a
-        // correct node.
-        if let NodeKind::Text(ref mut s) = current.borrow_mut().kind {
-            s.push(c);
-            return;
+        // If there is a Text node immediately before the adjusted insertion location, append the
+        // data to that Text node.
+        let last_child = current.borrow().last_child().upgrade();
+        if let Some(last) = &last_child {
+            if let NodeKind::Text(ref mut s) = last.borrow_mut().kind {
+                s.push(c);
+                return;
+            }
         }
 
         // do not create a Text node if new char is '\n' or ' '
@@ -132,24 +209,49 @@ impl HtmlParser {
             return;
         }
 
+        if !self.reserve_node() {
+            return;
+        }
+
         let node = Rc::new(RefCell::new(self.create_char(c)));
 
-        if current.borrow().first_child().is_some() {
-            // TODO: Probably impossible to reach here. `first_child` of the current node is always None.
-            current
-                .borrow()
-                .first_child()
-                .unwrap()
-                .borrow_mut()
-                .set_next_sibling(Some(node.clone()));
-        } else {
-            current.borrow_mut().set_first_child(Some(node.clone()));
+        match last_child {
+            Some(last) => {
+                last.borrow_mut().set_next_sibling(Some(node.clone()));
+                node.borrow_mut().set_previous_sibling(Rc::downgrade(&last));
+            }
+            None => current.borrow_mut().set_first_child(Some(node.clone())),
         }
 
         current.borrow_mut().set_last_child(Rc::downgrade(&node));
         node.borrow_mut().set_parent(Rc::downgrade(&current));
+    }
 
-        self.stack_of_open_elements.push(node);
+    /// Inserts a comment node as the last child of the current node, or of the document if no
+    /// element is open.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#insert-a-comment
+    fn insert_comment(&mut self, data: String) {
+        if !self.reserve_node() {
+            return;
+        }
+
+        let current = match self.stack_of_open_elements.last() {
+            Some(n) => n.clone(),
+            None => self.window.borrow().document(),
+        };
+        let node = Rc::new(RefCell::new(Node::new(NodeKind::Comment(data))));
+
+        let last_child = current.borrow().last_child().upgrade();
+        match last_child {
+            Some(last) => {
+                last.borrow_mut().set_next_sibling(Some(node.clone()));
+                node.borrow_mut().set_previous_sibling(Rc::downgrade(&last));
+            }
+            None => current.borrow_mut().set_first_child(Some(node.clone())),
+        }
+
+        current.borrow_mut().set_last_child(Rc::downgrade(&node));
+        node.borrow_mut().set_parent(Rc::downgrade(&current));
     }
 
     /// Returns true if the current node's kind is same as NodeKind::Element::<element_kind>.
@@ -187,6 +289,42 @@ impl HtmlParser {
         }
     }
 
+    /// https://html.spec.whatwg.org/multipage/parsing.html#close-a-p-element
+    /// Elements that end a button scope are not supported, so a p element anywhere in the stack
+    /// is closed.
+    fn close_p_element(&mut self) {
+        if !self.contain_in_stack(ElementKind::P) {
+            return;
+        }
+        console_debug(&self.browser, String::from("implicitly closed <p>"));
+        self.pop_until(ElementKind::P);
+    }
+
+    /// Pops nodes until a node with `element_kind` comes for an end tag. If there is no such
+    /// node, the end tag is a parse error and ignored.
+    fn close_element(&mut self, element_kind: ElementKind) {
+        if !self.contain_in_stack(element_kind) {
+            console_debug(&self.browser, format!("ignored stray </{}>", element_kind));
+            return;
+        }
+        self.pop_until(element_kind);
+    }
+
+    /// Pops elements up to and including the nearest unknown element whose tag name is `tag`.
+    /// The end tag is ignored if there is no such element.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody:stack-of-open-elements-4
+    fn close_unknown_element(&mut self, tag: &str) {
+        let position = self.stack_of_open_elements.iter().rposition(|n| {
+            n.borrow()
+                .get_element()
+                .is_some_and(|e| e.kind() == ElementKind::Unknown && e.local_name() == tag)
+        });
+        match position {
+            Some(i) => self.stack_of_open_elements.truncate(i),
+            None => console_debug(&self.browser, format!("ignored stray </{}>", tag)),
+        }
+    }
+
     /// Returns true if the stack of open elements has NodeKind::Element::<element_kind> node.
     fn contain_in_stack(&mut self, element_kind: ElementKind) -> bool {
         for i in 0..self.stack_of_open_elements.len() {
@@ -200,16 +338,28 @@ impl HtmlParser {
 
     /// https://html.spec.whatwg.org/multipage/parsing.html#tree-construction
     pub fn construct_tree(&mut self) -> Rc<RefCell<Window>> {
-        let mut token = self.t.next();
+        let mut token = self.next_token();
 
         while token.is_some() {
+            // Keep what was parsed so far if parsing is aborted.
+            if self.error.is_some() {
+                return self.window.clone();
+            }
+
+            // A comment is inserted in the same way in all insertion modes.
+            if let Some(HtmlToken::Comment(data)) = token {
+                self.insert_comment(data);
+                token = self.next_token();
+                continue;
+            }
+
             match self.mode {
                 // https://html.spec.whatwg.org/multipage/parsing.html#the-initial-insertion-mode
                 InsertionMode::Initial => {
                     // Ignore <!doctype html>.
                     // <!doctype html> is generated as a Char token.
                     if let Some(HtmlToken::Char(_)) = token {
-                        token = self.t.next();
+                        token = self.next_token();
                         continue;
                     }
 
@@ -222,7 +372,7 @@ impl HtmlParser {
                     match token {
                         Some(HtmlToken::Char(c)) => {
                             if c == ' ' || c == '\n' {
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
@@ -238,7 +388,7 @@ impl HtmlParser {
                             if tag == "html" {
                                 self.insert_element(tag, attributes.to_vec());
                                 self.mode = InsertionMode::BeforeHead;
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
@@ -247,10 +397,14 @@ impl HtmlParser {
                             // Parse error. Ignore the token.
                             if tag != "head" || tag != "body" || tag != "html" || tag != "br" {
                                 // Ignore the token.
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
+                        // A comment is inserted before the insertion mode is dispatched.
+                        Some(HtmlToken::Comment(_)) => {
+                            unreachable!("comments are already inserted")
+                        }
                         Some(HtmlToken::Eof) | None => {
                             return self.window.clone();
                         }
@@ -265,7 +419,7 @@ impl HtmlParser {
                     match token {
                         Some(HtmlToken::Char(c)) => {
                             if c == ' ' || c == '\n' {
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
@@ -277,7 +431,7 @@ impl HtmlParser {
                             if tag == "head" {
                                 self.insert_element(tag, attributes.to_vec());
                                 self.mode = InsertionMode::InHead;
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
@@ -296,7 +450,7 @@ impl HtmlParser {
                     match token {
                         Some(HtmlToken::Char(c)) => {
                             if c == ' ' || c == '\n' {
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
@@ -309,7 +463,17 @@ impl HtmlParser {
                                 self.insert_element(tag, attributes.to_vec());
                                 self.original_insertion_mode = self.mode;
                                 self.mode = InsertionMode::Text;
-                                token = self.t.next();
+                                token = self.next_token();
+                                continue;
+                            }
+                            // A start tag whose tag name is one of: "base", "basefont", "bgsound",
+                            // "link", "meta"
+                            if tag == "meta" {
+                                // Insert an HTML element for the token. Immediately pop the
+                                // current node off the stack of open elements.
+                                self.insert_element(tag, attributes.to_vec());
+                                self.pop_current_node(ElementKind::Meta);
+                                token = self.next_token();
                                 continue;
                             }
                             if tag == "script" {
@@ -324,7 +488,7 @@ impl HtmlParser {
                                 self.original_insertion_mode = self.mode;
                                 // "10. Switch the insertion mode to "text"."
                                 self.mode = InsertionMode::Text;
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
 
@@ -345,17 +509,21 @@ impl HtmlParser {
                         Some(HtmlToken::EndTag { ref tag }) => {
                             if tag == "head" {
                                 self.mode = InsertionMode::AfterHead;
-                                token = self.t.next();
+                                token = self.next_token();
                                 self.pop_until(ElementKind::Head);
                                 continue;
                             }
                         }
+                        // A comment is inserted before the insertion mode is dispatched.
+                        Some(HtmlToken::Comment(_)) => {
+                            unreachable!("comments are already inserted")
+                        }
                         Some(HtmlToken::Eof) | None => {
                             return self.window.clone();
                         }
                     }
                     // Ignore unsupported tags like <meta> and <title>.
-                    token = self.t.next();
+                    token = self.next_token();
                     continue;
                 } // end of InsertionMode::InHead
 
@@ -365,7 +533,7 @@ impl HtmlParser {
                         Some(HtmlToken::Char(c)) => {
                             if c == ' ' || c == '\n' {
                                 self.insert_char(c);
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
@@ -376,7 +544,7 @@ impl HtmlParser {
                         }) => {
                             if tag == "body" {
                                 self.insert_element(tag, attributes.to_vec());
-                                token = self.t.next();
+                                token = self.next_token();
                                 self.mode = InsertionMode::InBody;
                                 continue;
                             }
@@ -412,7 +580,46 @@ impl HtmlParser {
                                     self.t.switch_context(State::ScriptData);
                                     self.original_insertion_mode = self.mode;
                                     self.mode = InsertionMode::Text;
-                                    token = self.t.next();
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // Process the token using the rules for the "in head" insertion
+                                // mode. A meta element is a void element.
+                                "meta" => {
+                                    self.insert_element(tag, attributes.to_vec());
+                                    self.pop_current_node(ElementKind::Meta);
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // A start tag whose tag name is "select"
+                                "select" => {
+                                    // TODO: switch the insertion mode to "in select".
+                                    self.insert_element(tag, attributes.to_vec());
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // A start tag whose tag name is one of: "optgroup", "option"
+                                "optgroup" | "option" => {
+                                    // If the current node is an option element, then pop the
+                                    // current node off the stack of open elements.
+                                    self.pop_current_node(ElementKind::Option);
+                                    // An optgroup can't be nested in an optgroup.
+                                    // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inselect
+                                    if tag == "optgroup" {
+                                        self.pop_current_node(ElementKind::Optgroup);
+                                    }
+                                    self.insert_element(tag, attributes.to_vec());
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // A start tag whose tag name is "template"
+                                "template" => {
+                                    // The contents should be inserted into the template contents,
+                                    // a separate DocumentFragment. They're kept as children of the
+                                    // element instead, and the layout tree skips them.
+                                    // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inhead:template-contents
+                                    self.insert_element(tag, attributes.to_vec());
+                                    token = self.next_token();
                                     continue;
                                 }
                                 // A start tag whose tag name is one of: "address", "article",
@@ -420,13 +627,15 @@ impl HtmlParser {
                                 // "div", "dl", "fieldset", "figcaption", "figure", "footer",
                                 // "header", "hgroup", "main", "menu", "nav", "ol", "p", "section",
                                 // "summary", "ul"
-                                "div" | "p" | "ul" => {
+                                "article" | "center" | "div" | "dl" | "footer" | "header"
+                                | "main" | "nav" | "p" | "section" | "ul" => {
                                     // If the stack of open elements has a p element in button
                                     // scope, then close a p element.
-                                    //
+                                    self.close_p_element();
+
                                     // Insert an HTML element for the token.
                                     self.insert_element(tag, attributes.to_vec());
-                                    token = self.t.next();
+                                    token = self.next_token();
                                     continue;
                                 }
                                 // A start tag whose tag name is one of: "h1", "h2", "h3", "h4",
@@ -434,6 +643,7 @@ impl HtmlParser {
                                 "h1" | "h2" => {
                                     // If the stack of open elements has a p element in button
                                     // scope, then close a p element.
+                                    self.close_p_element();
                                     //
                                     // If the current node is an HTML element whose tag name is one
                                     // of "h1", "h2", "h3", "h4", "h5", or "h6", then this is a
@@ -442,13 +652,14 @@ impl HtmlParser {
                                     //
                                     // Insert an HTML element for the token.
                                     self.insert_element(tag, attributes.to_vec());
-                                    token = self.t.next();
+                                    token = self.next_token();
                                     continue;
                                 }
                                 // A start tag whose tag name is one of: "pre", "listing"
                                 "pre" => {
                                     // If the stack of open elements has a p element in button
                                     // scope, then close a p element.
+                                    self.close_p_element();
                                     //
                                     // Insert an HTML element for the token.
                                     //
@@ -459,7 +670,24 @@ impl HtmlParser {
                                     //
                                     // Set the frameset-ok flag to "not ok".
                                     self.insert_element(tag, attributes.to_vec());
-                                    token = self.t.next();
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // A start tag whose tag name is "form"
+                                "form" => {
+                                    // If the form element pointer is not null, and there is no
+                                    // template element on the stack of open elements, then this
+                                    // is a parse error; ignore the token.
+                                    //
+                                    // If the stack of open elements has a p element in button
+                                    // scope, then close a p element.
+                                    self.close_p_element();
+
+                                    // Insert an HTML element for the token, and, if there is no
+                                    // template element on the stack of open elements, set the
+                                    // form element pointer to point to the element created.
+                                    self.insert_element(tag, attributes.to_vec());
+                                    token = self.next_token();
                                     continue;
                                 }
                                 // A start tag whose tag name is "li"
@@ -487,10 +715,58 @@ impl HtmlParser {
                                     //
                                     // 6. Done: If the stack of open elements has a p element in
                                     // button scope, then close a p element.
+                                    self.close_p_element();
                                     //
                                     // 7. Finally, insert an HTML element for the token.
                                     self.insert_element(tag, attributes.to_vec());
-                                    token = self.t.next();
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // A start tag whose tag name is one of: "dd", "dt"
+                                "dd" | "dt" => {
+                                    // If the stack of open elements has a p element in button
+                                    // scope, then close a p element.
+                                    self.close_p_element();
+
+                                    // If the current node is a dd or dt element, generate implied
+                                    // end tags for it, since a term or a description ends where
+                                    // the next one starts.
+                                    if !self.pop_current_node(ElementKind::Dd) {
+                                        self.pop_current_node(ElementKind::Dt);
+                                    }
+
+                                    // Insert an HTML element for the token.
+                                    self.insert_element(tag, attributes.to_vec());
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // A start tag whose tag name is "table"
+                                "table" => {
+                                    // If the stack of open elements has a p element in button
+                                    // scope, then close a p element.
+                                    self.close_p_element();
+
+                                    // Insert an HTML element for the token.
+                                    self.insert_element(tag, attributes.to_vec());
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // A start tag whose tag name is one of: "tr", "td", "th"
+                                "tr" | "td" | "th" => {
+                                    // The "in table", "in row" and "in cell" insertion modes are
+                                    // not supported. A cell ends where the next cell or row
+                                    // starts, and a row ends where the next row starts.
+                                    // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intr
+                                    if !self.pop_current_node(ElementKind::Td) {
+                                        self.pop_current_node(ElementKind::Th);
+                                    }
+                                    if tag == "tr" {
+                                        self.pop_current_node(ElementKind::Tr);
+                                    }
+
+                                    // Insert an HTML element for the token.
+                                    self.insert_element(tag, attributes.to_vec());
+                                    token = self.next_token();
                                     continue;
                                 }
                                 // A start tag whose tag name is "a"
@@ -510,7 +786,18 @@ impl HtmlParser {
                                     // Insert an HTML element for the token. Push onto the list of
                                     // active formatting elements that element.
                                     self.insert_element(tag, attributes.to_vec());
-                                    token = self.t.next();
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // A start tag whose tag name is one of: "b", "big", "code", "em",
+                                // "font", "i", "s", "small", "strike", "strong", "tt", "u"
+                                "b" | "code" | "em" | "i" | "s" | "strike" | "strong" => {
+                                    // Reconstruct the active formatting elements, if any.
+                                    //
+                                    // Insert an HTML element for the token. Push onto the list of
+                                    // active formatting elements that element.
+                                    self.insert_element(tag, attributes.to_vec());
+                                    token = self.next_token();
                                     continue;
                                 }
                                 // A start tag whose tag name is one of: "area", "br", "embed", "img", "keygen", "wbr"
@@ -526,7 +813,7 @@ impl HtmlParser {
                                     self.insert_element(tag, attributes.to_vec());
                                     // IMG is always a void element, so pop it regardless of self_closing flag
                                     self.stack_of_open_elements.pop();
-                                    token = self.t.next();
+                                    token = self.next_token();
                                     continue;
                                 }
                                 // A start tag whose tag name is "input"
@@ -542,7 +829,32 @@ impl HtmlParser {
                                     self.insert_element(tag, attributes.to_vec());
                                     // Input is always a void element, so pop it regardless of self_closing flag
                                     self.stack_of_open_elements.pop();
-                                    token = self.t.next();
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // A start tag whose tag name is "wbr"
+                                "wbr" => {
+                                    // The element represents a line break opportunity and draws
+                                    // nothing, so it's inserted as a zero width space that the
+                                    // layout already breaks lines at.
+                                    // https://html.spec.whatwg.org/multipage/rendering.html#phrasing-content-3
+                                    self.insert_char('\u{200B}');
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // Any other start tag
+                                "abbr" | "del" | "font" | "label" | "q" | "span" => {
+                                    // Reconstruct the active formatting elements, if any.
+                                    //
+                                    // Insert an HTML element for the token.
+                                    self.insert_element(tag, attributes.to_vec());
+                                    token = self.next_token();
+                                    continue;
+                                }
+                                // Any other start tag of an unknown element, e.g. a custom element
+                                tag if ElementKind::from_str(tag).is_err() => {
+                                    self.insert_element(tag, attributes.to_vec());
+                                    token = self.next_token();
                                     continue;
                                 }
                                 _ => {
@@ -550,7 +862,7 @@ impl HtmlParser {
                                         &self.browser,
                                         format!("unsupported start tag in InBody {:?}", tag),
                                     );
-                                    token = self.t.next();
+                                    token = self.next_token();
                                 }
                             }
                         }
@@ -559,7 +871,7 @@ impl HtmlParser {
                                 // An end tag whose tag name is "body"
                                 "body" => {
                                     self.mode = InsertionMode::AfterBody;
-                                    token = self.t.next();
+                                    token = self.next_token();
                                     if !self.contain_in_stack(ElementKind::Body) {
                                         // Parse error. Ignore the token.
                                         continue;
@@ -575,7 +887,7 @@ impl HtmlParser {
                                         self.mode = InsertionMode::AfterBody;
                                         assert!(self.pop_current_node(ElementKind::Html));
                                     } else {
-                                        token = self.t.next();
+                                        token = self.next_token();
                                     }
                                     continue;
                                 }
@@ -584,27 +896,50 @@ impl HtmlParser {
                                 // "dir", "div", "dl", "fieldset", "figcaption", "figure",
                                 // "footer", "header", "hgroup", "listing", "main", "menu", "nav",
                                 // "ol", "pre", "section", "summary", "ul"
-                                "div" | "pre" | "ul" => {
+                                "article" | "center" | "div" | "dl" | "footer" | "header"
+                                | "main" | "nav" | "pre" | "section" | "ul" => {
                                     let element_kind = ElementKind::from_str(tag)
                                         .expect("failed to convert string to ElementKind");
-                                    token = self.t.next();
-                                    self.pop_until(element_kind);
+                                    token = self.next_token();
+                                    self.close_element(element_kind);
+                                    continue;
+                                }
+                                // An end tag whose tag name is "form"
+                                "form" => {
+                                    token = self.next_token();
+                                    self.close_element(ElementKind::Form);
                                     continue;
                                 }
                                 // An end tag whose tag name is "p"
                                 "p" => {
                                     let element_kind = ElementKind::from_str(tag)
                                         .expect("failed to convert string to ElementKind");
-                                    token = self.t.next();
-                                    self.pop_until(element_kind);
+                                    token = self.next_token();
+                                    self.close_element(element_kind);
+                                    continue;
+                                }
+                                // An end tag whose tag name is one of: "dd", "dt"
+                                "dd" | "dt" => {
+                                    let element_kind = ElementKind::from_str(tag)
+                                        .expect("failed to convert string to ElementKind");
+                                    token = self.next_token();
+                                    self.close_element(element_kind);
+                                    continue;
+                                }
+                                // An end tag whose tag name is one of: "table", "tr", "td", "th"
+                                "table" | "tr" | "td" | "th" => {
+                                    let element_kind = ElementKind::from_str(tag)
+                                        .expect("failed to convert string to ElementKind");
+                                    token = self.next_token();
+                                    self.close_element(element_kind);
                                     continue;
                                 }
                                 // An end tag whose tag name is "li"
                                 "li" => {
                                     let element_kind = ElementKind::from_str(tag)
                                         .expect("failed to convert string to ElementKind");
-                                    token = self.t.next();
-                                    self.pop_until(element_kind);
+                                    token = self.next_token();
+                                    self.close_element(element_kind);
                                     continue;
                                 }
                                 // An end tag whose tag name is one of: "h1", "h2", "h3", "h4",
@@ -612,19 +947,49 @@ impl HtmlParser {
                                 "h1" | "h2" => {
                                     let element_kind = ElementKind::from_str(tag)
                                         .expect("failed to convert string to ElementKind");
-                                    token = self.t.next();
-                                    self.pop_until(element_kind);
+                                    token = self.next_token();
+                                    self.close_element(element_kind);
                                     continue;
                                 }
                                 // An end tag whose tag name is one of: "a", "b", "big", "code",
                                 // "em", "font", "i", "nobr", "s", "small", "strike", "strong",
                                 // "tt", "u"
-                                "a" => {
+                                "a" | "b" | "code" | "em" | "i" | "s" | "strike" | "strong" => {
                                     // Run the adoption agency algorithm for the token.
                                     let element_kind = ElementKind::from_str(tag)
                                         .expect("failed to convert string to ElementKind");
-                                    token = self.t.next();
-                                    self.pop_until(element_kind);
+                                    token = self.next_token();
+                                    self.close_element(element_kind);
+                                    continue;
+                                }
+                                // An end tag whose tag name is one of: "select", "optgroup",
+                                // "option"
+                                "select" | "optgroup" | "option" => {
+                                    let element_kind = ElementKind::from_str(tag)
+                                        .expect("failed to convert string to ElementKind");
+                                    token = self.next_token();
+                                    self.close_element(element_kind);
+                                    continue;
+                                }
+                                // An end tag whose tag name is "template"
+                                "template" => {
+                                    token = self.next_token();
+                                    self.close_element(ElementKind::Template);
+                                    continue;
+                                }
+                                // Any other end tag
+                                "abbr" | "del" | "font" | "label" | "q" | "span" => {
+                                    let element_kind = ElementKind::from_str(tag)
+                                        .expect("failed to convert string to ElementKind");
+                                    token = self.next_token();
+                                    self.close_element(element_kind);
+                                    continue;
+                                }
+                                // Any other end tag of an unknown element
+                                tag if ElementKind::from_str(tag).is_err() => {
+                                    let tag = String::from(tag);
+                                    token = self.next_token();
+                                    self.close_unknown_element(&tag);
                                     continue;
                                 }
                                 _ => {
@@ -632,7 +997,7 @@ impl HtmlParser {
                                         &self.browser,
                                         format!("unsupported end tag InBody {:?}", tag),
                                     );
-                                    token = self.t.next();
+                                    token = self.next_token();
                                 }
                             }
                         }
@@ -642,9 +1007,13 @@ impl HtmlParser {
                             // Insert the token's character.
                             // TODO: Set the frameset-ok flag to "not ok".
                             self.insert_char(c);
-                            token = self.t.next();
+                            token = self.next_token();
                             continue;
                         }
+                        // A comment is inserted before the insertion mode is dispatched.
+                        Some(HtmlToken::Comment(_)) => {
+                            unreachable!("comments are already inserted")
+                        }
                         Some(HtmlToken::Eof) | None => {
                             return self.window.clone();
                         }
@@ -661,19 +1030,19 @@ impl HtmlParser {
                             if tag == "style" {
                                 self.pop_until(ElementKind::Style);
                                 self.mode = self.original_insertion_mode;
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                             if tag == "script" {
                                 self.pop_until(ElementKind::Script);
                                 self.mode = self.original_insertion_mode;
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
                         Some(HtmlToken::Char(c)) => {
                             self.insert_char(c);
-                            token = self.t.next();
+                            token = self.next_token();
                             continue;
                         }
                         _ => {}
@@ -689,13 +1058,13 @@ impl HtmlParser {
                             // Not align with the spec.
                             // TODO: Process the token using the rules for the "in body" insertion
                             // mode.
-                            token = self.t.next();
+                            token = self.next_token();
                             continue;
                         }
                         Some(HtmlToken::EndTag { ref tag }) => {
                             if tag == "html" {
                                 self.mode = InsertionMode::AfterAfterBody;
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
@@ -715,7 +1084,7 @@ impl HtmlParser {
                             // Not align with the spec.
                             // TODO: Process the token using the rules for the "in body" insertion
                             // mode.
-                            token = self.t.next();
+                            token = self.next_token();
                             continue;
                         }
                         Some(HtmlToken::Eof) | None => {
@@ -738,7 +1107,10 @@ mod tests {
     use super::*;
     use crate::alloc::string::ToString;
     use crate::renderer::dom::api::get_target_element_node;
+    use crate::renderer::html::html_builder::dom_to_html;
     use alloc::vec;
+    use core::sync::atomic::AtomicUsize;
+    use core::sync::atomic::Ordering;
 
     #[test]
     fn test_empty() {
@@ -1015,4 +1387,225 @@ mod tests {
         assert_eq!(element.get_attribute("type"), Some("password".to_string()));
         assert_eq!(element.get_attribute("placeholder"), Some("Enter password".to_string()));
     }
+
+    #[test]
+    fn test_implicitly_closed_p() {
+        let browser = Browser::new();
+        let html = "<html><body><p>a<p>b</body></html>".to_string();
+        let t = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+        let window = HtmlParser::new(Rc::downgrade(&browser), t).construct_tree();
+
+        let body = get_target_element_node(
+            window.borrow().document().borrow().first_child(),
+            ElementKind::Body,
+        )
+        .expect("failed to get body");
+        let p1 = body.borrow().first_child().expect("failed to get p");
+        let p2 = p1.borrow().next_sibling().expect("p should be a sibling");
+        assert_eq!(Some(ElementKind::P), p2.borrow().element_kind());
+
+        let logs: Vec<String> = browser
+            .borrow()
+            .logs()
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        assert!(logs.contains(&"Debug: implicitly closed <p>".to_string()));
+    }
+
+    #[test]
+    fn test_stray_end_tag() {
+        let browser = Browser::new();
+        let html = "<html><body><p>a</div></p></body></html>".to_string();
+        let t = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+        let window = HtmlParser::new(Rc::downgrade(&browser), t).construct_tree();
+
+        let body = get_target_element_node(
+            window.borrow().document().borrow().first_child(),
+            ElementKind::Body,
+        )
+        .expect("failed to get body");
+        let p = body.borrow().first_child().expect("failed to get p");
+        assert_eq!(Some(ElementKind::P), p.borrow().element_kind());
+
+        let logs: Vec<String> = browser
+            .borrow()
+            .logs()
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        assert!(logs.contains(&"Debug: ignored stray </div>".to_string()));
+    }
+
+    #[test]
+    fn test_attribute_order() {
+        let browser = Browser::new();
+        let html = "<html><body><input a=\"1\" b=\"2\" c=\"3\" a=\"4\"></body></html>".to_string();
+        let t = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+        let window = HtmlParser::new(Rc::downgrade(&browser), t).construct_tree();
+
+        let body = get_target_element_node(
+            window.borrow().document().borrow().first_child(),
+            ElementKind::Body,
+        )
+        .expect("failed to get body");
+        let input = body.borrow().first_child().expect("failed to get input");
+        let element = input.borrow().get_element().expect("should be an element");
+
+        // Attributes keep the source order, and a duplicated attribute is dropped.
+        let attributes: Vec<(String, String)> = element
+            .attributes()
+            .iter()
+            .map(|a| (a.name(), a.value()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "2".to_string()),
+                ("c".to_string(), "3".to_string()),
+            ],
+            attributes
+        );
+        assert_eq!(
+            "<input a=\"1\" b=\"2\" c=\"3\"></input>",
+            dom_to_html(&Some(input.clone()))
+        );
+    }
+
+    #[test]
+    fn test_unknown_element() {
+        let browser = Browser::new();
+        let html =
+            "<html><body><my-widget id=\"w\">hi<my-item>x</my-item></my-widget>tail</body></html>"
+                .to_string();
+        let t = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+        let window = HtmlParser::new(Rc::downgrade(&browser), t).construct_tree();
+
+        let body = get_target_element_node(
+            window.borrow().document().borrow().first_child(),
+            ElementKind::Body,
+        )
+        .expect("failed to get body");
+        let widget = body
+            .borrow()
+            .first_child()
+            .expect("failed to get my-widget");
+        assert_eq!(Some(ElementKind::Unknown), widget.borrow().element_kind());
+        assert_eq!(
+            "<my-widget id=\"w\">hi<my-item>x</my-item></my-widget>tail",
+            dom_to_html(&Some(widget.clone()))
+        );
+    }
+
+    #[test]
+    fn test_max_dom_nodes() {
+        let browser = Browser::new();
+        browser.borrow_mut().set_max_dom_nodes(5);
+        let html = "<html><head></head><body><p>a</p><p>b</p><p>c</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+        let mut parser = HtmlParser::new(Rc::downgrade(&browser), t);
+        let window = parser.construct_tree();
+
+        // html, head, body, p and "a" are parsed, and the rest is dropped.
+        let body = get_target_element_node(
+            window.borrow().document().borrow().first_child(),
+            ElementKind::Body,
+        )
+        .expect("failed to get body");
+        let p = body.borrow().first_child().expect("failed to get p");
+        assert!(p.borrow().first_child().is_some());
+        assert!(p.borrow().next_sibling().is_none());
+        assert!(parser.error().is_some());
+
+        let logs: Vec<String> = browser
+            .borrow()
+            .logs()
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        assert!(logs.contains(
+            &"Warning: The document exceeds the limit of 5 nodes. Parsing stopped".to_string()
+        ));
+    }
+
+    static PROGRESS: AtomicUsize = AtomicUsize::new(0);
+
+    fn record_progress(token_count: usize) {
+        PROGRESS.store(token_count, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_progress_callback() {
+        let browser = Browser::new();
+        let html = "<html><body><p>abc</p><p>def</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+        let window = HtmlParser::new(Rc::downgrade(&browser), t)
+            .with_progress_callback(4, record_progress)
+            .construct_tree();
+
+        // 8 tags, 6 characters and the end of the file.
+        assert_eq!(12, PROGRESS.load(Ordering::SeqCst));
+        let body = get_target_element_node(
+            window.borrow().document().borrow().first_child(),
+            ElementKind::Body,
+        )
+        .expect("failed to get body");
+        assert!(body.borrow().first_child().is_some());
+    }
+
+    #[test]
+    fn test_comment() {
+        let browser = Browser::new();
+        let html =
+            "<html><head></head><body><!-- note --><p>a<!--x-->b</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(Rc::downgrade(&browser), html.clone());
+        let window = HtmlParser::new(Rc::downgrade(&browser), t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = get_target_element_node(document.borrow().first_child(), ElementKind::Body)
+            .expect("failed to get body");
+        let comment = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a comment");
+        assert_eq!(
+            NodeKind::Comment(" note ".to_string()),
+            comment.borrow().kind()
+        );
+        assert_eq!(html, dom_to_html(&document.borrow().first_child()));
+    }
+
+    #[test]
+    fn test_attribute_round_trip() {
+        let browser = Browser::new();
+        let parse = |html: String| {
+            let t = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+            let window = HtmlParser::new(Rc::downgrade(&browser), t).construct_tree();
+            let document = window.borrow().document();
+            document
+        };
+        let attributes = |document: &Rc<RefCell<Node>>| {
+            let p = get_target_element_node(Some(document.clone()), ElementKind::P)
+                .expect("failed to get p");
+            let e = p.borrow().get_element().expect("p should be an element");
+            (e.get_attribute("onclick"), e.get_attribute("title"))
+        };
+
+        let document = parse(
+            "<html><head></head><body><p onclick='f(\"a b\")' title=\"x &amp; &quot;y&quot;\">t</p></body></html>"
+                .to_string(),
+        );
+        let expected = (
+            Some("f(\"a b\")".to_string()),
+            Some("x & \"y\"".to_string()),
+        );
+        assert_eq!(expected, attributes(&document));
+
+        let html = dom_to_html(&document.borrow().first_child());
+        assert_eq!(
+            "<html><head></head><body><p onclick=\"f(&quot;a b&quot;)\" title=\"x &amp; &quot;y&quot;\">t</p></body></html>",
+            html
+        );
+        assert_eq!(expected, attributes(&parse(html)));
+    }
 }
@@ -48,6 +48,10 @@ pub enum State {
     ScriptDataEndTagName,
     /// https://html.spec.whatwg.org/multipage/parsing.html#temporary-buffer
     TemporaryBuffer,
+    /// https://html.spec.whatwg.org/multipage/parsing.html#markup-declaration-open-state
+    MarkupDeclarationOpen,
+    /// https://html.spec.whatwg.org/multipage/parsing.html#comment-state
+    Comment,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -64,6 +68,8 @@ pub enum HtmlToken {
     },
     // "foo"
     Char(char),
+    // <!--foo-->
+    Comment(String),
     Eof,
 }
 
@@ -208,6 +214,59 @@ impl HtmlTokenizer {
         t
     }
 
+    /// Returns true if the input from the current position starts with `s`.
+    fn next_input_starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.input.get(self.pos + i) == Some(&c))
+    }
+
+    /// Consumes a character reference after '&' and returns the character it refers to, e.g.
+    /// U+00A0 for `&nbsp;` and 'A' for `&#65;`. If the input doesn't start with a supported
+    /// character reference, nothing is consumed and None is returned, so '&' is emitted as is.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#character-reference-state
+    fn consume_character_reference(&mut self) -> Option<char> {
+        let end = self.input[self.pos..]
+            .iter()
+            .take(MAX_CHARACTER_REFERENCE_LEN)
+            .position(|c| *c == ';')?;
+        let name: String = self.input[self.pos..self.pos + end].iter().collect();
+
+        let c = match name.strip_prefix('#') {
+            // https://html.spec.whatwg.org/multipage/parsing.html#numeric-character-reference-state
+            Some(number) => {
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse::<u32>().ok()?,
+                };
+                char::from_u32(code)?
+            }
+            None => named_character_reference(&name)?,
+        };
+
+        // skip the name and ';'
+        self.pos += end + 1;
+        Some(c)
+    }
+
+    /// Returns the char of an attribute value for `c`. A character reference such as `&quot;` is
+    /// decoded.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#attribute-value-(double-quoted)-state
+    fn consume_attribute_value_char(&mut self, c: char) -> char {
+        if c != '&' {
+            return c;
+        }
+        self.consume_character_reference().unwrap_or(c)
+    }
+
+    /// Appends a char to the data of the comment in `latest_token`.
+    fn append_comment(&mut self, c: char) {
+        match self.latest_token.as_mut() {
+            Some(HtmlToken::Comment(ref mut data)) => data.push(c),
+            _ => panic!("`latest_token` should be Comment"),
+        }
+    }
+
     /// Returns true if the current position is larger than the length of input.
     fn is_eof(&self) -> bool {
         self.pos > self.input.len()
@@ -245,10 +304,27 @@ impl Iterator for HtmlTokenizer {
                         return Some(HtmlToken::Eof);
                     }
 
+                    if c == '&' {
+                        if let Some(c) = self.consume_character_reference() {
+                            return Some(HtmlToken::Char(c));
+                        }
+                    }
+
                     return Some(HtmlToken::Char(c));
                 }
                 // https://html.spec.whatwg.org/multipage/parsing.html#tag-open-state
                 State::TagOpen => {
+                    if c == '!' {
+                        if self.pos >= self.input.len() {
+                            // incorrectly-opened-comment parse error. "<!" at the end of the
+                            // input is an empty bogus comment.
+                            self.state = State::Data;
+                            return Some(HtmlToken::Comment(String::new()));
+                        }
+                        self.state = State::MarkupDeclarationOpen;
+                        continue;
+                    }
+
                     if c == '/' {
                         self.state = State::EndTagOpen;
                         continue;
@@ -284,7 +360,7 @@ impl Iterator for HtmlTokenizer {
                 }
                 // https://html.spec.whatwg.org/multipage/parsing.html#tag-name-state
                 State::TagName => {
-                    if c == ' ' {
+                    if is_whitespace(c) {
                         self.state = State::BeforeAttributeName;
                         continue;
                     }
@@ -313,6 +389,11 @@ impl Iterator for HtmlTokenizer {
                 }
                 // https://html.spec.whatwg.org/multipage/parsing.html#before-attribute-name-state
                 State::BeforeAttributeName => {
+                    if is_whitespace(c) {
+                        // Ignore the char.
+                        continue;
+                    }
+
                     if c == '/' || c == '>' || self.is_eof() {
                         self.reconsume = true;
                         self.state = State::AfterAttributeName;
@@ -325,7 +406,7 @@ impl Iterator for HtmlTokenizer {
                 }
                 // https://html.spec.whatwg.org/multipage/parsing.html#attribute-name-state
                 State::AttributeName => {
-                    if c == ' ' || c == '/' || c == '>' || self.is_eof() {
+                    if is_whitespace(c) || c == '/' || c == '>' || self.is_eof() {
                         self.reconsume = true;
                         self.state = State::AfterAttributeName;
                         continue;
@@ -345,7 +426,7 @@ impl Iterator for HtmlTokenizer {
                 }
                 // https://html.spec.whatwg.org/multipage/parsing.html#after-attribute-name-state
                 State::AfterAttributeName => {
-                    if c == ' ' {
+                    if is_whitespace(c) {
                         // Ignore.
                         continue;
                     }
@@ -375,7 +456,7 @@ impl Iterator for HtmlTokenizer {
                 }
                 // https://html.spec.whatwg.org/multipage/parsing.html#before-attribute-value-state
                 State::BeforeAttributeValue => {
-                    if c == ' ' {
+                    if is_whitespace(c) {
                         // Ignore the char.
                         continue;
                     }
@@ -404,6 +485,7 @@ impl Iterator for HtmlTokenizer {
                         return Some(HtmlToken::Eof);
                     }
 
+                    let c = self.consume_attribute_value_char(c);
                     self.append_attribute(c, /*is_name*/ false);
                 }
                 // https://html.spec.whatwg.org/multipage/parsing.html#attribute-value-(single-quoted)-state
@@ -417,11 +499,12 @@ impl Iterator for HtmlTokenizer {
                         return Some(HtmlToken::Eof);
                     }
 
+                    let c = self.consume_attribute_value_char(c);
                     self.append_attribute(c, /*is_name*/ false);
                 }
                 // https://html.spec.whatwg.org/multipage/parsing.html#attribute-value-(unquoted)-state
                 State::AttributeValueUnquoted => {
-                    if c == ' ' {
+                    if is_whitespace(c) {
                         self.state = State::BeforeAttributeName;
                         continue;
                     }
@@ -435,11 +518,12 @@ impl Iterator for HtmlTokenizer {
                         return Some(HtmlToken::Eof);
                     }
 
+                    let c = self.consume_attribute_value_char(c);
                     self.append_attribute(c, /*is_name*/ false);
                 }
                 // https://html.spec.whatwg.org/multipage/parsing.html#after-attribute-value-(quoted)-state
                 State::AfterAttributeValueQuoted => {
-                    if c == ' ' {
+                    if is_whitespace(c) {
                         self.state = State::BeforeAttributeName;
                         continue;
                     }
@@ -556,11 +640,73 @@ impl Iterator for HtmlTokenizer {
                     self.buf.remove(0);
                     return Some(HtmlToken::Char(c));
                 }
+                // https://html.spec.whatwg.org/multipage/parsing.html#markup-declaration-open-state
+                State::MarkupDeclarationOpen => {
+                    // `c` is the first char after "<!".
+                    if c == '-' && self.next_input_starts_with("-") {
+                        self.pos += 1;
+                        if self.pos >= self.input.len() {
+                            // eof-in-comment parse error. Emit the empty comment.
+                            self.state = State::Data;
+                            return Some(HtmlToken::Comment(String::new()));
+                        }
+                        self.latest_token = Some(HtmlToken::Comment(String::new()));
+                        self.state = State::Comment;
+                        continue;
+                    }
+
+                    // Other markup declarations such as <!doctype html> are emitted as Char
+                    // tokens from '!', and the tree construction ignores them.
+                    self.reconsume = true;
+                    self.state = State::Data;
+                    return Some(HtmlToken::Char('!'));
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#comment-state
+                State::Comment => {
+                    if c == '-' && self.next_input_starts_with("->") {
+                        self.pos += 2;
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    self.append_comment(c);
+
+                    if self.pos >= self.input.len() {
+                        // eof-in-comment parse error. Emit the comment.
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+                }
             } // end of `match self.state`
         } // end of `loop`
     }
 }
 
+/// The maximum length of a character reference after '&' including ';' that is looked for.
+const MAX_CHARACTER_REFERENCE_LEN: usize = 10;
+
+/// Returns the character of a named character reference without '&' and ';', e.g. "nbsp".
+/// Only a few common names are supported.
+/// https://html.spec.whatwg.org/multipage/named-characters.html
+fn named_character_reference(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{00A0}'),
+        _ => None,
+    }
+}
+
+/// Returns true if `c` separates a tag name and attributes, i.e. TAB, LF, FF or SPACE. A CR
+/// is handled as a whitespace too, although the input stream should normalize it to LF.
+/// https://infra.spec.whatwg.org/#ascii-whitespace
+fn is_whitespace(c: char) -> bool {
+    c.is_ascii_whitespace()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -679,4 +825,119 @@ mod tests {
             assert_eq!(Some(e), tokenizer.next());
         }
     }
+
+    #[test]
+    fn test_comment() {
+        let browser = Browser::new();
+        let html = "<p><!-- a - b --></p><!doctype html>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+        let expected = [
+            HtmlToken::StartTag {
+                tag: "p".to_string(),
+                self_closing: false,
+                attributes: Vec::new(),
+            },
+            HtmlToken::Comment(" a - b ".to_string()),
+            HtmlToken::EndTag {
+                tag: "p".to_string(),
+            },
+            HtmlToken::Char('!'),
+            HtmlToken::Char('d'),
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+    }
+
+    #[test]
+    fn test_comment_at_end_of_input() {
+        let browser = Browser::new();
+        let p = HtmlToken::StartTag {
+            tag: "p".to_string(),
+            self_closing: false,
+            attributes: Vec::new(),
+        };
+        for (html, expected) in [
+            (
+                "<p><!--",
+                vec![p.clone(), HtmlToken::Comment(String::new())],
+            ),
+            (
+                "<p>a<!",
+                vec![
+                    p.clone(),
+                    HtmlToken::Char('a'),
+                    HtmlToken::Comment(String::new()),
+                ],
+            ),
+            (
+                "<p><!--a",
+                vec![p.clone(), HtmlToken::Comment("a".to_string())],
+            ),
+        ] {
+            let tokenizer = HtmlTokenizer::new(Rc::downgrade(&browser), html.to_string());
+            assert_eq!(expected, tokenizer.collect::<Vec<HtmlToken>>(), "{}", html);
+        }
+    }
+
+    fn attribute(name: &str, value: &str) -> Attribute {
+        let mut attr = Attribute::new();
+        for c in name.chars() {
+            attr.add_char(c, true);
+        }
+        for c in value.chars() {
+            attr.add_char(c, false);
+        }
+        attr
+    }
+
+    #[test]
+    fn test_character_reference() {
+        let browser = Browser::new();
+        let html = "a&nbsp;&lt;&#65;&#x42;&unknown;&b".to_string();
+        let tokenizer = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+        let text: String = tokenizer
+            .filter_map(|t| match t {
+                HtmlToken::Char(c) => Some(c),
+                _ => None,
+            })
+            .collect();
+        assert_eq!("a\u{00A0}<AB&unknown;&b", text);
+    }
+
+    #[test]
+    fn test_attribute_forms() {
+        let browser = Browser::new();
+        let html = "<input  type='text'\n\tvalue=a/b\tdisabled name=\"\"\nchecked>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+
+        let expected = HtmlToken::StartTag {
+            tag: "input".to_string(),
+            self_closing: false,
+            attributes: vec![
+                attribute("type", "text"),
+                attribute("value", "a/b"),
+                attribute("disabled", ""),
+                attribute("name", ""),
+                attribute("checked", ""),
+            ],
+        };
+        assert_eq!(Some(expected), tokenizer.next());
+        assert_eq!(None, tokenizer.next());
+    }
+
+    #[test]
+    fn test_unquoted_attribute_at_tag_end() {
+        let browser = Browser::new();
+        let html = "<a href=x>link</a>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+
+        let expected = HtmlToken::StartTag {
+            tag: "a".to_string(),
+            self_closing: false,
+            attributes: vec![attribute("href", "x")],
+        };
+        assert_eq!(Some(expected), tokenizer.next());
+        assert_eq!(Some(HtmlToken::Char('l')), tokenizer.next());
+    }
 }
@@ -1,10 +1,12 @@
 //! https://github.com/estree/estree
 //! https://astexplorer.net/
 
+use crate::error::Error;
 use crate::renderer::js::token::JsLexer;
 use crate::renderer::js::token::Token;
 use alloc::rc::Rc;
 use alloc::string::String;
+use alloc::string::ToString;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::iter::Peekable;
@@ -211,25 +213,16 @@ impl JsParser {
     /// MemberExpression ::= ( ( FunctionExpression | PrimaryExpression ) ( MemberExpressionPart)* )
     ///                    | AllocationExpression
     fn member_expression(&mut self) -> Option<Rc<Node>> {
-        let expr = self.primary_expression();
+        let mut expr = self.primary_expression();
 
-        let t = match self.t.peek() {
-            Some(token) => token,
-            None => return expr,
-        };
-
-        match t {
-            Token::Punctuator(c) => {
-                if c == &'.' {
-                    // consume '.'
-                    assert!(self.t.next().is_some());
-                    return Node::new_member_expression(expr, self.identifier());
-                }
-
-                expr
-            }
-            _ => expr,
+        // A chain such as `a.b.c` is parsed as `(a.b).c`.
+        while let Some(Token::Punctuator('.')) = self.t.peek() {
+            // consume '.'
+            assert!(self.t.next().is_some());
+            expr = Node::new_member_expression(expr, self.identifier());
         }
+
+        expr
     }
 
     /// MemberExpression ::= ( ( FunctionExpression | PrimaryExpression ) ( MemberExpressionPart)* )
@@ -238,27 +231,28 @@ impl JsParser {
     /// Arguments ::= "(" ( ArgumentList )? ")"
     /// CallExpression ::= MemberExpression Arguments ( CallExpressionPart )*
     ///
+    /// CallExpressionPart ::= Arguments | ( "[" Expression "]" ) | ( "." Identifier )
+    ///
     /// LeftHandSideExpression ::= CallExpression | MemberExpression
     fn left_hand_side_expression(&mut self) -> Option<Rc<Node>> {
-        let expr = self.member_expression();
+        let mut expr = self.member_expression();
 
-        let t = match self.t.peek() {
-            Some(token) => token,
-            None => return expr,
-        };
-
-        match t {
-            Token::Punctuator(c) => {
-                if c == &'(' {
+        // A chain such as `a.b().c()` is parsed as `((a.b)().c)()`.
+        loop {
+            match self.t.peek() {
+                Some(Token::Punctuator('(')) => {
                     // consume '('
                     assert!(self.t.next().is_some());
-                    return Node::new_call_expression(expr, self.arguments());
+                    expr = Node::new_call_expression(expr, self.arguments());
                 }
-
-                // return MemberExpression
-                expr
+                Some(Token::Punctuator('.')) => {
+                    // consume '.'
+                    assert!(self.t.next().is_some());
+                    expr = Node::new_member_expression(expr, self.identifier());
+                }
+                // return MemberExpression or CallExpression
+                _ => return expr,
             }
-            _ => expr,
         }
     }
 
@@ -418,25 +412,26 @@ impl JsParser {
     }
 
     /// FunctionBody ::= "{" ( SourceElements )? "}"
+    ///
+    /// Returns None if the body isn't enclosed in curly brackets.
     fn function_body(&mut self) -> Option<Rc<Node>> {
         // consume '{'
         match self.t.next() {
-            Some(t) => match t {
-                Token::Punctuator(c) => assert!(c == '{'),
-                _ => unimplemented!("function should have open curly blacket but got {:?}", t),
-            },
-            None => unimplemented!("function should have open curly blacket but got None"),
+            Some(Token::Punctuator('{')) => {}
+            _ => return None,
         }
 
         let mut body = Vec::new();
         loop {
             // loop until hits '}'
-            if let Some(Token::Punctuator(c)) = self.t.peek() {
-                if c == &'}' {
+            match self.t.peek() {
+                Some(Token::Punctuator('}')) => {
                     // consume '}'
                     assert!(self.t.next().is_some());
                     return Node::new_block_statement(body);
                 }
+                None => return None,
+                _ => {}
             }
 
             body.push(self.source_element());
@@ -472,16 +467,15 @@ impl JsParser {
     }
 
     /// FormalParameterList ::= Identifier ( "," Identifier )*
-    fn parameter_list(&mut self) -> Vec<Option<Rc<Node>>> {
+    ///
+    /// Returns None if the parameters aren't enclosed in parentheses.
+    fn parameter_list(&mut self) -> Option<Vec<Option<Rc<Node>>>> {
         let mut params = Vec::new();
 
         // consume '('
         match self.t.next() {
-            Some(t) => match t {
-                Token::Punctuator(c) => assert!(c == '(', "expect ( but got {:?}", c),
-                _ => unimplemented!("function should have `(` but got {:?}", t),
-            },
-            None => unimplemented!("function should have `(` but got None"),
+            Some(Token::Punctuator('(')) => {}
+            _ => return None,
         }
 
         loop {
@@ -492,7 +486,7 @@ impl JsParser {
                         if c == &')' {
                             // consume ')'
                             assert!(self.t.next().is_some());
-                            return params;
+                            return Some(params);
                         }
                         if c == &',' {
                             // consume ','
@@ -503,7 +497,7 @@ impl JsParser {
                         params.push(self.identifier());
                     }
                 },
-                None => return params,
+                None => return None,
             }
         }
     }
@@ -511,8 +505,9 @@ impl JsParser {
     /// FunctionDeclaration ::= "function" Identifier ( "(" ( FormalParameterList )? ")" ) FunctionBody
     fn function_declaration(&mut self) -> Option<Rc<Node>> {
         let id = self.identifier();
-        let params = self.parameter_list();
-        Node::new_function_declaration(id, params, self.function_body())
+        let params = self.parameter_list()?;
+        let body = self.function_body()?;
+        Node::new_function_declaration(id, params, Some(body))
     }
 
     /// SourceElement ::= FunctionDeclaration | Statement
@@ -560,6 +555,27 @@ impl JsParser {
             }
         }
     }
+
+    /// Parses the whole input like `parse_ast`, but returns an error instead of dropping the
+    /// rest of the input when a source element can't be parsed.
+    pub fn parse_program(&mut self) -> Result<Program, Error> {
+        let mut program = Program::new();
+        let mut body = Vec::new();
+
+        while self.t.peek().is_some() {
+            match self.source_element() {
+                Some(n) if *n != Node::ExpressionStatement(None) => body.push(n),
+                _ => {
+                    return Err(Error::UnexpectedInput(
+                        "failed to parse the script".to_string(),
+                    ))
+                }
+            }
+        }
+
+        program.set_body(body);
+        Ok(program)
+    }
 }
 
 #[cfg(test)]
@@ -805,4 +821,23 @@ mod tests {
         expected.set_body(body);
         assert_eq!(expected, parser.parse_ast());
     }
+
+    #[test]
+    fn test_member_expression_chain() {
+        let input = "event.target.id".to_string();
+        let lexer = JsLexer::new(input);
+        let mut parser = JsParser::new(lexer);
+        let mut expected = Program::new();
+        let body = vec![Rc::new(Node::ExpressionStatement(Some(Rc::new(
+            Node::MemberExpression {
+                object: Some(Rc::new(Node::MemberExpression {
+                    object: Some(Rc::new(Node::Identifier("event".to_string()))),
+                    property: Some(Rc::new(Node::Identifier("target".to_string()))),
+                })),
+                property: Some(Rc::new(Node::Identifier("id".to_string()))),
+            },
+        ))))];
+        expected.set_body(body);
+        assert_eq!(expected, parser.parse_ast());
+    }
 }
@@ -28,6 +28,11 @@ pub enum RuntimeValue {
         object: Rc<RefCell<DomNode>>,
         property: Option<String>,
     },
+    /// https://dom.spec.whatwg.org/#interface-event
+    Event {
+        target: Rc<RefCell<DomNode>>,
+        property: Option<String>,
+    },
 }
 
 impl Display for RuntimeValue {
@@ -41,6 +46,10 @@ impl Display for RuntimeValue {
             } => {
                 format!("HtmlElement: {:#?}", object)
             }
+            RuntimeValue::Event {
+                target: _,
+                property: _,
+            } => "[object Event]".to_string(),
         };
         write!(f, "{}", s)
     }
@@ -61,6 +70,10 @@ impl PartialEq for RuntimeValue {
                 object: _,
                 property: _,
             } => false,
+            RuntimeValue::Event {
+                target: _,
+                property: _,
+            } => false,
         }
     }
 }
@@ -156,6 +169,14 @@ impl Function {
 pub struct JsRuntime {
     dom_root: Rc<RefCell<DomNode>>,
     dom_modified: bool,
+    /// True if `innerHTML` inserted markup. It's kept as a text node until the HTML parser
+    /// parses the serialized DOM tree again.
+    markup_inserted: bool,
+    /// The canceled flag of the event being dispatched.
+    /// https://dom.spec.whatwg.org/#canceled-flag
+    default_prevented: bool,
+    /// True while a program from a console is evaluated.
+    in_console: bool,
     functions: Vec<Function>,
     env: Rc<RefCell<Environment>>,
 }
@@ -174,6 +195,9 @@ impl JsRuntime {
         Self {
             dom_root,
             dom_modified: false,
+            markup_inserted: false,
+            default_prevented: false,
+            in_console: false,
             functions: Vec::new(),
             env: Rc::new(RefCell::new(env)),
         }
@@ -187,6 +211,10 @@ impl JsRuntime {
         self.dom_modified
     }
 
+    pub fn markup_inserted(&self) -> bool {
+        self.markup_inserted
+    }
+
     /// https://developer.mozilla.org/en-US/docs/Web/API
     ///
     /// returns a tuple (bool, Option<RuntimeValue>)
@@ -199,7 +227,7 @@ impl JsRuntime {
         env: Rc<RefCell<Environment>>,
     ) -> (bool, Option<RuntimeValue>) {
         if func == &RuntimeValue::StringLiteral("console.log".to_string()) {
-            match self.eval(&arguments[0], env.clone()) {
+            match self.eval(&argument(arguments, 0), env.clone()) {
                 Some(_arg) => {
                     //panic!("[console.log] {:?}", arg.to_string());
                     return (true, None);
@@ -208,13 +236,92 @@ impl JsRuntime {
             }
         }
 
-        if let RuntimeValue::HtmlElement {
-            object: _,
+        // https://dom.spec.whatwg.org/#dom-event-preventdefault
+        if let RuntimeValue::Event {
+            target: _,
             property,
         } = func
         {
+            if property == &Some("preventDefault".to_string()) {
+                self.default_prevented = true;
+                return (true, None);
+            }
+        }
+
+        // A console calls `getElementById` on the document.
+        if self.in_console && func == &RuntimeValue::StringLiteral("getElementById".to_string()) {
+            let func = RuntimeValue::HtmlElement {
+                object: self.dom_root.clone(),
+                property: Some("getElementById".to_string()),
+            };
+            return self.call_browser_api(&func, arguments, env);
+        }
+
+        if let RuntimeValue::HtmlElement { object, property } = func {
+            // https://dom.spec.whatwg.org/#dom-element-getattribute
+            if property == &Some("getAttribute".to_string()) {
+                let name = match self.eval(&argument(arguments, 0), env.clone()) {
+                    Some(a) => a.to_string(),
+                    None => return (true, None),
+                };
+                let value = RefCell::borrow(object)
+                    .get_element()
+                    .and_then(|e| e.get_attribute(&name));
+                return (true, value.map(RuntimeValue::StringLiteral));
+            }
+
+            // https://dom.spec.whatwg.org/#dom-element-setattribute
+            if property == &Some("setAttribute".to_string()) {
+                let name = match self.eval(&argument(arguments, 0), env.clone()) {
+                    Some(a) => a.to_string(),
+                    None => return (true, None),
+                };
+                let value = match self.eval(&argument(arguments, 1), env.clone()) {
+                    Some(a) => a.to_string(),
+                    None => return (true, None),
+                };
+                if let DomNodeKind::Element(ref mut e) = object.borrow_mut().kind {
+                    // Only a change needs to re-render the page.
+                    if e.set_attribute(&name, &value) {
+                        self.dom_modified = true;
+                    }
+                }
+                return (true, None);
+            }
+
+            // https://dom.spec.whatwg.org/#dom-element-classlist
+            if let Some(method) = property.as_ref().and_then(|p| p.strip_prefix("classList.")) {
+                let token = match self.eval(&argument(arguments, 0), env.clone()) {
+                    Some(a) => a.to_string(),
+                    None => return (true, None),
+                };
+                if let DomNodeKind::Element(ref mut e) = object.borrow_mut().kind {
+                    let mut classes: Vec<String> = e
+                        .get_attribute("class")
+                        .unwrap_or_default()
+                        .split_ascii_whitespace()
+                        .map(|c| c.to_string())
+                        .collect();
+                    let exists = classes.contains(&token);
+                    match method {
+                        // https://dom.spec.whatwg.org/#dom-domtokenlist-add
+                        "add" if !exists => classes.push(token),
+                        // https://dom.spec.whatwg.org/#dom-domtokenlist-remove
+                        "remove" => classes.retain(|c| c != &token),
+                        // https://dom.spec.whatwg.org/#dom-domtokenlist-toggle
+                        "toggle" if exists => classes.retain(|c| c != &token),
+                        "toggle" => classes.push(token),
+                        _ => {}
+                    }
+                    if e.set_attribute("class", &classes.join(" ")) {
+                        self.dom_modified = true;
+                    }
+                }
+                return (true, None);
+            }
+
             if property == &Some("getElementById".to_string()) {
-                let arg = match self.eval(&arguments[0], env.clone()) {
+                let arg = match self.eval(&argument(arguments, 0), env.clone()) {
                     Some(a) => a,
                     None => return (true, None),
                 };
@@ -323,6 +430,35 @@ impl JsRuntime {
                     }
                 }
 
+                // `el.dataset.fooBar = "baz";` sets the `data-foo-bar` attribute.
+                // https://html.spec.whatwg.org/multipage/dom.html#dom-domstringmap-setitem
+                if let Some(node) = left {
+                    if let Node::MemberExpression { object, property } = node.borrow() {
+                        if let Some(RuntimeValue::HtmlElement {
+                            object: element,
+                            property: Some(p),
+                        }) = self.eval(object, env.clone())
+                        {
+                            if p == "dataset" {
+                                let name = match self.eval(property, env.clone()) {
+                                    Some(name) => dataset_attribute_name(&name.to_string()),
+                                    None => return None,
+                                };
+                                let value = match self.eval(right, env.clone()) {
+                                    Some(value) => value.to_string(),
+                                    None => return None,
+                                };
+                                if let DomNodeKind::Element(ref mut e) = element.borrow_mut().kind {
+                                    if e.set_attribute(&name, &value) {
+                                        self.dom_modified = true;
+                                    }
+                                }
+                                return None;
+                            }
+                        }
+                    }
+                }
+
                 // If the left value is HtmlElement, update DOM.
                 let left_value = match self.eval(&left, env.clone()) {
                     Some(value) => value,
@@ -339,8 +475,7 @@ impl JsRuntime {
                             // this is the implementation of
                             // `document.getElementById("target").textContent = "foobar";`
                             if p == "textContent" {
-                                // Not necessary to set dom_modified=true because only text
-                                // content is changed.
+                                self.dom_modified = true;
                                 object
                                     .borrow_mut()
                                     .set_first_child(Some(Rc::new(RefCell::new(DomNode::new(
@@ -352,6 +487,7 @@ impl JsRuntime {
                             // Currently, an assignment value should be a text like "foobar".
                             if p == "innerHTML" {
                                 self.dom_modified = true;
+                                self.markup_inserted = true;
                                 object
                                     .borrow_mut()
                                     .set_first_child(Some(Rc::new(RefCell::new(DomNode::new(
@@ -378,11 +514,42 @@ impl JsRuntime {
                 match object_value {
                     // return html element for DOM manipulation
                     RuntimeValue::HtmlElement { object, property } => {
-                        assert!(property.is_none());
-
-                        // set `property` to the HtmlElement value.
+                        // set `property` to the HtmlElement value. A nested property such as
+                        // `classList.add` is joined with '.'.
+                        let property = match property {
+                            Some(p) => format!("{}.{}", p, property_value),
+                            None => property_value.to_string(),
+                        };
+                        // `el.dataset.fooBar` reads the `data-foo-bar` attribute.
+                        // https://html.spec.whatwg.org/multipage/dom.html#dom-domstringmap-nameditem
+                        if let Some(name) = property.strip_prefix("dataset.") {
+                            let name = dataset_attribute_name(name);
+                            return RefCell::borrow(&object)
+                                .get_element()
+                                .and_then(|e| e.get_attribute(&name))
+                                .map(RuntimeValue::StringLiteral);
+                        }
                         Some(RuntimeValue::HtmlElement {
                             object,
+                            property: Some(property),
+                        })
+                    }
+                    // https://dom.spec.whatwg.org/#dom-event-target
+                    RuntimeValue::Event { target, property } => {
+                        // A member of a member such as `event.foo.bar` isn't supported and is
+                        // undefined.
+                        if property.is_some() {
+                            return None;
+                        }
+
+                        if property_value.to_string() == "target" {
+                            return Some(RuntimeValue::HtmlElement {
+                                object: target,
+                                property: None,
+                            });
+                        }
+                        Some(RuntimeValue::Event {
+                            target,
                             property: Some(property_value.to_string()),
                         })
                     }
@@ -441,21 +608,19 @@ impl JsRuntime {
                         }
                     }
 
-                    match f {
-                        Some(f) => f,
-                        None => unimplemented!("function {:?} doesn't exist", callee),
-                    }
+                    // Calling an undefined function throws a TypeError, which isn't supported
+                    // yet, so the call is undefined.
+                    f?
                 };
 
-                // assign arguments to params as local variables
-                assert!(arguments.len() == function.params.len());
-                for (i, item) in arguments.iter().enumerate() {
+                // assign arguments to params as local variables. A missing argument is
+                // undefined, and an extra argument is ignored.
+                for (i, param) in function.params.iter().enumerate() {
                     if let Some(RuntimeValue::StringLiteral(name)) =
-                        self.eval(&function.params[i], new_env.clone())
+                        self.eval(param, new_env.clone())
                     {
-                        new_env
-                            .borrow_mut()
-                            .add_variable(name, self.eval(item, new_env.clone()));
+                        let value = self.eval(&argument(arguments, i), new_env.clone());
+                        new_env.borrow_mut().add_variable(name, value);
                     }
                 }
 
@@ -477,10 +642,70 @@ impl JsRuntime {
     }
 
     pub fn execute(&mut self, program: &Program) {
+        self.evaluate(program);
+    }
+
+    /// Runs `program` and returns the value of its last statement, e.g. for a console. None
+    /// means the value is undefined.
+    pub fn evaluate(&mut self, program: &Program) -> Option<RuntimeValue> {
+        let mut result = None;
+        for node in program.body() {
+            result = self.eval(&Some(node.clone()), self.env.clone());
+        }
+        result
+    }
+
+    /// Runs `program` from a console like `evaluate`. As in the command line API of devtools,
+    /// `getElementById` can be called without `document`.
+    /// https://developer.chrome.com/docs/devtools/console/utilities
+    pub fn evaluate_in_console(&mut self, program: &Program) -> Option<RuntimeValue> {
+        self.in_console = true;
+        let result = self.evaluate(program);
+        self.in_console = false;
+        result
+    }
+
+    /// Runs an event handler `program` with an `event` variable whose target is `target`.
+    /// Returns true if the handler called `event.preventDefault()`.
+    /// https://html.spec.whatwg.org/multipage/webappapis.html#the-event-handler-processing-algorithm
+    pub fn run_event_handler(&mut self, program: &Program, target: Rc<RefCell<DomNode>>) -> bool {
+        self.default_prevented = false;
+
+        let env = Rc::new(RefCell::new(Environment::new(Some(self.env.clone()))));
+        env.borrow_mut().add_variable(
+            "event".to_string(),
+            Some(RuntimeValue::Event {
+                target,
+                property: None,
+            }),
+        );
         for node in program.body() {
-            self.eval(&Some(node.clone()), self.env.clone());
+            self.eval(&Some(node.clone()), env.clone());
+        }
+
+        self.default_prevented
+    }
+}
+
+/// Returns the `i`-th argument of a call, or None if it's not given, which is undefined.
+fn argument(arguments: &[Option<Rc<Node>>], i: usize) -> Option<Rc<Node>> {
+    arguments.get(i).cloned().flatten()
+}
+
+/// Converts a property name of `dataset` to the name of its custom data attribute, e.g.
+/// `userId` to `data-user-id`.
+/// https://html.spec.whatwg.org/multipage/dom.html#dom-domstringmap-setitem
+fn dataset_attribute_name(property: &str) -> String {
+    let mut name = String::from("data-");
+    for c in property.chars() {
+        if c.is_ascii_uppercase() {
+            name.push('-');
+            name.push(c.to_ascii_lowercase());
+        } else {
+            name.push(c);
         }
     }
+    name
 }
 
 #[cfg(test)]
@@ -488,6 +713,7 @@ mod tests {
     use super::*;
     use crate::renderer::js::ast::JsParser;
     use crate::renderer::js::token::JsLexer;
+    use alloc::vec;
 
     #[test]
     fn test_num() {
@@ -689,4 +915,83 @@ mod tests {
             i += 1;
         }
     }
+
+    #[test]
+    fn test_attribute_api() {
+        let dom = Rc::new(RefCell::new(DomNode::new(DomNodeKind::Document)));
+        let a = Rc::new(RefCell::new(DomNode::new(DomNodeKind::Element(
+            crate::renderer::dom::node::Element::new("a", Vec::new()),
+        ))));
+        let input = "el.setAttribute(\"class\", \"link\"); el.getAttribute(\"class\")".to_string();
+        let lexer = JsLexer::new(input);
+        let mut parser = JsParser::new(lexer);
+        let ast = parser.parse_ast();
+        let mut runtime = JsRuntime::new(dom);
+        runtime.env.borrow_mut().add_variable(
+            "el".to_string(),
+            Some(RuntimeValue::HtmlElement {
+                object: a.clone(),
+                property: None,
+            }),
+        );
+        let expected = [None, Some(RuntimeValue::StringLiteral("link".to_string()))];
+
+        for (i, node) in ast.body().iter().enumerate() {
+            let result = runtime.eval(&Some(node.clone()), runtime.env.clone());
+            assert_eq!(expected[i], result);
+        }
+        assert!(runtime.dom_modified());
+        assert_eq!(
+            Some("link".to_string()),
+            RefCell::borrow(&a)
+                .get_element()
+                .expect("should be an element")
+                .get_attribute("class")
+        );
+    }
+
+    #[test]
+    fn test_dataset() {
+        let dom = Rc::new(RefCell::new(DomNode::new(DomNodeKind::Document)));
+        let mut attribute = crate::renderer::html::attribute::Attribute::new();
+        for c in "data-user-id".chars() {
+            attribute.add_char(c, true);
+        }
+        for c in "42".chars() {
+            attribute.add_char(c, false);
+        }
+        let div = Rc::new(RefCell::new(DomNode::new(DomNodeKind::Element(
+            crate::renderer::dom::node::Element::new("div", vec![attribute]),
+        ))));
+        let input = "el.dataset.userId; el.dataset.userId = \"7\"; el.dataset.userId".to_string();
+        let lexer = JsLexer::new(input);
+        let mut parser = JsParser::new(lexer);
+        let ast = parser.parse_ast();
+        let mut runtime = JsRuntime::new(dom);
+        runtime.env.borrow_mut().add_variable(
+            "el".to_string(),
+            Some(RuntimeValue::HtmlElement {
+                object: div.clone(),
+                property: None,
+            }),
+        );
+        let expected = [
+            Some(RuntimeValue::StringLiteral("42".to_string())),
+            None,
+            Some(RuntimeValue::StringLiteral("7".to_string())),
+        ];
+
+        for (i, node) in ast.body().iter().enumerate() {
+            let result = runtime.eval(&Some(node.clone()), runtime.env.clone());
+            assert_eq!(expected[i], result);
+        }
+        assert!(runtime.dom_modified());
+        assert_eq!(
+            Some("7".to_string()),
+            RefCell::borrow(&div)
+                .get_element()
+                .expect("should be an element")
+                .get_attribute("data-user-id")
+        );
+    }
 }
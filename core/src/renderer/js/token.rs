@@ -1,5 +1,7 @@
 //! https://262.ecma-international.org/12.0/#sec-ecmascript-language-lexical-grammar
 
+use crate::error::Error;
+use alloc::format;
 use alloc::string::String;
 use alloc::string::ToString;
 use alloc::vec::Vec;
@@ -56,7 +58,8 @@ impl JsLexer {
         num
     }
 
-    fn consume_string(&mut self) -> String {
+    /// Consumes a string literal which is enclosed in `quote`.
+    fn consume_string(&mut self, quote: char) -> String {
         let mut result = String::new();
         self.pos += 1;
 
@@ -65,7 +68,7 @@ impl JsLexer {
                 return result;
             }
 
-            if self.input[self.pos] == '"' {
+            if self.input[self.pos] == quote {
                 self.pos += 1;
                 return result;
             }
@@ -94,6 +97,10 @@ impl JsLexer {
     }
 
     fn contains(&self, keyword: &str) -> bool {
+        if self.pos + keyword.len() > self.input.len() {
+            return false;
+        }
+
         for i in 0..keyword.len() {
             if keyword
                 .chars()
@@ -117,14 +124,12 @@ impl JsLexer {
 
         None
     }
-}
 
-impl Iterator for JsLexer {
-    type Item = Token;
-
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Returns the next token, or None at the end of the input. A character which isn't
+    /// supported yet is an error.
+    fn next_token(&mut self) -> Result<Option<Token>, Error> {
         if self.pos >= self.input.len() {
-            return None;
+            return Ok(None);
         }
 
         // skip a white space and a new line
@@ -132,14 +137,14 @@ impl Iterator for JsLexer {
             self.pos += 1;
 
             if self.pos >= self.input.len() {
-                return None;
+                return Ok(None);
             }
         }
 
         if let Some(keyword) = self.check_reserved_word() {
             self.pos += keyword.len();
             let token = Some(Token::Keyword(keyword));
-            return token;
+            return Ok(token);
         }
 
         let c = self.input[self.pos];
@@ -150,14 +155,40 @@ impl Iterator for JsLexer {
                 self.pos += 1;
                 t
             }
-            '"' => Token::StringLiteral(self.consume_string()),
+            // https://262.ecma-international.org/12.0/#prod-StringLiteral
+            '"' | '\'' => Token::StringLiteral(self.consume_string(c)),
             '0'..='9' => Token::Number(self.consume_number()),
             // https://262.ecma-international.org/12.0/#prod-IdentifierStart
             'a'..='z' | 'A'..='Z' | '_' | '$' => Token::Identifier(self.consume_identifier()),
-            _ => unimplemented!("char {:?} is not supported yet", c),
+            _ => {
+                return Err(Error::UnexpectedInput(format!(
+                    "char {:?} is not supported yet",
+                    c
+                )))
+            }
         };
 
-        Some(token)
+        Ok(Some(token))
+    }
+
+    /// Returns all tokens of the input, or an error if the input has a character which isn't
+    /// supported yet.
+    pub fn tokenize(mut self) -> Result<Vec<Token>, Error> {
+        let mut tokens = Vec::new();
+        while let Some(token) = self.next_token()? {
+            tokens.push(token);
+        }
+        Ok(tokens)
+    }
+}
+
+impl Iterator for JsLexer {
+    type Item = Token;
+
+    /// Returns the next token. The input is treated as ended at a character which isn't supported
+    /// yet, so use `tokenize` to detect the error.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_token().unwrap_or(None)
     }
 }
 
@@ -198,6 +229,26 @@ mod tests {
         assert!(lexer.peek().is_none());
     }
 
+    #[test]
+    fn test_single_quoted_string() {
+        let input = "'foo \"bar\"'".to_string();
+        let mut lexer = JsLexer::new(input).peekable();
+        assert_eq!(
+            Some(Token::StringLiteral("foo \"bar\"".to_string())),
+            lexer.next()
+        );
+        assert!(lexer.peek().is_none());
+    }
+
+    #[test]
+    fn test_unsupported_char() {
+        let input = "1 * 2".to_string();
+        assert!(JsLexer::new(input.clone()).tokenize().is_err());
+        // The iterator ends at the unsupported char.
+        let tokens: Vec<Token> = JsLexer::new(input).collect();
+        assert_eq!([Token::Number(1)].to_vec(), tokens);
+    }
+
     #[test]
     fn test_add_nums() {
         let input = "1 + 2".to_string();
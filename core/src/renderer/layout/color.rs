@@ -133,6 +133,33 @@ impl Color {
         })
     }
 
+    /// Creates a color from 8-bit RGB channels.
+    pub fn from_rgb_u8(r: u8, g: u8, b: u8) -> Self {
+        let code = format!("#{:02x}{:02x}{:02x}", r, g, b);
+        // Reuse the name of a supported color if any.
+        match Self::from_code(&code) {
+            Ok(color) => color,
+            Err(_) => Self {
+                name: None,
+                code,
+                rgb: (r as f64 / 255f64, g as f64 / 255f64, b as f64 / 255f64),
+            },
+        }
+    }
+
+    /// Returns this color drawn with `alpha` (0.0-1.0) over `backdrop`.
+    /// https://www.w3.org/TR/compositing-1/#simplealphacompositing
+    pub fn blend(&self, backdrop: &Color, alpha: f64) -> Self {
+        let source = self.code_u32();
+        let destination = backdrop.code_u32();
+        let channel = |shift: u32| {
+            let s = ((source >> shift) & 0xff) as f64;
+            let d = ((destination >> shift) & 0xff) as f64;
+            (s * alpha + d * (1.0 - alpha) + 0.5) as u8
+        };
+        Self::from_rgb_u8(channel(16), channel(8), channel(0))
+    }
+
     pub fn white() -> Self {
         Self {
             name: Some("white".to_string()),
@@ -9,20 +9,43 @@ use crate::renderer::dom::node::NodeKind;
 use crate::renderer::layout::color::*;
 use alloc::format;
 use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
 use core::cell::RefCell;
+use core::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ComputedStyle {
+    align_items: Option<AlignItems>,
     background_color: Option<Color>,
+    border: Option<Border>,
+    border_radius: Option<BorderRadius>,
     color: Option<Color>,
+    cursor: Option<Cursor>,
+    direction: Option<Direction>,
     display: Option<DisplayType>,
+    flex_direction: Option<FlexDirection>,
+    flex_grow: Option<f64>,
+    flex_wrap: Option<FlexWrap>,
+    font_family: Option<FontFamily>,
     font_size: Option<FontSize>,
+    font_style: Option<FontStyle>,
+    font_weight: Option<FontWeight>,
     height: Option<f64>,
+    justify_content: Option<JustifyContent>,
     margin: Option<BoxInfo>,
+    /// True if the left and right margins are `auto`.
+    margin_auto: Option<bool>,
+    max_width: Option<MaxWidth>,
+    opacity: Option<f64>,
     padding: Option<BoxInfo>,
-    text_decoration: Option<TextDecoration>,
+    text_align: Option<TextAlign>,
+    text_decoration: Option<TextDecorations>,
     white_space: Option<WhiteSpace>,
     width: Option<f64>,
+    /// Properties whose value is `initial` or `inherit`. They're resolved in defaulting.
+    css_wide_keywords: Vec<(String, CssWideKeyword)>,
 }
 
 impl Default for ComputedStyle {
@@ -51,26 +74,159 @@ impl ComputedStyle {
     pub fn new() -> Self {
         // It may be better to handle cascading, defaulting and inheritance here.
         Self {
+            align_items: None,
             background_color: None,
+            border: None,
+            border_radius: None,
             color: None,
+            cursor: None,
+            direction: None,
             display: None,
+            flex_direction: None,
+            flex_grow: None,
+            flex_wrap: None,
+            font_family: None,
             font_size: None,
+            font_style: None,
+            font_weight: None,
             height: None,
+            justify_content: None,
             margin: None,
+            margin_auto: None,
+            max_width: None,
+            opacity: None,
             padding: None,
+            text_align: None,
             text_decoration: None,
             white_space: None,
             width: None,
+            css_wide_keywords: Vec::new(),
         }
     }
 
+    /// Makes `property` take its initial value or its parent's value, overriding the earlier
+    /// declarations. It's resolved in `defaulting()`.
+    /// https://www.w3.org/TR/css-cascade-4/#defaulting-keywords
+    pub fn set_css_wide_keyword(
+        &mut self,
+        property: &str,
+        keyword: CssWideKeyword,
+    ) -> Result<(), Error> {
+        // Reset the cascaded value.
+        self.copy_property(property, &ComputedStyle::new())?;
+        self.clear_css_wide_keyword(property);
+        self.css_wide_keywords.push((property.to_string(), keyword));
+        Ok(())
+    }
+
+    /// Cancels `initial` or `inherit` for `property` when a later declaration sets a value.
+    pub fn clear_css_wide_keyword(&mut self, property: &str) {
+        self.css_wide_keywords.retain(|(p, _)| p != property);
+    }
+
+    /// Copies the value of `property` from `source`.
+    fn copy_property(&mut self, property: &str, source: &ComputedStyle) -> Result<(), Error> {
+        match property {
+            "align-items" => self.align_items = source.align_items,
+            "background-color" => self.background_color = source.background_color.clone(),
+            "border" => self.border = source.border.clone(),
+            "border-radius" => self.border_radius = source.border_radius,
+            "color" => self.color = source.color.clone(),
+            "cursor" => self.cursor = source.cursor,
+            "direction" => self.direction = source.direction,
+            "display" => self.display = source.display,
+            "flex-direction" => self.flex_direction = source.flex_direction,
+            "flex-grow" => self.flex_grow = source.flex_grow,
+            "flex-wrap" => self.flex_wrap = source.flex_wrap,
+            "font-family" => self.font_family = source.font_family,
+            "font-size" => self.font_size = source.font_size,
+            "font-style" => self.font_style = source.font_style,
+            "font-weight" => self.font_weight = source.font_weight,
+            "height" => self.height = source.height,
+            "justify-content" => self.justify_content = source.justify_content,
+            "margin" => {
+                self.margin = source.margin;
+                self.margin_auto = source.margin_auto;
+            }
+            "max-width" => self.max_width = source.max_width,
+            "opacity" => self.opacity = source.opacity,
+            "text-align" => self.text_align = source.text_align,
+            "text-decoration" => self.text_decoration = source.text_decoration,
+            "white-space" => self.white_space = source.white_space,
+            "width" => self.width = source.width,
+            _ => {
+                return Err(Error::UnexpectedInput(format!(
+                    "css property {} doesn't support initial or inherit yet",
+                    property
+                )))
+            }
+        }
+        Ok(())
+    }
+
     /// https://www.w3.org/TR/css-cascade-4/#defaulting
     /// https://www.w3.org/TR/css-cascade-4/#inheriting
     /// If there is no cascading value, use the default value.
-    pub fn defaulting(&mut self, node: &Rc<RefCell<Node>>, parent_style: Option<ComputedStyle>) {
+    /// Resolves CSS-wide keywords, inherits properties from `parent_style` and fills the rest
+    /// with initial values. The initial font size is scaled by `font_scale`.
+    pub fn defaulting(
+        &mut self,
+        node: &Rc<RefCell<Node>>,
+        parent_style: Option<ComputedStyle>,
+        font_scale: f64,
+    ) {
+        // https://www.w3.org/TR/css-cascade-4/#defaulting-keywords
+        // `inherit` takes the parent's computed value, and `initial` takes the initial value. The
+        // root element inherits the initial value.
+        for (property, keyword) in core::mem::take(&mut self.css_wide_keywords) {
+            let source = match (keyword, &parent_style) {
+                (CssWideKeyword::Inherit, Some(parent_style)) => parent_style.clone(),
+                _ => {
+                    let mut initial = ComputedStyle::new();
+                    initial.defaulting(node, None, font_scale);
+                    initial
+                }
+            };
+            // The property is validated when the keyword is set.
+            let _ = self.copy_property(&property, &source);
+        }
+
+        // https://html.spec.whatwg.org/multipage/rendering.html#bidi-rendering
+        // The `dir` attribute is mapped to the `direction` property unless CSS sets it.
+        if self.direction.is_none() {
+            if let NodeKind::Element(e) = node.borrow().kind() {
+                if let Some(dir) = e.get_attribute("dir") {
+                    self.direction = Direction::from_str(&dir.to_ascii_lowercase()).ok();
+                }
+            }
+        }
+
+        // https://html.spec.whatwg.org/multipage/rendering.html#phrasing-content-3
+        // The `color` and `size` attributes of a font element are mapped to the `color` and
+        // `font-size` properties unless CSS sets them.
+        if let NodeKind::Element(e) = node.borrow().kind() {
+            if e.kind() == ElementKind::Font {
+                if self.color.is_none() {
+                    self.color = e.get_attribute("color").and_then(|color| {
+                        let color = color.trim().to_ascii_lowercase();
+                        Color::from_name(&color)
+                            .or_else(|_| Color::from_code(&color))
+                            .ok()
+                    });
+                }
+                if self.font_size.is_none() {
+                    self.font_size = e
+                        .get_attribute("size")
+                        .and_then(|size| legacy_font_size(&size));
+                }
+            }
+        }
+
         // If the parent exists and a CSS property doesn't have a default value, inherit the value.
         if let Some(parent_style) = parent_style {
-            // currently, only inherit `background_color`, `color`, `font_size` and `text_decoration`.
+            // currently, only inherit `background_color`, `color`, `cursor`, `direction`,
+            // `font_family`, `font_size`, `font_style`, `font_weight`, `text_align`,
+            // `text_decoration` and `white_space`.
             if self.background_color.is_none() && parent_style.background_color() != Color::white()
             {
                 self.background_color = Some(parent_style.background_color());
@@ -78,32 +234,87 @@ impl ComputedStyle {
             if self.color.is_none() && parent_style.color() != Color::black() {
                 self.color = Some(parent_style.color());
             }
+            if self.cursor.is_none() && parent_style.cursor() != Cursor::Auto {
+                self.cursor = Some(parent_style.cursor());
+            }
+            if self.direction.is_none() && parent_style.direction() != Direction::Ltr {
+                self.direction = Some(parent_style.direction());
+            }
+            if self.font_family.is_none() && parent_style.font_family() != FontFamily::default() {
+                self.font_family = Some(parent_style.font_family());
+            }
             if self.font_size.is_none() && parent_style.font_size() != FontSize::Medium {
                 self.font_size = Some(parent_style.font_size());
             }
-            if self.text_decoration.is_none()
-                && parent_style.text_decoration() != TextDecoration::None
-            {
-                self.text_decoration = Some(parent_style.text_decoration());
+            if self.font_style.is_none() && parent_style.font_style() != FontStyle::Normal {
+                self.font_style = Some(parent_style.font_style());
+            }
+            if self.font_weight.is_none() && parent_style.font_weight() != FontWeight::Normal {
+                self.font_weight = Some(parent_style.font_weight());
+            }
+            if self.text_align.is_none() && parent_style.text_align() != TextAlign::Start {
+                self.text_align = Some(parent_style.text_align());
+            }
+            if self.text_decoration.is_none() && !parent_style.text_decorations().is_none() {
+                self.text_decoration = Some(parent_style.text_decorations());
+            }
+            if self.white_space.is_none() && parent_style.white_space() != WhiteSpace::Normal {
+                self.white_space = Some(parent_style.white_space());
             }
         }
 
+        if self.align_items.is_none() {
+            self.align_items = Some(AlignItems::default());
+        }
         if self.background_color.is_none() {
             self.background_color = Some(Color::white());
         }
+        if self.border.is_none() {
+            self.border = Some(Border::default());
+        }
+        if self.border_radius.is_none() {
+            self.border_radius = Some(BorderRadius::default());
+        }
         if self.color.is_none() {
             self.color = Some(Color::black());
         }
+        if self.cursor.is_none() {
+            self.cursor = Some(Cursor::default());
+        }
+        if self.direction.is_none() {
+            self.direction = Some(Direction::default());
+        }
         if self.display.is_none() {
             self.display = Some(DisplayType::default(node));
         }
+        if self.flex_direction.is_none() {
+            self.flex_direction = Some(FlexDirection::default());
+        }
+        if self.flex_grow.is_none() {
+            self.flex_grow = Some(0.0);
+        }
+        if self.flex_wrap.is_none() {
+            self.flex_wrap = Some(FlexWrap::default());
+        }
+        if self.font_family.is_none() {
+            self.font_family = Some(FontFamily::default());
+        }
         if self.font_size.is_none() {
-            self.font_size = Some(FontSize::default(node));
+            self.font_size = Some(FontSize::default().scaled(font_scale));
+        }
+        if self.font_style.is_none() {
+            self.font_style = Some(FontStyle::default());
+        }
+        if self.font_weight.is_none() {
+            self.font_weight = Some(FontWeight::default());
         }
         if self.height.is_none() {
             // check the default value for height
             self.height = Some(0.0);
         }
+        if self.justify_content.is_none() {
+            self.justify_content = Some(JustifyContent::default());
+        }
         if self.margin.is_none() {
             // check the default value for margin
             self.margin = Some(BoxInfo::new(0.0, 0.0, 0.0, 0.0));
@@ -112,11 +323,23 @@ impl ComputedStyle {
             // check the default value for padding
             self.padding = Some(BoxInfo::new(0.0, 0.0, 0.0, 0.0));
         }
+        if self.text_align.is_none() {
+            self.text_align = Some(TextAlign::default());
+        }
         if self.text_decoration.is_none() {
-            self.text_decoration = Some(TextDecoration::default(node));
+            self.text_decoration = Some(TextDecorations::default());
+        }
+        if self.margin_auto.is_none() {
+            self.margin_auto = Some(false);
+        }
+        if self.max_width.is_none() {
+            self.max_width = Some(MaxWidth::default());
+        }
+        if self.opacity.is_none() {
+            self.opacity = Some(1.0);
         }
         if self.white_space.is_none() {
-            self.white_space = Some(WhiteSpace::default(node));
+            self.white_space = Some(WhiteSpace::default());
         }
         if self.width.is_none() {
             // check the default value for width
@@ -134,6 +357,26 @@ impl ComputedStyle {
             .expect("failed to access CSS property: background_color")
     }
 
+    pub fn set_border(&mut self, border: Border) {
+        self.border = Some(border);
+    }
+
+    pub fn border(&self) -> Border {
+        self.border
+            .clone()
+            .expect("failed to access CSS property: border")
+    }
+
+    pub fn set_border_radius(&mut self, border_radius: BorderRadius) {
+        self.border_radius = Some(border_radius);
+    }
+
+    /// Returns the radii of the corners. The UIs may draw square corners instead.
+    pub fn border_radius(&self) -> BorderRadius {
+        self.border_radius
+            .expect("failed to access CSS property: border_radius")
+    }
+
     pub fn set_color(&mut self, color: Color) {
         self.color = Some(color);
     }
@@ -144,6 +387,15 @@ impl ComputedStyle {
             .expect("failed to access CSS property: color")
     }
 
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = Some(direction);
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+            .expect("failed to access CSS property: direction")
+    }
+
     pub fn set_height(&mut self, height: f64) {
         self.height = Some(height);
     }
@@ -161,6 +413,89 @@ impl ComputedStyle {
             .expect("failed to access CSS property: display")
     }
 
+    /// Returns true if the box is placed as a block box. A flex container and the boxes of a
+    /// table are placed like block boxes too.
+    /// https://www.w3.org/TR/css-display-3/#block-level
+    pub fn is_block(&self) -> bool {
+        matches!(
+            self.display(),
+            DisplayType::Block
+                | DisplayType::Flex
+                | DisplayType::Table
+                | DisplayType::TableRow
+                | DisplayType::TableCell
+        )
+    }
+
+    /// Returns true if the box lays out its children as flex items.
+    /// https://www.w3.org/TR/css-flexbox-1/#flex-containers
+    pub fn is_flex(&self) -> bool {
+        self.display() == DisplayType::Flex
+    }
+
+    /// Returns true if the box is placed in a line like an inline box. An inline-block box is
+    /// placed inline too.
+    /// https://www.w3.org/TR/css-display-3/#inline-level
+    pub fn is_inline(&self) -> bool {
+        matches!(
+            self.display(),
+            DisplayType::Inline | DisplayType::InlineBlock
+        )
+    }
+
+    /// Returns true if the element and its descendants generate no boxes.
+    pub fn is_none(&self) -> bool {
+        self.display() == DisplayType::DisplayNone
+    }
+
+    pub fn set_flex_direction(&mut self, flex_direction: FlexDirection) {
+        self.flex_direction = Some(flex_direction);
+    }
+
+    pub fn flex_direction(&self) -> FlexDirection {
+        self.flex_direction
+            .expect("failed to access CSS property: flex_direction")
+    }
+
+    /// Sets how much the item grows to fill the free space of a flex line. A negative value is
+    /// invalid, so it's clamped to 0.
+    /// https://www.w3.org/TR/css-flexbox-1/#flex-grow-property
+    pub fn set_flex_grow(&mut self, flex_grow: f64) {
+        self.flex_grow = Some(flex_grow.max(0.0));
+    }
+
+    pub fn flex_grow(&self) -> f64 {
+        self.flex_grow
+            .expect("failed to access CSS property: flex_grow")
+    }
+
+    pub fn set_flex_wrap(&mut self, flex_wrap: FlexWrap) {
+        self.flex_wrap = Some(flex_wrap);
+    }
+
+    pub fn flex_wrap(&self) -> FlexWrap {
+        self.flex_wrap
+            .expect("failed to access CSS property: flex_wrap")
+    }
+
+    pub fn set_justify_content(&mut self, justify_content: JustifyContent) {
+        self.justify_content = Some(justify_content);
+    }
+
+    pub fn justify_content(&self) -> JustifyContent {
+        self.justify_content
+            .expect("failed to access CSS property: justify_content")
+    }
+
+    pub fn set_align_items(&mut self, align_items: AlignItems) {
+        self.align_items = Some(align_items);
+    }
+
+    pub fn align_items(&self) -> AlignItems {
+        self.align_items
+            .expect("failed to access CSS property: align_items")
+    }
+
     pub fn set_width(&mut self, width: f64) {
         self.width = Some(width);
     }
@@ -177,6 +512,61 @@ impl ComputedStyle {
         self.margin.expect("failed to access CSS property: margin")
     }
 
+    /// Returns the margin set so far, or the initial value 0 if it's not set yet. It's used to
+    /// update one side of the margin before defaulting.
+    fn margin_or_initial(&self) -> BoxInfo {
+        self.margin.unwrap_or(BoxInfo::new(0.0, 0.0, 0.0, 0.0))
+    }
+
+    pub fn set_margin_top(&mut self, value: f64) {
+        let m = self.margin_or_initial();
+        self.margin = Some(BoxInfo::new(value, m.right, m.left, m.bottom));
+    }
+
+    pub fn set_margin_right(&mut self, value: f64) {
+        let m = self.margin_or_initial();
+        self.margin = Some(BoxInfo::new(m.top, value, m.left, m.bottom));
+    }
+
+    pub fn set_margin_bottom(&mut self, value: f64) {
+        let m = self.margin_or_initial();
+        self.margin = Some(BoxInfo::new(m.top, m.right, m.left, value));
+    }
+
+    pub fn set_margin_left(&mut self, value: f64) {
+        let m = self.margin_or_initial();
+        self.margin = Some(BoxInfo::new(m.top, m.right, value, m.bottom));
+    }
+
+    pub fn set_margin_auto(&mut self, margin_auto: bool) {
+        self.margin_auto = Some(margin_auto);
+    }
+
+    pub fn margin_auto(&self) -> bool {
+        self.margin_auto
+            .expect("failed to access CSS property: margin_auto")
+    }
+
+    pub fn set_max_width(&mut self, max_width: MaxWidth) {
+        self.max_width = Some(max_width);
+    }
+
+    pub fn max_width(&self) -> MaxWidth {
+        self.max_width
+            .expect("failed to access CSS property: max_width")
+    }
+
+    /// Sets the opacity. The value is clamped to the range [0.0, 1.0].
+    /// https://www.w3.org/TR/css-color-4/#transparency
+    pub fn set_opacity(&mut self, opacity: f64) {
+        self.opacity = Some(opacity.clamp(0.0, 1.0));
+    }
+
+    pub fn opacity(&self) -> f64 {
+        self.opacity
+            .expect("failed to access CSS property: opacity")
+    }
+
     pub fn set_padding(&mut self, padding: BoxInfo) {
         self.padding = Some(padding);
     }
@@ -186,6 +576,20 @@ impl ComputedStyle {
             .expect("failed to access CSS property: padding")
     }
 
+    pub fn set_font_family(&mut self, font_family: FontFamily) {
+        self.font_family = Some(font_family);
+    }
+
+    pub fn font_family(&self) -> FontFamily {
+        self.font_family
+            .expect("failed to access CSS property: font_family")
+    }
+
+    /// Returns true if a monospace font is used.
+    pub fn is_monospace(&self) -> bool {
+        self.font_family() == FontFamily::Monospace
+    }
+
     pub fn font_size(&self) -> FontSize {
         self.font_size
             .expect("failed to access CSS property: font_size")
@@ -195,16 +599,80 @@ impl ComputedStyle {
         self.font_size = Some(font_size);
     }
 
+    pub fn font_style(&self) -> FontStyle {
+        self.font_style
+            .expect("failed to access CSS property: font_style")
+    }
+
+    pub fn set_font_style(&mut self, font_style: FontStyle) {
+        self.font_style = Some(font_style);
+    }
+
+    pub fn font_weight(&self) -> FontWeight {
+        self.font_weight
+            .expect("failed to access CSS property: font_weight")
+    }
+
+    pub fn set_font_weight(&mut self, font_weight: FontWeight) {
+        self.font_weight = Some(font_weight);
+    }
+
+    pub fn set_cursor(&mut self, cursor: Cursor) {
+        self.cursor = Some(cursor);
+    }
+
+    pub fn cursor(&self) -> Cursor {
+        self.cursor.expect("failed to access CSS property: cursor")
+    }
+
+    pub fn set_white_space(&mut self, white_space: WhiteSpace) {
+        self.white_space = Some(white_space);
+    }
+
     pub fn white_space(&self) -> WhiteSpace {
         self.white_space
             .expect("failed to access CSS property: white_space")
     }
 
+    pub fn set_text_align(&mut self, text_align: TextAlign) {
+        self.text_align = Some(text_align);
+    }
+
+    pub fn text_align(&self) -> TextAlign {
+        self.text_align
+            .expect("failed to access CSS property: text_align")
+    }
+
+    pub fn set_text_decoration(&mut self, text_decoration: TextDecoration) {
+        self.text_decoration = Some(TextDecorations::from(text_decoration));
+    }
+
+    /// Returns the single decoration line. If there are multiple lines, `Underline` takes
+    /// precedence. Use `text_decorations()` or `has_text_decoration()` to see all of them.
     pub fn text_decoration(&self) -> TextDecoration {
+        let decorations = self.text_decorations();
+        if decorations.contains(TextDecoration::Underline) {
+            TextDecoration::Underline
+        } else if decorations.contains(TextDecoration::LineThrough) {
+            TextDecoration::LineThrough
+        } else {
+            TextDecoration::None
+        }
+    }
+
+    pub fn set_text_decorations(&mut self, text_decorations: TextDecorations) {
+        self.text_decoration = Some(text_decorations);
+    }
+
+    pub fn text_decorations(&self) -> TextDecorations {
         self.text_decoration
             .expect("failed to access CSS property: text_decoration")
     }
 
+    pub fn has_text_decoration(&self, text_decoration: TextDecoration) -> bool {
+        self.text_decorations().contains(text_decoration)
+    }
+
     pub fn margin_top(&self) -> f64 {
         self.margin().top
     }
@@ -244,26 +712,30 @@ pub enum DisplayType {
     Block,
     /// https://www.w3.org/TR/css-display-3/#valdef-display-inline
     Inline,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-inline-block
+    InlineBlock,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-flex
+    Flex,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-table
+    Table,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-table-row
+    TableRow,
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-table-cell
+    TableCell,
     /// https://www.w3.org/TR/css-display-3/#valdef-display-none
     DisplayNone,
 }
 
 impl DisplayType {
+    /// https://www.w3.org/TR/css-display-3/#the-display-properties
+    /// The initial value is `inline`. Block-level elements are defined in the user-agent
+    /// stylesheet.
     fn default(node: &Rc<RefCell<Node>>) -> Self {
         match &node.borrow().kind() {
             NodeKind::Document => DisplayType::Block,
-            NodeKind::Element(e) => {
-                if e.kind() == ElementKind::Script || e.kind() == ElementKind::Style {
-                    return DisplayType::DisplayNone;
-                }
-
-                if e.is_block_element() {
-                    DisplayType::Block
-                } else {
-                    DisplayType::Inline
-                }
-            }
-            NodeKind::Text(_) => DisplayType::Inline,
+            NodeKind::Element(_) | NodeKind::Text(_) => DisplayType::Inline,
+            // A comment is not rendered.
+            NodeKind::Comment(_) => DisplayType::DisplayNone,
         }
     }
 
@@ -271,6 +743,11 @@ impl DisplayType {
         match s {
             "block" => Ok(Self::Block),
             "inline" => Ok(Self::Inline),
+            "inline-block" => Ok(Self::InlineBlock),
+            "flex" => Ok(Self::Flex),
+            "table" => Ok(Self::Table),
+            "table-row" => Ok(Self::TableRow),
+            "table-cell" => Ok(Self::TableCell),
             "none" => Ok(Self::DisplayNone),
             _ => Err(Error::UnexpectedInput(format!(
                 "display {:?} is not supported yet",
@@ -280,6 +757,217 @@ impl DisplayType {
     }
 }
 
+/// https://www.w3.org/TR/css-flexbox-1/#flex-direction-property
+/// Only `row` is supported yet.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FlexDirection {
+    Row,
+}
+
+/// The initial value is `row`.
+impl Default for FlexDirection {
+    fn default() -> Self {
+        FlexDirection::Row
+    }
+}
+
+impl FromStr for FlexDirection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "row" => Ok(Self::Row),
+            _ => Err(Error::UnexpectedInput(format!(
+                "flex-direction {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-flexbox-1/#flex-wrap-property
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FlexWrap {
+    NoWrap,
+    Wrap,
+}
+
+/// The initial value is `nowrap`.
+impl Default for FlexWrap {
+    fn default() -> Self {
+        FlexWrap::NoWrap
+    }
+}
+
+impl FromStr for FlexWrap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nowrap" => Ok(Self::NoWrap),
+            "wrap" => Ok(Self::Wrap),
+            _ => Err(Error::UnexpectedInput(format!(
+                "flex-wrap {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-align-3/#justify-content
+/// How the free space of a flex line is distributed between and around the items.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum JustifyContent {
+    FlexStart,
+    FlexEnd,
+    Center,
+    SpaceBetween,
+}
+
+/// The initial value is `normal`, which behaves as `flex-start` in a flex container.
+impl Default for JustifyContent {
+    fn default() -> Self {
+        JustifyContent::FlexStart
+    }
+}
+
+impl FromStr for JustifyContent {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" | "flex-start" => Ok(Self::FlexStart),
+            "flex-end" => Ok(Self::FlexEnd),
+            "center" => Ok(Self::Center),
+            "space-between" => Ok(Self::SpaceBetween),
+            _ => Err(Error::UnexpectedInput(format!(
+                "justify-content {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+impl JustifyContent {
+    /// Returns the offset of the first item and the space between items for `free` pixels of
+    /// free space on a line of `count` items.
+    /// https://www.w3.org/TR/css-align-3/#distribution-values
+    pub fn distribute(&self, free: i64, count: usize) -> (i64, i64) {
+        match self {
+            JustifyContent::FlexStart => (0, 0),
+            JustifyContent::FlexEnd => (free, 0),
+            JustifyContent::Center => (free / 2, 0),
+            // A single item or overflowing items are placed like `flex-start`.
+            JustifyContent::SpaceBetween if count > 1 && free > 0 => (0, free / (count as i64 - 1)),
+            JustifyContent::SpaceBetween => (0, 0),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-align-3/#align-items-property
+/// Where the items are placed on the cross axis of a flex line.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AlignItems {
+    FlexStart,
+    FlexEnd,
+    Center,
+}
+
+/// The initial value is `normal`, which behaves as `stretch` in a flex container. Stretching is
+/// not supported, so it's treated as `flex-start`.
+impl Default for AlignItems {
+    fn default() -> Self {
+        AlignItems::FlexStart
+    }
+}
+
+impl AlignItems {
+    /// Returns the offset of an item on the cross axis of a line with `free` pixels of space
+    /// left beside the item.
+    pub fn offset(&self, free: i64) -> i64 {
+        match self {
+            AlignItems::FlexStart => 0,
+            AlignItems::FlexEnd => free,
+            AlignItems::Center => free / 2,
+        }
+    }
+}
+
+impl FromStr for AlignItems {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" | "flex-start" => Ok(Self::FlexStart),
+            "flex-end" => Ok(Self::FlexEnd),
+            "center" => Ok(Self::Center),
+            _ => Err(Error::UnexpectedInput(format!(
+                "align-items {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-sizing-3/#max-size-properties
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MaxWidth {
+    None,
+    Px(f64),
+    /// A percentage of the width of the containing block.
+    Percentage(f64),
+}
+
+/// The initial value is `none`.
+impl Default for MaxWidth {
+    fn default() -> Self {
+        MaxWidth::None
+    }
+}
+
+impl MaxWidth {
+    /// Returns the maximum width in pixels for a containing block of `containing_width`, or
+    /// None if the width is not limited.
+    pub fn resolve(&self, containing_width: i64) -> Option<i64> {
+        match self {
+            MaxWidth::None => None,
+            MaxWidth::Px(width) => Some(*width as i64),
+            MaxWidth::Percentage(percentage) => {
+                Some((containing_width as f64 * percentage / 100.0) as i64)
+            }
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-writing-modes-4/#direction
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Direction {
+    Ltr,
+    Rtl,
+}
+
+/// The initial value is `ltr`.
+impl Default for Direction {
+    fn default() -> Self {
+        Direction::Ltr
+    }
+}
+
+impl FromStr for Direction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ltr" => Ok(Self::Ltr),
+            "rtl" => Ok(Self::Rtl),
+            _ => Err(Error::UnexpectedInput(format!(
+                "direction {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct BoxInfo {
     top: f64,
@@ -315,6 +1003,108 @@ impl BoxInfo {
     }
 }
 
+/// The width in px and the color of the borders of a box. All four sides have the same border,
+/// and any line style except `none` and `hidden` is drawn as a solid line.
+/// https://www.w3.org/TR/css-backgrounds-3/#border-shorthands
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Border {
+    width: f64,
+    /// None if the border takes the `color` of the box, i.e. `currentcolor`.
+    color: Option<Color>,
+}
+
+impl Border {
+    pub fn new(width: f64, color: Option<Color>) -> Self {
+        Self { width, color }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn color(&self) -> Option<Color> {
+        self.color.clone()
+    }
+}
+
+/// The radii of the four corners of a box in px. Elliptical corners are not supported.
+/// https://www.w3.org/TR/css-backgrounds-3/#border-radius
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct BorderRadius {
+    top_left: f64,
+    top_right: f64,
+    bottom_right: f64,
+    bottom_left: f64,
+}
+
+impl BorderRadius {
+    pub fn new(top_left: f64, top_right: f64, bottom_right: f64, bottom_left: f64) -> Self {
+        Self {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        }
+    }
+
+    /// Expands the values of the `border-radius` shorthand. One to four values are set in the
+    /// order of top-left, top-right, bottom-right and bottom-left, and omitted corners take the
+    /// value of the opposite corner.
+    /// https://www.w3.org/TR/css-backgrounds-3/#border-radius
+    pub fn from_values(values: &[f64]) -> Option<Self> {
+        match *values {
+            [r] => Some(Self::new(r, r, r, r)),
+            [tl_br, tr_bl] => Some(Self::new(tl_br, tr_bl, tl_br, tr_bl)),
+            [tl, tr_bl, br] => Some(Self::new(tl, tr_bl, br, tr_bl)),
+            [tl, tr, br, bl] => Some(Self::new(tl, tr, br, bl)),
+            _ => None,
+        }
+    }
+
+    pub fn top_left(&self) -> f64 {
+        self.top_left
+    }
+
+    pub fn top_right(&self) -> f64 {
+        self.top_right
+    }
+
+    pub fn bottom_right(&self) -> f64 {
+        self.bottom_right
+    }
+
+    pub fn bottom_left(&self) -> f64 {
+        self.bottom_left
+    }
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-family-prop
+/// The UIs have fixed fonts, so only whether a monospace font is used is tracked.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FontFamily {
+    SansSerif,
+    Monospace,
+}
+
+/// The initial value depends on the user agent.
+impl Default for FontFamily {
+    fn default() -> Self {
+        FontFamily::SansSerif
+    }
+}
+
+impl FontFamily {
+    /// https://www.w3.org/TR/css-fonts-4/#generic-font-families
+    /// Returns `Monospace` for the `monospace` generic family and well-known monospace fonts.
+    pub fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "monospace" | "ui-monospace" | "courier" | "courier new" | "consolas" | "menlo"
+            | "monaco" => FontFamily::Monospace,
+            _ => FontFamily::SansSerif,
+        }
+    }
+}
+
 /// https://www.w3.org/TR/css-fonts-4/#absolute-size-mapping
 /// https://docs.gtk.org/Pango/pango_markup.html align with pango markup syntax
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -324,18 +1114,15 @@ pub enum FontSize {
     XXLarge,
 }
 
-impl FontSize {
-    fn default(node: &Rc<RefCell<Node>>) -> Self {
-        match &node.borrow().kind() {
-            NodeKind::Element(element) => match element.kind() {
-                ElementKind::H1 => FontSize::XXLarge,
-                ElementKind::H2 => FontSize::XLarge,
-                _ => FontSize::Medium,
-            },
-            _ => FontSize::Medium,
-        }
+/// https://www.w3.org/TR/css-fonts-4/#font-size-prop
+/// The initial value is `medium`.
+impl Default for FontSize {
+    fn default() -> Self {
+        FontSize::Medium
     }
+}
 
+impl FontSize {
     /// Convert numeric font size to FontSize enum
     /// This is a simplified mapping - in a real browser, this would be more complex
     pub fn from_number(size: f64) -> Self {
@@ -347,6 +1134,129 @@ impl FontSize {
             FontSize::XXLarge
         }
     }
+
+    /// Returns the font size for text enlarged (or shrunk) by `factor`. Each size is mapped to a
+    /// number in its range of `from_number`, so a factor of 1.0 keeps the size as is.
+    pub fn scaled(self, factor: f64) -> Self {
+        let size = match self {
+            FontSize::Medium => 12.0,
+            FontSize::XLarge => 18.0,
+            FontSize::XXLarge => 24.0,
+        };
+        FontSize::from_number(size * factor)
+    }
+}
+
+impl FromStr for FontSize {
+    type Err = Error;
+
+    /// Maps an `<absolute-size>` keyword to the closest size. Keywords smaller than `medium` are
+    /// treated as `medium` because there's no smaller size.
+    /// https://www.w3.org/TR/css-fonts-4/#valdef-font-size-absolute-size
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "xx-small" | "x-small" | "small" | "medium" => Ok(Self::Medium),
+            "large" | "x-large" => Ok(Self::XLarge),
+            "xx-large" | "xxx-large" => Ok(Self::XXLarge),
+            _ => Err(Error::UnexpectedInput(format!(
+                "font-size {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-style-prop
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+}
+
+/// The initial value is `normal`.
+impl Default for FontStyle {
+    fn default() -> Self {
+        FontStyle::Normal
+    }
+}
+
+impl FromStr for FontStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            // Oblique faces are not distinguished from italic ones.
+            "italic" | "oblique" => Ok(Self::Italic),
+            _ => Err(Error::UnexpectedInput(format!(
+                "font-style {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-weight-prop
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FontWeight {
+    Normal,
+    Bold,
+}
+
+/// The initial value is `normal`.
+impl Default for FontWeight {
+    fn default() -> Self {
+        FontWeight::Normal
+    }
+}
+
+impl FontWeight {
+    /// https://www.w3.org/TR/css-fonts-4/#font-weight-numeric-values
+    /// 400 is the same as normal, and 700 is the same as bold.
+    pub fn from_number(weight: f64) -> Self {
+        if weight >= 600.0 {
+            FontWeight::Bold
+        } else {
+            FontWeight::Normal
+        }
+    }
+}
+
+impl FromStr for FontWeight {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" | "lighter" => Ok(Self::Normal),
+            "bold" | "bolder" => Ok(Self::Bold),
+            _ => Err(Error::UnexpectedInput(format!(
+                "font-weight {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-cascade-4/#defaulting-keywords
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CssWideKeyword {
+    Initial,
+    Inherit,
+}
+
+impl FromStr for CssWideKeyword {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "initial" => Ok(Self::Initial),
+            "inherit" => Ok(Self::Inherit),
+            _ => Err(Error::UnexpectedInput(format!(
+                "{:?} is not a CSS-wide keyword",
+                s
+            ))),
+        }
+    }
 }
 
 /// https://w3c.github.io/csswg-drafts/css-text-decor/#text-decoration-property
@@ -354,16 +1264,135 @@ impl FontSize {
 pub enum TextDecoration {
     None,
     Underline,
+    LineThrough,
 }
 
-impl TextDecoration {
-    fn default(node: &Rc<RefCell<Node>>) -> Self {
-        match &node.borrow().kind() {
-            NodeKind::Element(element) => match element.kind() {
-                ElementKind::A => TextDecoration::Underline,
-                _ => TextDecoration::None,
-            },
-            _ => TextDecoration::None,
+/// The initial value is `none`.
+impl Default for TextDecoration {
+    fn default() -> Self {
+        TextDecoration::None
+    }
+}
+
+impl FromStr for TextDecoration {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "underline" => Ok(Self::Underline),
+            "line-through" => Ok(Self::LineThrough),
+            _ => Err(Error::UnexpectedInput(format!(
+                "text-decoration {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// A set of decoration lines, e.g. `underline line-through`.
+/// https://w3c.github.io/csswg-drafts/css-text-decor/#text-decoration-line-property
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct TextDecorations {
+    underline: bool,
+    line_through: bool,
+}
+
+impl TextDecorations {
+    /// Adds `text_decoration` to the set. `None` clears the set.
+    pub fn insert(&mut self, text_decoration: TextDecoration) {
+        match text_decoration {
+            TextDecoration::None => *self = Self::default(),
+            TextDecoration::Underline => self.underline = true,
+            TextDecoration::LineThrough => self.line_through = true,
+        }
+    }
+
+    /// Returns true if `text_decoration` is in the set. `None` is contained only in an empty set.
+    pub fn contains(&self, text_decoration: TextDecoration) -> bool {
+        match text_decoration {
+            TextDecoration::None => self.is_none(),
+            TextDecoration::Underline => self.underline,
+            TextDecoration::LineThrough => self.line_through,
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        !self.underline && !self.line_through
+    }
+}
+
+impl From<TextDecoration> for TextDecorations {
+    fn from(text_decoration: TextDecoration) -> Self {
+        let mut decorations = Self::default();
+        decorations.insert(text_decoration);
+        decorations
+    }
+}
+
+/// https://www.w3.org/TR/css-text-3/#text-align-property
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TextAlign {
+    Start,
+    End,
+    Left,
+    Right,
+    Center,
+}
+
+/// The initial value is `start`.
+impl Default for TextAlign {
+    fn default() -> Self {
+        TextAlign::Start
+    }
+}
+
+impl FromStr for TextAlign {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "start" => Ok(Self::Start),
+            "end" => Ok(Self::End),
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "center" => Ok(Self::Center),
+            _ => Err(Error::UnexpectedInput(format!(
+                "text-align {:?} is not supported yet",
+                s
+            ))),
+        }
+    }
+}
+
+/// https://drafts.csswg.org/css-ui/#cursor
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Cursor {
+    /// The UI chooses the cursor from the context, e.g. a pointer over a link.
+    Auto,
+    Default,
+    Pointer,
+}
+
+/// The initial value is `auto`.
+impl Default for Cursor {
+    fn default() -> Self {
+        Cursor::Auto
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "default" => Ok(Self::Default),
+            "pointer" => Ok(Self::Pointer),
+            _ => Err(Error::UnexpectedInput(format!(
+                "cursor {:?} is not supported yet",
+                s
+            ))),
         }
     }
 }
@@ -373,17 +1402,105 @@ impl TextDecoration {
 pub enum WhiteSpace {
     Normal,
     Pre,
+    /// Collapses spaces like `normal` but preserves newlines as line breaks.
+    PreLine,
 }
 
-impl WhiteSpace {
-    fn default(node: &Rc<RefCell<Node>>) -> Self {
-        match &node.borrow().kind() {
-            NodeKind::Element(element) => match element.kind() {
-                ElementKind::P => WhiteSpace::Normal,
-                ElementKind::Pre => WhiteSpace::Pre,
-                _ => WhiteSpace::Normal,
-            },
-            _ => WhiteSpace::Normal,
+/// The initial value is `normal`.
+impl Default for WhiteSpace {
+    fn default() -> Self {
+        WhiteSpace::Normal
+    }
+}
+
+impl FromStr for WhiteSpace {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "pre" => Ok(Self::Pre),
+            "pre-line" => Ok(Self::PreLine),
+            _ => Err(Error::UnexpectedInput(format!(
+                "white-space {:?} is not supported yet",
+                s
+            ))),
         }
     }
 }
+
+/// Returns the font size for the `size` attribute of a font element, e.g. "5" or "+2". The
+/// size is one of 1 to 7, and a relative size is added to 3.
+/// https://html.spec.whatwg.org/multipage/rendering.html#rules-for-parsing-a-legacy-font-size
+fn legacy_font_size(value: &str) -> Option<FontSize> {
+    let value = value.trim();
+    let (sign, digits) = match value.chars().next() {
+        Some('+') => (1, &value[1..]),
+        Some('-') => (-1, &value[1..]),
+        _ => (0, value),
+    };
+    let digits: String = digits.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let number: i64 = digits.parse().ok()?;
+    let size = if sign == 0 { number } else { 3 + sign * number };
+
+    let keyword = match size.clamp(1, 7) {
+        1 => "x-small",
+        2 => "small",
+        3 => "medium",
+        4 => "large",
+        5 => "x-large",
+        6 => "xx-large",
+        _ => "xxx-large",
+    };
+    FontSize::from_str(keyword).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style_with_display(display: DisplayType) -> ComputedStyle {
+        let mut style = ComputedStyle::new();
+        style.set_display(display);
+        style
+    }
+
+    #[test]
+    fn test_display_predicates() {
+        let block = style_with_display(DisplayType::Block);
+        assert!(block.is_block());
+        assert!(!block.is_inline());
+        assert!(!block.is_none());
+
+        let inline = style_with_display(DisplayType::Inline);
+        assert!(!inline.is_block());
+        assert!(inline.is_inline());
+        assert!(!inline.is_none());
+
+        let inline_block = style_with_display(DisplayType::InlineBlock);
+        assert!(!inline_block.is_block());
+        assert!(inline_block.is_inline());
+        assert!(!inline_block.is_none());
+
+        let flex = style_with_display(DisplayType::Flex);
+        assert!(flex.is_block());
+        assert!(flex.is_flex());
+        assert!(!flex.is_inline());
+
+        let none = style_with_display(DisplayType::DisplayNone);
+        assert!(!none.is_block());
+        assert!(!none.is_inline());
+        assert!(none.is_none());
+    }
+
+    #[test]
+    fn test_legacy_font_size() {
+        assert_eq!(Some(FontSize::Medium), legacy_font_size("3"));
+        assert_eq!(Some(FontSize::XLarge), legacy_font_size(" 5"));
+        assert_eq!(Some(FontSize::XXLarge), legacy_font_size("6"));
+        assert_eq!(Some(FontSize::XXLarge), legacy_font_size("10"));
+        assert_eq!(Some(FontSize::XLarge), legacy_font_size("+1"));
+        assert_eq!(Some(FontSize::Medium), legacy_font_size("-2"));
+        assert_eq!(None, legacy_font_size("big"));
+    }
+}
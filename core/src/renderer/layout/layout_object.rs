@@ -6,6 +6,10 @@
 use crate::alloc::string::ToString;
 use crate::browser::Browser;
 use crate::constants::*;
+use crate::display_item::button_label;
+use crate::display_item::check_mark;
+use crate::display_item::is_button_input;
+use crate::display_item::is_checkable_input;
 use crate::display_item::DisplayItem;
 use crate::renderer::css::cssom::ComponentValue;
 use crate::renderer::css::cssom::Declaration;
@@ -15,10 +19,27 @@ use crate::renderer::dom::node::ElementKind;
 use crate::renderer::dom::node::Node;
 use crate::renderer::dom::node::NodeKind;
 use crate::renderer::layout::color::Color;
+use crate::renderer::layout::computed_style::AlignItems;
+use crate::renderer::layout::computed_style::Border;
+use crate::renderer::layout::computed_style::BorderRadius;
 use crate::renderer::layout::computed_style::BoxInfo;
 use crate::renderer::layout::computed_style::ComputedStyle;
+use crate::renderer::layout::computed_style::CssWideKeyword;
+use crate::renderer::layout::computed_style::Cursor;
+use crate::renderer::layout::computed_style::Direction;
 use crate::renderer::layout::computed_style::DisplayType;
+use crate::renderer::layout::computed_style::FlexDirection;
+use crate::renderer::layout::computed_style::FlexWrap;
+use crate::renderer::layout::computed_style::FontFamily;
 use crate::renderer::layout::computed_style::FontSize;
+use crate::renderer::layout::computed_style::FontStyle;
+use crate::renderer::layout::computed_style::FontWeight;
+use crate::renderer::layout::computed_style::JustifyContent;
+use crate::renderer::layout::computed_style::MaxWidth;
+use crate::renderer::layout::computed_style::TextAlign;
+use crate::renderer::layout::computed_style::TextDecoration;
+use crate::renderer::layout::computed_style::TextDecorations;
+use crate::renderer::layout::computed_style::WhiteSpace;
 use crate::renderer::layout::layout_point::LayoutPoint;
 use crate::renderer::layout::layout_size::LayoutSize;
 use crate::utils::console_debug;
@@ -30,34 +51,160 @@ use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::cell::RefCell;
+use core::ops::Range;
+use core::str::FromStr;
+
+/// Returns the advance width of one character in `style`. Both UIs draw all text with one
+/// fixed-width font, so monospace and proportional text have the same advance.
+pub(crate) fn char_width(style: &ComputedStyle) -> i64 {
+    let ratio = match style.font_size() {
+        FontSize::Medium => 1,
+        FontSize::XLarge => 2,
+        FontSize::XXLarge => 3,
+    };
+    CHAR_WIDTH * ratio
+}
+
+/// An invisible character which offers a line break opportunity.
+/// https://drafts.csswg.org/css-text/#line-break-details
+const ZERO_WIDTH_SPACE: char = '\u{200B}';
+/// An invisible character which offers a line break opportunity. A hyphen is shown at the end
+/// of a line when the line breaks at it.
+/// https://drafts.csswg.org/css-text/#soft-hyphen
+const SOFT_HYPHEN: char = '\u{00AD}';
+/// A no-break space, e.g. `&nbsp;`. It's drawn as a space but doesn't offer a line break
+/// opportunity.
+/// https://drafts.csswg.org/css-text/#line-break-details
+const NO_BREAK_SPACE: char = '\u{00A0}';
+
+fn is_invisible(c: char) -> bool {
+    c == ZERO_WIDTH_SPACE || c == SOFT_HYPHEN
+}
+
+/// Returns the number of characters drawn for `text`.
+fn visible_len(text: &str) -> usize {
+    text.chars().filter(|c| !is_invisible(*c)).count()
+}
+
+/// Returns the characters drawn for `chars`. Invisible characters are dropped and a no-break
+/// space is drawn as a space.
+fn drawn_text(chars: &[char]) -> String {
+    chars
+        .iter()
+        .filter(|c| !is_invisible(**c))
+        .map(|c| if *c == NO_BREAK_SPACE { ' ' } else { *c })
+        .collect()
+}
 
 /// This is used when { word-break: normal; } in CSS.
 /// https://drafts.csswg.org/css-text/#word-break-property
-fn find_index_for_line_break(line: String, max_index: usize) -> usize {
-    for i in (0..max_index).rev() {
-        if line.chars().collect::<Vec<char>>()[i] == ' ' {
+fn find_index_for_line_break(line: &[char], max_index: usize) -> usize {
+    for i in (0..max_index.min(line.len())).rev() {
+        if line[i] == ' ' || is_invisible(line[i]) {
             return i;
         }
     }
     max_index
 }
 
+/// Splits preformatted text into lines. Tabs are expanded to spaces up to the next tab stop,
+/// every `tab_size` columns, because the UIs draw characters with fixed advances.
+/// https://drafts.csswg.org/css-text/#white-space-processing
+/// https://drafts.csswg.org/css-text/#tab-size-property
+fn preformatted_lines(text: &str, tab_size: usize) -> Vec<String> {
+    // A newline right before the end of the element doesn't start a new line.
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    text.split('\n')
+        .map(|line| {
+            let mut expanded = String::new();
+            let mut column = 0;
+            for c in line.chars() {
+                if c == '\t' && tab_size > 0 {
+                    let spaces = tab_size - column % tab_size;
+                    expanded.push_str(&" ".repeat(spaces));
+                    column += spaces;
+                } else if !is_invisible(c) {
+                    expanded.push(c);
+                    column += 1;
+                }
+            }
+            expanded
+        })
+        .collect()
+}
+
+/// Collapses a sequence of spaces and newlines into a single space.
+/// https://drafts.csswg.org/css-text/#white-space-phase-1
+fn collapse_spaces(text: &str) -> String {
+    text.replace("\n", " ")
+        .split(' ')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Splits text of `white-space: pre-line` into lines. Spaces are collapsed and newlines are
+/// preserved as line breaks. Each line is wrapped like normal text.
+/// https://drafts.csswg.org/css-text/#valdef-white-space-pre-line
+fn pre_line_lines(text: &str, char_width: i64) -> Vec<String> {
+    // A newline right before the end of the element doesn't start a new line.
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    text.split('\n')
+        .flat_map(|line| split_text(collapse_spaces(line), char_width))
+        .collect()
+}
+
 /// https://drafts.csswg.org/css-text/#word-break-property
 fn split_text(line: String, char_width: i64) -> Vec<String> {
     let mut result: Vec<String> = vec![];
-    if line.len() as i64 * char_width > (WINDOW_WIDTH + WINDOW_PADDING) {
-        let s = line.split_at(find_index_for_line_break(
-            line.clone(),
+    let chars: Vec<char> = line.chars().collect();
+    if visible_len(&line) as i64 * char_width > (WINDOW_WIDTH + WINDOW_PADDING) {
+        let index = find_index_for_line_break(
+            &chars,
             ((WINDOW_WIDTH + WINDOW_PADDING) / char_width) as usize,
-        ));
-        result.push(s.0.to_string());
-        result.extend(split_text(s.1.trim().to_string(), char_width))
+        );
+        let mut first = drawn_text(&chars[..index]);
+        // The character at a break opportunity is not drawn, except for a soft hyphen which
+        // becomes a visible hyphen.
+        let rest_index = match chars.get(index) {
+            Some(&SOFT_HYPHEN) => {
+                first.push('-');
+                index + 1
+            }
+            Some(&ZERO_WIDTH_SPACE) => index + 1,
+            _ => index,
+        };
+        result.push(first);
+        let rest: String = chars[rest_index..].iter().collect();
+        // Only spaces are trimmed because a no-break space is a part of the text.
+        result.extend(split_text(rest.trim_matches(' ').to_string(), char_width))
     } else {
-        result.push(line);
+        result.push(drawn_text(&chars));
     }
     result
 }
 
+/// Breaks flex items whose widths are `widths` into lines. Without wrapping, all items are
+/// placed on one line.
+/// https://www.w3.org/TR/css-flexbox-1/#algo-line-break
+fn flex_line_ranges(widths: &[i64], container_width: i64, wrap: bool) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut line_width = 0;
+    for (i, width) in widths.iter().enumerate() {
+        if wrap && i > start && line_width + width > container_width {
+            lines.push(start..i);
+            start = i;
+            line_width = 0;
+        }
+        line_width += width;
+    }
+    if start < widths.len() {
+        lines.push(start..widths.len());
+    }
+    lines
+}
+
 pub fn create_layout_object(
     browser: Weak<RefCell<Browser>>,
     node: &Option<Rc<RefCell<Node>>>,
@@ -65,6 +212,12 @@ pub fn create_layout_object(
     cssom: &StyleSheet,
 ) -> Option<Rc<RefCell<LayoutObject>>> {
     if let Some(n) = node {
+        // The contents of a template element are inert and never rendered, regardless of CSS.
+        // https://html.spec.whatwg.org/multipage/scripting.html#the-template-element
+        if n.borrow().element_kind() == Some(ElementKind::Template) {
+            return None;
+        }
+
         let layout_object = Rc::new(RefCell::new(LayoutObject::new(
             browser.clone(),
             n.clone(),
@@ -88,7 +241,7 @@ pub fn create_layout_object(
         };
         layout_object.borrow_mut().defaulting_style(n, parent_style);
 
-        if layout_object.borrow().style().display() == DisplayType::DisplayNone {
+        if layout_object.borrow().style().is_none() {
             return None;
         }
 
@@ -166,16 +319,18 @@ impl LayoutObject {
         match self.node_kind() {
             NodeKind::Document => panic!("should not create a layout object for a Document node"),
             NodeKind::Element(_) => {
-                let display = self.style.display();
-                match display {
-                    DisplayType::Block => self.kind = LayoutObjectKind::Block,
-                    DisplayType::Inline => self.kind = LayoutObjectKind::Inline,
-                    DisplayType::DisplayNone => {
-                        panic!("should not create a layout object for display:none")
-                    }
+                if self.style.is_none() {
+                    panic!("should not create a layout object for display:none");
+                }
+                // An inline-block box is placed like an inline box.
+                if self.style.is_block() {
+                    self.kind = LayoutObjectKind::Block;
+                } else {
+                    self.kind = LayoutObjectKind::Inline;
                 }
             }
             NodeKind::Text(_) => self.kind = LayoutObjectKind::Text,
+            NodeKind::Comment(_) => panic!("should not create a layout object for a Comment node"),
         }
     }
 
@@ -203,6 +358,16 @@ impl LayoutObject {
         self.next_sibling.as_ref().cloned()
     }
 
+    pub fn children(&self) -> Vec<Rc<RefCell<LayoutObject>>> {
+        let mut children = Vec::new();
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            child = c.borrow().next_sibling();
+            children.push(c);
+        }
+        children
+    }
+
     pub fn style(&self) -> ComputedStyle {
         self.style.clone()
     }
@@ -222,7 +387,28 @@ impl LayoutObject {
     // because it supports "Normal user declarations" input only.
     pub fn cascading_style(&mut self, declarations: Vec<Declaration>) {
         for declaration in declarations {
+            if let ComponentValue::Ident(value) = &declaration.value {
+                if let Ok(keyword) = CssWideKeyword::from_str(value) {
+                    if let Err(e) = self
+                        .style
+                        .set_css_wide_keyword(&declaration.property, keyword)
+                    {
+                        console_warning(&self.browser, format!("{:?}", e));
+                    }
+                    continue;
+                }
+            }
+            self.style.clear_css_wide_keyword(&declaration.property);
+
             match declaration.property.as_str() {
+                "align-items" => {
+                    if let ComponentValue::Ident(value) = &declaration.value {
+                        match AlignItems::from_str(value) {
+                            Ok(align_items) => self.style.set_align_items(align_items),
+                            Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                        }
+                    }
+                }
                 "background-color" => {
                     if let ComponentValue::Ident(value) = &declaration.value {
                         let color = match Color::from_name(value) {
@@ -248,6 +434,67 @@ impl LayoutObject {
                         continue;
                     }
                 }
+                "border" => {
+                    // A border is drawn only if its line style is given. Units are ignored.
+                    // https://www.w3.org/TR/css-backgrounds-3/#propdef-border
+                    let mut width = 3.0;
+                    let mut has_line = false;
+                    let mut color = None;
+                    let mut valid = true;
+                    for value in declaration.values() {
+                        match value {
+                            ComponentValue::Number(value) => width = value,
+                            ComponentValue::Ident(value) => match value.as_str() {
+                                "px" => {}
+                                "thin" => width = 1.0,
+                                "medium" => width = 3.0,
+                                "thick" => width = 5.0,
+                                "none" | "hidden" => has_line = false,
+                                "solid" | "dashed" | "dotted" | "double" | "groove" | "ridge"
+                                | "inset" | "outset" => has_line = true,
+                                "currentcolor" => color = None,
+                                name => match Color::from_name(name) {
+                                    Ok(c) => color = Some(c),
+                                    Err(_) => valid = false,
+                                },
+                            },
+                            ComponentValue::HashToken(code) => match Color::from_code(&code) {
+                                Ok(c) => color = Some(c),
+                                Err(_) => valid = false,
+                            },
+                            _ => valid = false,
+                        }
+                    }
+                    if !valid {
+                        console_warning(
+                            &self.browser,
+                            format!("invalid border {:?}", declaration.values()),
+                        );
+                        continue;
+                    }
+                    let width = if has_line { width } else { 0.0 };
+                    self.style.set_border(Border::new(width, color));
+                }
+                "border-radius" => {
+                    // Elliptical radii after "/" are not supported, so only the horizontal radii
+                    // are used. Units are ignored.
+                    let radii: Vec<f64> = declaration
+                        .values()
+                        .into_iter()
+                        .take_while(|v| *v != ComponentValue::Delim('/'))
+                        .filter_map(|v| match v {
+                            ComponentValue::Number(value) => Some(value),
+                            _ => None,
+                        })
+                        .collect();
+                    match BorderRadius::from_values(&radii) {
+                        Some(border_radius) => self.style.set_border_radius(border_radius),
+                        None => console_warning(
+                            &self.browser,
+                            format!("invalid border-radius {:?}", declaration.values()),
+                        ),
+                    }
+                }
                 "color" => {
                     if let ComponentValue::Ident(value) = &declaration.value {
                         let color = match Color::from_name(value) {
@@ -271,6 +518,14 @@ impl LayoutObject {
                         self.style.set_color(color);
                     }
                 }
+                "direction" => {
+                    if let ComponentValue::Ident(value) = &declaration.value {
+                        match Direction::from_str(value) {
+                            Ok(direction) => self.style.set_direction(direction),
+                            Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                        }
+                    }
+                }
                 "display" => {
                     if let ComponentValue::Ident(value) = declaration.value {
                         let display_type = match DisplayType::from_str(&value) {
@@ -283,11 +538,82 @@ impl LayoutObject {
                         self.style.set_display(display_type)
                     }
                 }
+                "flex-direction" => {
+                    if let ComponentValue::Ident(value) = &declaration.value {
+                        match FlexDirection::from_str(value) {
+                            Ok(flex_direction) => self.style.set_flex_direction(flex_direction),
+                            Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                        }
+                    }
+                }
+                "flex-grow" => {
+                    if let ComponentValue::Number(value) = declaration.value {
+                        self.style.set_flex_grow(value);
+                    }
+                }
+                "flex-wrap" => {
+                    if let ComponentValue::Ident(value) = &declaration.value {
+                        match FlexWrap::from_str(value) {
+                            Ok(flex_wrap) => self.style.set_flex_wrap(flex_wrap),
+                            Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                        }
+                    }
+                }
+                "font-family" => {
+                    // TODO: support a list of families. Only the first family is used.
+                    match &declaration.value {
+                        ComponentValue::Ident(name) | ComponentValue::StringToken(name) => {
+                            self.style.set_font_family(FontFamily::from_name(name));
+                        }
+                        _ => {}
+                    }
+                }
                 "font-size" => {
+                    if let ComponentValue::Ident(value) = &declaration.value {
+                        match FontSize::from_str(value) {
+                            Ok(font_size) => {
+                                let font_size = font_size.scaled(self.font_scale());
+                                self.style.set_font_size(font_size);
+                            }
+                            Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                        }
+                    }
+
                     if let ComponentValue::Number(value) = declaration.value {
-                        let font_size = FontSize::from_number(value);
+                        let font_size = FontSize::from_number(value * self.font_scale());
                         self.style.set_font_size(font_size);
                     }
+
+                    // A percentage is relative to the parent's font size.
+                    // https://www.w3.org/TR/css-fonts-4/#valdef-font-size-length-percentage
+                    if let ComponentValue::Percentage(value) = declaration.value {
+                        let parent_font_size = match self.parent.upgrade() {
+                            Some(parent) => parent.borrow().style().font_size(),
+                            None => FontSize::default().scaled(self.font_scale()),
+                        };
+                        self.style
+                            .set_font_size(parent_font_size.scaled(value / 100.0));
+                    }
+                }
+                "font-style" => {
+                    if let ComponentValue::Ident(value) = declaration.value {
+                        match FontStyle::from_str(&value) {
+                            Ok(font_style) => self.style.set_font_style(font_style),
+                            Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                        }
+                    }
+                }
+                "font-weight" => {
+                    if let ComponentValue::Ident(value) = &declaration.value {
+                        match FontWeight::from_str(value) {
+                            Ok(font_weight) => self.style.set_font_weight(font_weight),
+                            Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                        }
+                    }
+
+                    if let ComponentValue::Number(value) = declaration.value {
+                        self.style.set_font_weight(FontWeight::from_number(value));
+                    }
                 }
                 "height" => {
                     if let ComponentValue::Number(value) = declaration.value {
@@ -296,6 +622,14 @@ impl LayoutObject {
                         self.style.set_height(value);
                     }
                 }
+                "justify-content" => {
+                    if let ComponentValue::Ident(value) = &declaration.value {
+                        match JustifyContent::from_str(value) {
+                            Ok(justify_content) => self.style.set_justify_content(justify_content),
+                            Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                        }
+                    }
+                }
                 "width" => {
                     if let ComponentValue::Number(value) = declaration.value {
                         // TODO: remove this? because layout() updates size and style.
@@ -304,38 +638,92 @@ impl LayoutObject {
                     }
                 }
                 "margin" => {
-                    // TODO: support string (e.g. "auto")
+                    // TODO: support multiple values (e.g. "0 auto")
                     if let ComponentValue::Number(value) = declaration.value {
                         self.style
                             .set_margin(BoxInfo::new(value, value, value, value));
+                        self.style.set_margin_auto(false);
+                    }
+
+                    // https://www.w3.org/TR/CSS22/visudet.html#blockwidth
+                    if declaration.value == ComponentValue::Ident("auto".to_string()) {
+                        self.style.set_margin_auto(true);
                     }
                 }
+                "max-width" => match declaration.value {
+                    ComponentValue::Number(value) => self.style.set_max_width(MaxWidth::Px(value)),
+                    ComponentValue::Percentage(value) => {
+                        self.style.set_max_width(MaxWidth::Percentage(value))
+                    }
+                    ComponentValue::Ident(ref value) if value == "none" => {
+                        self.style.set_max_width(MaxWidth::None)
+                    }
+                    _ => {}
+                },
                 "margin-top" => {
                     if let ComponentValue::Number(value) = declaration.value {
-                        let m = self.style.margin();
-                        self.style
-                            .set_margin(BoxInfo::new(value, m.right(), m.bottom(), m.left()));
+                        self.style.set_margin_top(value);
                     }
                 }
                 "margin-right" => {
                     if let ComponentValue::Number(value) = declaration.value {
-                        let m = self.style.margin();
-                        self.style
-                            .set_margin(BoxInfo::new(m.top(), value, m.bottom(), m.left()));
+                        self.style.set_margin_right(value);
                     }
                 }
                 "margin-bottom" => {
                     if let ComponentValue::Number(value) = declaration.value {
-                        let m = self.style.margin();
-                        self.style
-                            .set_margin(BoxInfo::new(m.top(), m.right(), value, m.left()));
+                        self.style.set_margin_bottom(value);
                     }
                 }
                 "margin-left" => {
                     if let ComponentValue::Number(value) = declaration.value {
-                        let m = self.style.margin();
-                        self.style
-                            .set_margin(BoxInfo::new(m.top(), m.right(), m.bottom(), value));
+                        self.style.set_margin_left(value);
+                    }
+                }
+                "opacity" => {
+                    if let ComponentValue::Number(value) = declaration.value {
+                        self.style.set_opacity(value);
+                    }
+                }
+                "text-align" => {
+                    if let ComponentValue::Ident(value) = &declaration.value {
+                        match TextAlign::from_str(value) {
+                            Ok(text_align) => self.style.set_text_align(text_align),
+                            Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                        }
+                    }
+                }
+                "text-decoration" => {
+                    // Multiple lines can be combined, e.g. `underline line-through`.
+                    let mut text_decorations: Option<TextDecorations> = None;
+                    for value in declaration.values() {
+                        if let ComponentValue::Ident(value) = value {
+                            match TextDecoration::from_str(&value) {
+                                Ok(text_decoration) => text_decorations
+                                    .get_or_insert_with(TextDecorations::default)
+                                    .insert(text_decoration),
+                                Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                            }
+                        }
+                    }
+                    if let Some(text_decorations) = text_decorations {
+                        self.style.set_text_decorations(text_decorations);
+                    }
+                }
+                "cursor" => {
+                    if let ComponentValue::Ident(value) = declaration.value {
+                        match Cursor::from_str(&value) {
+                            Ok(cursor) => self.style.set_cursor(cursor),
+                            Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                        }
+                    }
+                }
+                "white-space" => {
+                    if let ComponentValue::Ident(value) = declaration.value {
+                        match WhiteSpace::from_str(&value) {
+                            Ok(white_space) => self.style.set_white_space(white_space),
+                            Err(e) => console_error(&self.browser, format!("{:?}", e)),
+                        }
                     }
                 }
                 // TODO: support padding
@@ -357,7 +745,23 @@ impl LayoutObject {
         node: &Rc<RefCell<Node>>,
         parent_style: Option<ComputedStyle>,
     ) {
-        self.style.defaulting(node, parent_style);
+        let font_scale = self.font_scale();
+        self.style.defaulting(node, parent_style, font_scale);
+    }
+
+    /// Returns how much the browser's base font size enlarges text.
+    fn font_scale(&self) -> f64 {
+        match self.browser.upgrade() {
+            Some(browser) => browser.borrow().base_font_px() / DEFAULT_BASE_FONT_PX,
+            None => 1.0,
+        }
+    }
+
+    fn tab_size(&self) -> usize {
+        match self.browser.upgrade() {
+            Some(browser) => browser.borrow().tab_size(),
+            None => DEFAULT_TAB_SIZE,
+        }
     }
 
     /// Returns the size of this element including margins, paddings, etc.
@@ -376,17 +780,21 @@ impl LayoutObject {
         }
 
         if is_height_set && is_width_set {
+            self.clamp_width(parent_size);
             return;
         }
 
         match self.kind() {
             LayoutObjectKind::Block => {
-                // For a block element, consider the parent's width.
+                // For a block element, consider the parent's width. The horizontal margins are
+                // outside of the box.
                 // TODO: add content_size to LayoutSize?
                 size.set_width(
                     parent_size.width()
                         - self.style.padding_left() as i64
-                        - self.style.padding_right() as i64,
+                        - self.style.padding_right() as i64
+                        - self.style.margin_left() as i64
+                        - self.style.margin_right() as i64,
                 );
 
                 // For height, sum up the height of all children next to the block element.
@@ -408,19 +816,74 @@ impl LayoutObject {
                     previous_child_kind = c.borrow().kind();
                     child = c.borrow().next_sibling();
                 }
+
+                // Cells of a table row are placed side by side, so the row is as tall as its
+                // tallest cell.
+                if self.style.display() == DisplayType::TableRow {
+                    height = self
+                        .children()
+                        .iter()
+                        .map(|cell| cell.borrow().size.height())
+                        .max()
+                        .unwrap_or(0);
+                }
+
+                // Flex lines are stacked, and each line is as tall as its tallest item. The height
+                // of a flex container is kept when it's set.
+                if self.style.is_flex() && is_height_set {
+                    height = self.style.height() as i64;
+                } else if self.style.is_flex() {
+                    height = self
+                        .flex_lines_in(size.width())
+                        .iter()
+                        .map(|line| {
+                            line.iter()
+                                .map(|item| item.borrow().size.height())
+                                .max()
+                                .unwrap_or(0)
+                        })
+                        .sum();
+                }
                 size.set_height(height);
             }
             LayoutObjectKind::Inline => {
                 // Check if this is an input element and set default size
                 if let NodeKind::Element(e) = self.node_kind() {
                     if e.kind() == ElementKind::Input {
-                        size.set_width(200); // Default input width
+                        let input_type = e.get_attribute("type").unwrap_or_default();
+                        if is_button_input(&input_type) {
+                            // A button is as wide as its label.
+                            let label = button_label(&input_type, e.get_value());
+                            size.set_width(
+                                char_width(&self.style) * visible_len(&label) as i64
+                                    + BUTTON_PADDING * 2,
+                            );
+                        } else if is_checkable_input(&input_type) {
+                            // A checkbox or a radio button is as wide as its mark.
+                            let mark = check_mark(&input_type, e.is_checked());
+                            size.set_width(char_width(&self.style) * visible_len(&mark) as i64);
+                        } else {
+                            size.set_width(200); // Default input width
+                        }
                         size.set_height(30); // Default input height
                         self.size = size;
                         return;
                     }
                 }
 
+                // An empty link takes the space of its label.
+                if let Some(label) = self.aria_label_fallback() {
+                    let ratio = match self.style.font_size() {
+                        FontSize::Medium => 1,
+                        FontSize::XLarge => 2,
+                        FontSize::XXLarge => 3,
+                    };
+                    size.set_width(char_width(&self.style) * visible_len(&label) as i64);
+                    size.set_height(CHAR_HEIGHT_WITH_PADDING * ratio);
+                    self.size = size;
+                    return;
+                }
+
                 // Sum up the width and height of all children directly under this element.
                 let mut width = 0;
                 let mut height = 0;
@@ -437,17 +900,44 @@ impl LayoutObject {
                     child = c.borrow().next_sibling();
                 }
 
+                // An inline-block box keeps its width and height like a block box.
+                if self.style.display() == DisplayType::InlineBlock {
+                    if is_width_set {
+                        width = self.style.width() as i64;
+                    }
+                    if is_height_set {
+                        height = self.style.height() as i64;
+                    }
+                }
+
                 size.set_width(width);
                 size.set_height(height);
             }
             LayoutObjectKind::Text => {
-                if let NodeKind::Text(t) = self.node_kind() {
+                if let Some(t) = self.text() {
                     let ratio = match self.style.font_size() {
                         FontSize::Medium => 1,
                         FontSize::XLarge => 2,
                         FontSize::XXLarge => 3,
                     };
-                    let width = CHAR_WIDTH * ratio * t.len() as i64;
+                    if self.style.white_space() == WhiteSpace::Pre {
+                        // Preformatted text is not wrapped.
+                        let lines = preformatted_lines(&t, self.tab_size());
+                        let columns = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+                        size.set_width(char_width(&self.style) * columns as i64);
+                        size.set_height(CHAR_HEIGHT_WITH_PADDING * ratio * lines.len() as i64);
+                        self.size = size;
+                        return;
+                    }
+                    if self.style.white_space() == WhiteSpace::PreLine {
+                        let lines = pre_line_lines(&t, char_width(&self.style));
+                        let columns = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+                        size.set_width(char_width(&self.style) * columns as i64);
+                        size.set_height(CHAR_HEIGHT_WITH_PADDING * ratio * lines.len() as i64);
+                        self.size = size;
+                        return;
+                    }
+                    let width = char_width(&self.style) * visible_len(&t) as i64;
                     if width > CONTENT_AREA_WIDTH {
                         // The text is multiple lines.
                         size.set_width(CONTENT_AREA_WIDTH);
@@ -467,20 +957,222 @@ impl LayoutObject {
         }
 
         self.size = size;
+        self.clamp_width(parent_size);
+    }
+
+    /// Makes a flex item placed as a block box. Images and inputs keep their own boxes.
+    /// https://www.w3.org/TR/css-display-3/#blockify
+    pub fn blockify(&mut self) {
+        if self.kind != LayoutObjectKind::Inline {
+            return;
+        }
+        if matches!(
+            self.node.borrow().element_kind(),
+            Some(ElementKind::IMG) | Some(ElementKind::Input)
+        ) {
+            return;
+        }
+        self.kind = LayoutObjectKind::Block;
+    }
+
+    /// Wraps a text run directly contained in a flex container in an anonymous block box, so
+    /// that the text is placed as a flex item. The box shares the node and the style of the
+    /// text.
+    /// https://www.w3.org/TR/css-flexbox-1/#flex-items
+    /// https://www.w3.org/TR/CSS22/visuren.html#anonymous-block-level
+    pub fn anonymous_block(text: &Rc<RefCell<LayoutObject>>) -> Rc<RefCell<LayoutObject>> {
+        let mut style = text.borrow().style();
+        style.set_display(DisplayType::Block);
+        let block = Rc::new(RefCell::new(Self {
+            browser: text.borrow().browser.clone(),
+            kind: LayoutObjectKind::Block,
+            node: text.borrow().node(),
+            first_child: Some(text.clone()),
+            next_sibling: None,
+            parent: text.borrow().parent(),
+            style,
+            point: LayoutPoint::new(0, 0),
+            size: LayoutSize::new(0, 0),
+        }));
+
+        let mut t = text.borrow_mut();
+        t.next_sibling = None;
+        t.parent = Rc::downgrade(&block);
+        block
+    }
+
+    /// Returns the width of the content laid out without line breaks. A block box is as wide as
+    /// its widest line, and a flex container is as wide as its items side by side. Sizes of the
+    /// descendants should be computed beforehand.
+    /// https://www.w3.org/TR/css-sizing-3/#max-content
+    fn max_content_width(&self) -> i64 {
+        if self.kind() != LayoutObjectKind::Block {
+            return self.size.width();
+        }
+        if self.style.width() != 0.0 {
+            return self.style.width() as i64;
+        }
+
+        if self.style.is_flex() {
+            return self
+                .children()
+                .iter()
+                .map(|item| item.borrow().max_content_width())
+                .sum();
+        }
+
+        let mut widest = 0;
+        let mut line = 0;
+        for child in self.children() {
+            let child = child.borrow();
+            if child.kind() == LayoutObjectKind::Block {
+                widest = widest.max(line).max(child.max_content_width());
+                line = 0;
+            } else {
+                line += child.size.width();
+            }
+        }
+        widest.max(line)
+    }
+
+    /// Returns the widths of the flex items of this flex container. Each item starts from its
+    /// max-content width, and the free space of each line is distributed by `flex-grow`. When
+    /// no item has `flex-grow`, the free space is shared equally unless `justify-content`
+    /// places the items in it. Overflowing items shrink equally.
+    /// https://www.w3.org/TR/css-flexbox-1/#resolve-flexible-lengths
+    pub fn flex_item_widths(&self) -> Vec<i64> {
+        let items = self.children();
+        let mut widths: Vec<i64> = items
+            .iter()
+            .map(|item| item.borrow().max_content_width())
+            .collect();
+        let grows: Vec<f64> = items
+            .iter()
+            .map(|item| item.borrow().style.flex_grow())
+            .collect();
+        let has_grow = grows.iter().any(|grow| *grow > 0.0);
+        let justified = self.style.justify_content() != JustifyContent::FlexStart;
+        let container_width = self.size.width();
+        let wrap = self.style.flex_wrap() == FlexWrap::Wrap;
+
+        for line in flex_line_ranges(&widths, container_width, wrap) {
+            let free = container_width - widths[line.clone()].iter().sum::<i64>();
+            if !has_grow && justified && free > 0 {
+                continue;
+            }
+            let factors: Vec<f64> = line
+                .clone()
+                .map(|i| if has_grow && free > 0 { grows[i] } else { 1.0 })
+                .collect();
+            let total: f64 = factors.iter().sum();
+            if total <= 0.0 {
+                continue;
+            }
+            for (i, factor) in line.zip(factors) {
+                widths[i] = (widths[i] + (free as f64 * factor / total) as i64).max(0);
+            }
+        }
+        widths
+    }
+
+    /// Returns the number of columns which this table cell spans. It's given by the `colspan`
+    /// attribute, and an invalid value spans one column.
+    /// https://html.spec.whatwg.org/multipage/tables.html#attr-tdth-colspan
+    pub fn column_span(&self) -> i64 {
+        self.node
+            .borrow()
+            .get_element()
+            .and_then(|e| e.get_attribute("colspan"))
+            .and_then(|colspan| colspan.trim().parse::<i64>().ok())
+            .filter(|span| *span > 0)
+            .map_or(1, |span| span.min(1000))
+    }
+
+    /// Returns the number of columns of this table, which is the most columns spanned by the
+    /// cells of a row. Rows in row groups such as `<tbody>` are counted too.
+    /// https://html.spec.whatwg.org/multipage/tables.html#forming-a-table
+    pub fn table_column_count(&self) -> i64 {
+        self.children()
+            .iter()
+            .map(|child| {
+                let child = child.borrow();
+                if child.style.display() == DisplayType::TableRow {
+                    child
+                        .children()
+                        .iter()
+                        .map(|cell| cell.borrow().column_span())
+                        .sum()
+                } else {
+                    child.table_column_count()
+                }
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the flex items of this flex container grouped by lines.
+    pub fn flex_lines(&self) -> Vec<Vec<Rc<RefCell<LayoutObject>>>> {
+        self.flex_lines_in(self.size.width())
+    }
+
+    fn flex_lines_in(&self, container_width: i64) -> Vec<Vec<Rc<RefCell<LayoutObject>>>> {
+        let items = self.children();
+        let widths: Vec<i64> = items
+            .iter()
+            .map(|item| item.borrow().size.width())
+            .collect();
+        let wrap = self.style.flex_wrap() == FlexWrap::Wrap;
+        flex_line_ranges(&widths, container_width, wrap)
+            .into_iter()
+            .map(|line| items[line].to_vec())
+            .collect()
+    }
+
+    /// Limits the width of a block box to its `max-width`.
+    /// https://www.w3.org/TR/CSS22/visudet.html#min-max-widths
+    fn clamp_width(&mut self, parent_size: LayoutSize) {
+        if self.kind() != LayoutObjectKind::Block {
+            return;
+        }
+        if let Some(max_width) = self.style.max_width().resolve(parent_size.width()) {
+            if self.size.width() > max_width {
+                self.size.set_width(max_width);
+            }
+        }
     }
 
     /// Returns the position of this element.
     ///
     /// The position is calculated based on the normal flow, which is the default value in the `position` property in CSS.
     /// https://developer.mozilla.org/en-US/docs/Learn/CSS/CSS_layout/Normal_Flow
+    ///
+    /// When `direction` is `rtl`, inline-level boxes are placed from the right edge of the parent.
+    /// Bidirectional reordering within a line is not supported.
+    /// https://www.w3.org/TR/css-writing-modes-4/#direction
+    ///
+    /// `text-align` moves the inline-level box which starts a line. The following boxes on the
+    /// line are placed next to it.
+    /// https://www.w3.org/TR/css-text-3/#text-align-property
     pub fn compute_position(
         &mut self,
         parent_point: LayoutPoint,
+        parent_size: LayoutSize,
         previous_sibling_kind: LayoutObjectKind,
         previous_sibling_point: Option<LayoutPoint>,
         previous_sibling_size: Option<LayoutSize>,
     ) {
         let mut point = LayoutPoint::new(0, 0);
+        let is_rtl_inline =
+            self.kind() != LayoutObjectKind::Block && self.style.direction() == Direction::Rtl;
+        let left_x = parent_point.x();
+        let right_x = parent_point.x() + parent_size.width() - self.size.width();
+        // The x position of an inline-level box which starts a line.
+        let line_start_x = match (self.style.text_align(), is_rtl_inline) {
+            _ if self.kind() == LayoutObjectKind::Block => left_x + self.style.margin_left() as i64,
+            (TextAlign::Center, _) => left_x + ((right_x - left_x) / 2).max(0),
+            (TextAlign::Left, _) | (TextAlign::Start, false) | (TextAlign::End, true) => left_x,
+            (TextAlign::Right, _) | (TextAlign::Start, true) | (TextAlign::End, false) => right_x,
+        };
 
         match (self.kind(), previous_sibling_kind) {
             // If a current node or a sibling node is a block element, grow along the Y-axis direction.
@@ -491,23 +1183,33 @@ impl LayoutObject {
                 } else {
                     point.set_y(parent_point.y());
                 }
-                point.set_x(parent_point.x());
+                point.set_x(line_start_x);
+
+                // A block box with auto left and right margins is centered in its parent.
+                // https://www.w3.org/TR/CSS22/visudet.html#blockwidth
+                if self.kind() == LayoutObjectKind::Block
+                    && self.style.margin_auto()
+                    && self.size.width() < parent_size.width()
+                {
+                    point.set_x(parent_point.x() + (parent_size.width() - self.size.width()) / 2);
+                }
             }
-            // If both a current node and a sibling node are inline elements, grow along the X-axis direction.
-            (LayoutObjectKind::Inline, LayoutObjectKind::Inline) => {
+            // If both a current node and a sibling node are inline-level (inline elements or
+            // texts), grow along the X-axis direction.
+            _ => {
                 if let (Some(size), Some(pos)) = (previous_sibling_size, previous_sibling_point) {
                     // TODO: consider padding of the previous sibling.
-                    point.set_x(pos.x() + size.width() + self.style.margin_left() as i64);
+                    if is_rtl_inline {
+                        point.set_x(pos.x() - self.style.margin_right() as i64 - self.size.width());
+                    } else {
+                        point.set_x(pos.x() + size.width() + self.style.margin_left() as i64);
+                    }
                     point.set_y(pos.y());
                 } else {
-                    point.set_x(parent_point.x());
+                    point.set_x(line_start_x);
                     point.set_y(parent_point.y());
                 }
             }
-            _ => {
-                point.set_x(parent_point.x());
-                point.set_y(parent_point.y());
-            }
         }
 
         self.point = point;
@@ -515,60 +1217,194 @@ impl LayoutObject {
 
     pub fn is_node_selected(&self, selector: &Selector) -> bool {
         match &self.node_kind() {
-            NodeKind::Element(e) => match selector {
-                Selector::TypeSelector(type_name) => {
-                    if e.kind().to_string() == *type_name {
-                        return true;
-                    }
-                    false
-                }
-                Selector::ClassSelector(class_name) => {
-                    for attr in &e.attributes() {
-                        if attr.name() == "class" && attr.value() == *class_name {
-                            return true;
-                        }
+            NodeKind::Element(e) => e.matches_selector(selector, &self.node),
+            _ => false,
+        }
+    }
+
+    /// Returns the text of a text node. The first and last texts in a <q> element are surrounded
+    /// by quotation marks.
+    /// https://html.spec.whatwg.org/multipage/rendering.html#quotes
+    fn text(&self) -> Option<String> {
+        let mut text = match self.node_kind() {
+            NodeKind::Text(t) => t,
+            _ => return None,
+        };
+
+        if let Some(parent) = self.node.borrow().parent().upgrade() {
+            if parent.borrow().element_kind() == Some(ElementKind::Q) {
+                if let Some(first) = parent.borrow().first_child() {
+                    if Rc::ptr_eq(&first, &self.node) {
+                        text.insert(0, '"');
                     }
-                    false
                 }
-                Selector::IdSelector(id_name) => {
-                    for attr in &e.attributes() {
-                        if attr.name() == "id" && attr.value() == *id_name {
-                            return true;
-                        }
+                if let Some(last) = parent.borrow().last_child().upgrade() {
+                    if Rc::ptr_eq(&last, &self.node) {
+                        text.push('"');
                     }
-                    false
                 }
-                Selector::UnknownSelector => false,
-            },
-            _ => false,
+            }
         }
+
+        Some(text)
+    }
+
+    /// Returns the `aria-label` of a link which has no content. The label is its accessible name.
+    /// https://www.w3.org/TR/wai-aria-1.2/#aria-label
+    fn aria_label_fallback(&self) -> Option<String> {
+        if self.node.borrow().first_child().is_some() {
+            return None;
+        }
+        match self.node_kind() {
+            NodeKind::Element(e) if e.kind() == ElementKind::A => e
+                .get_attribute("aria-label")
+                .map(|label| label.trim().to_string())
+                .filter(|label| !label.is_empty()),
+            _ => None,
+        }
+    }
+
+    /// Returns the `href` of the nearest <a> element containing this node. It's resolved against
+    /// the current URL of the browser like `Page::links`.
+    fn link_destination(&self) -> Option<String> {
+        let anchor = Node::closest(&self.node, "a")?;
+        let href = match anchor.borrow().kind() {
+            NodeKind::Element(e) => e.get_attribute("href")?,
+            _ => return None,
+        };
+        match self.browser.upgrade() {
+            Some(browser) => Some(browser.borrow().resolve_url(&href)),
+            None => Some(href),
+        }
+    }
+
+    /// Returns the opacity of this object multiplied by its ancestors' opacities, and the
+    /// background color behind the outermost translucent ancestor.
+    fn opacity_and_backdrop(&self) -> (f64, Color) {
+        let mut opacity = self.style.opacity();
+        let mut backdrop = None;
+        if opacity < 1.0 {
+            backdrop = self.parent().upgrade();
+        }
+
+        let mut current = self.parent().upgrade();
+        while let Some(n) = current {
+            let ancestor_opacity = n.borrow().style().opacity();
+            if ancestor_opacity < 1.0 {
+                opacity *= ancestor_opacity;
+                backdrop = n.borrow().parent().upgrade();
+            }
+            current = n.borrow().parent().upgrade();
+        }
+
+        let backdrop = match backdrop {
+            Some(n) => n.borrow().style().background_color(),
+            None => Color::white(),
+        };
+        (opacity, backdrop)
+    }
+
+    /// Returns the style used to paint this object. Neither UI supports alpha, so the colors of
+    /// a translucent object are blended with the background behind it. Returns None if the
+    /// object is fully transparent.
+    fn painted_style(&self) -> Option<ComputedStyle> {
+        let (opacity, backdrop) = self.opacity_and_backdrop();
+        if opacity <= 0.0 {
+            return None;
+        }
+
+        let mut style = self.style();
+        if opacity < 1.0 {
+            style.set_background_color(style.background_color().blend(&backdrop, opacity));
+            style.set_color(style.color().blend(&backdrop, opacity));
+            let border = style.border();
+            if let Some(color) = border.color() {
+                style.set_border(Border::new(
+                    border.width(),
+                    Some(color.blend(&backdrop, opacity)),
+                ));
+            }
+        }
+        Some(style)
+    }
+
+    /// Returns the rects which draw the border of this box over its background. The border is
+    /// drawn along the inside of the box because it doesn't take space in layout yet.
+    fn paint_border(&self, style: &ComputedStyle) -> Vec<DisplayItem> {
+        let border = style.border();
+        let (point, size) = (self.point(), self.size());
+        let width = (border.width() as i64).min(size.width());
+        let height = (border.width() as i64).min(size.height());
+        if width <= 0 || height <= 0 {
+            return vec![];
+        }
+
+        let mut border_style = style.clone();
+        border_style.set_background_color(border.color().unwrap_or_else(|| style.color()));
+        border_style.set_border_radius(BorderRadius::default());
+        let (x, y, w, h) = (point.x(), point.y(), size.width(), size.height());
+        [
+            (x, y, w, height),
+            (x, y + h - height, w, height),
+            (x, y, width, h),
+            (x + w - width, y, width, h),
+        ]
+        .into_iter()
+        .map(|(x, y, w, h)| DisplayItem::Rect {
+            style: border_style.clone(),
+            layout_point: LayoutPoint::new(x, y),
+            layout_size: LayoutSize::new(w, h),
+        })
+        .collect()
     }
 
     /// https://source.chromium.org/chromium/chromium/src/+/main:third_party/blink/renderer/core/layout/layout_object.h;drc=0e9a0b6e9bb6ec59521977eec805f5d0bca833e0;bpv=1;bpt=1;l=2377
     pub fn paint(&mut self) -> Vec<DisplayItem> {
-        if self.style.display() == DisplayType::DisplayNone {
+        if self.style.is_none() {
             return vec![];
         }
 
+        let style = match self.painted_style() {
+            Some(style) => style,
+            None => return vec![],
+        };
+
         match self.kind {
             LayoutObjectKind::Block => {
                 if let NodeKind::Element(_e) = self.node_kind() {
-                    return vec![DisplayItem::Rect {
-                        style: self.style(),
+                    let mut items = vec![DisplayItem::Rect {
+                        style: style.clone(),
                         layout_point: self.point(),
                         layout_size: self.size(),
                     }];
+                    items.extend(self.paint_border(&style));
+                    return items;
                 }
             }
             LayoutObjectKind::Inline => {
+                // An inline-block box paints its box like a block box.
+                if self.style.display() == DisplayType::InlineBlock {
+                    let mut items = vec![DisplayItem::Rect {
+                        style: style.clone(),
+                        layout_point: self.point(),
+                        layout_size: self.size(),
+                    }];
+                    items.extend(self.paint_border(&style));
+                    return items;
+                }
+
                 if let NodeKind::Element(e) = self.node_kind() {
                     if e.kind() == ElementKind::IMG {
                         for attr in &e.attributes() {
                             if attr.name() == "src" {
+                                let lazy = e
+                                    .get_attribute("loading")
+                                    .is_some_and(|loading| loading.eq_ignore_ascii_case("lazy"));
                                 return vec![DisplayItem::Img {
                                     src: attr.value(),
-                                    style: self.style(),
+                                    style: style.clone(),
                                     layout_point: self.point(),
+                                    lazy,
                                 }];
                             }
                         }
@@ -577,7 +1413,12 @@ impl LayoutObject {
                         let name = e.get_attribute("name");
                         let placeholder = e.get_attribute("placeholder");
                         // Use dynamic value if available, otherwise fall back to attribute value
-                        let value = e.get_value();
+                        let mut value = e.get_value();
+                        if is_button_input(&input_type) {
+                            value = Some(button_label(&input_type, value));
+                        } else if is_checkable_input(&input_type) {
+                            value = Some(check_mark(&input_type, e.is_checked()));
+                        }
 
                         console_debug(
                             &self.browser,
@@ -592,38 +1433,56 @@ impl LayoutObject {
                             name,
                             placeholder,
                             value,
-                            style: self.style(),
+                            caret: e.caret(),
+                            style: style.clone(),
                             layout_point: self.point(),
                             layout_size: self.size(),
                         }];
                     }
                 }
+
+                // An empty link (e.g. an icon link) shows its label so that it can be seen and
+                // focused.
+                if let Some(label) = self.aria_label_fallback() {
+                    let visited = match (self.link_destination(), self.browser.upgrade()) {
+                        (Some(href), Some(browser)) => browser.borrow().is_visited(&href),
+                        _ => false,
+                    };
+                    return vec![DisplayItem::Text {
+                        text: label,
+                        style: style.clone(),
+                        layout_point: self.point(),
+                        visited,
+                        highlight: None,
+                    }];
+                }
             }
             LayoutObjectKind::Text => {
-                if let NodeKind::Text(t) = self.node_kind() {
+                if let Some(t) = self.text() {
                     let mut v = vec![];
 
-                    let ratio = match self.style.font_size() {
-                        FontSize::Medium => 1,
-                        FontSize::XLarge => 2,
-                        FontSize::XXLarge => 3,
+                    let lines = match self.style.white_space() {
+                        WhiteSpace::Pre => preformatted_lines(&t, self.tab_size()),
+                        WhiteSpace::PreLine => pre_line_lines(&t, char_width(&self.style)),
+                        WhiteSpace::Normal => {
+                            split_text(collapse_spaces(&t), char_width(&self.style))
+                        }
+                    };
+                    let visited = match (self.link_destination(), self.browser.upgrade()) {
+                        (Some(href), Some(browser)) => browser.borrow().is_visited(&href),
+                        _ => false,
                     };
-                    let plain_text = t
-                        .replace("\n", " ")
-                        .split(' ')
-                        .filter(|s| !s.is_empty())
-                        .collect::<Vec<_>>()
-                        .join(" ");
-                    let lines = split_text(plain_text, CHAR_WIDTH * ratio);
                     let mut i = 0;
                     for line in lines {
                         let item = DisplayItem::Text {
                             text: line,
-                            style: self.style(),
+                            style: style.clone(),
                             layout_point: LayoutPoint::new(
                                 self.point().x(),
                                 self.point().y() + CHAR_HEIGHT_WITH_PADDING * i,
                             ),
+                            visited,
+                            highlight: None,
                         };
                         v.push(item);
                         i += 1;
@@ -29,4 +29,32 @@ impl LayoutPoint {
     pub fn set_y(&mut self, y: i64) {
         self.y = y;
     }
+
+    /// Returns the point moved by `dx` and `dy`, e.g. to convert a point in the content area to
+    /// a point in a window. The coordinates saturate instead of overflowing.
+    pub fn translate(&self, dx: i64, dy: i64) -> LayoutPoint {
+        LayoutPoint::new(self.x.saturating_add(dx), self.y.saturating_add(dy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        let point = LayoutPoint::new(10, 20);
+        assert_eq!(LayoutPoint::new(15, 45), point.translate(5, 25));
+        assert_eq!(LayoutPoint::new(-5, 0), point.translate(-15, -20));
+        assert_eq!(point, point.translate(0, 0));
+    }
+
+    #[test]
+    fn test_translate_overflow() {
+        let point = LayoutPoint::new(i64::MAX - 1, i64::MIN + 1);
+        assert_eq!(
+            LayoutPoint::new(i64::MAX, i64::MIN),
+            point.translate(10, -10)
+        );
+    }
 }
@@ -29,4 +29,35 @@ impl LayoutSize {
     pub fn set_height(&mut self, height: i64) {
         self.height = height;
     }
+
+    /// Returns the size shrunk to fit in `max_width` x `max_height`, e.g. the space left in a
+    /// window. A negative width or height becomes 0.
+    pub fn clamp(&self, max_width: i64, max_height: i64) -> LayoutSize {
+        LayoutSize::new(
+            self.width.min(max_width).max(0),
+            self.height.min(max_height).max(0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp() {
+        let size = LayoutSize::new(100, 50);
+        assert_eq!(LayoutSize::new(80, 50), size.clamp(80, 60));
+        assert_eq!(size, size.clamp(i64::MAX, i64::MAX));
+    }
+
+    #[test]
+    fn test_clamp_negative() {
+        let size = LayoutSize::new(100, 50);
+        assert_eq!(LayoutSize::new(0, 0), size.clamp(-10, -1));
+        assert_eq!(
+            LayoutSize::new(0, 10),
+            LayoutSize::new(-5, 10).clamp(20, 20)
+        );
+    }
 }
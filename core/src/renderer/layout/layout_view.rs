@@ -5,16 +5,24 @@
 use crate::browser::Browser;
 use crate::constants::CONTENT_AREA_WIDTH;
 use crate::display_item::DisplayItem;
+use crate::renderer::css::cssom::CssParser;
 use crate::renderer::css::cssom::StyleSheet;
+use crate::renderer::css::token::CssTokenizer;
+use crate::renderer::css::user_agent::DEFAULT_USER_AGENT_STYLESHEET;
 use crate::renderer::dom::api::get_target_element_node;
 use crate::renderer::dom::node::ElementKind;
 use crate::renderer::dom::node::Node;
+use crate::renderer::dom::node::NodeKind;
+use crate::renderer::layout::computed_style::DisplayType;
 use crate::renderer::layout::layout_object::create_layout_object;
 use crate::renderer::layout::layout_object::LayoutObject;
 use crate::renderer::layout::layout_object::LayoutObjectKind;
 use crate::renderer::layout::layout_point::LayoutPoint;
 use crate::renderer::layout::layout_size::LayoutSize;
+use crate::utils::console_warning;
+use alloc::format;
 use alloc::rc::{Rc, Weak};
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::cell::RefCell;
 
@@ -107,6 +115,28 @@ fn build_layout_tree(
         };
         obj.borrow_mut().set_first_child(first_child);
         obj.borrow_mut().set_next_sibling(next_sibling);
+
+        // https://www.w3.org/TR/css-flexbox-1/#flex-items
+        // The children of a flex container are placed as block boxes. A text run directly in
+        // the container is wrapped in an anonymous block box.
+        if obj.borrow().style().is_flex() {
+            let mut items = Vec::new();
+            for item in obj.borrow().children() {
+                if item.borrow().kind() == LayoutObjectKind::Text {
+                    items.push(LayoutObject::anonymous_block(&item));
+                    continue;
+                }
+                item.borrow_mut().blockify();
+                items.push(item);
+            }
+
+            let mut next = None;
+            for item in items.into_iter().rev() {
+                item.borrow_mut().set_next_sibling(next);
+                next = Some(item);
+            }
+            obj.borrow_mut().set_first_child(next);
+        }
     }
 
     layout_object
@@ -129,37 +159,122 @@ impl LayoutView {
         // https://html.spec.whatwg.org/multipage/dom.html#flow-content-2
         let body_root = get_target_element_node(Some(root), ElementKind::Body);
 
+        // https://www.w3.org/TR/css-cascade-4/#cascade-origin
+        // User-agent declarations come first so that author declarations override them.
+        let mut rules = Self::user_agent_stylesheet(&browser).rules;
+        rules.extend(cssom.rules.clone());
+        let mut stylesheet = StyleSheet::new();
+        stylesheet.set_rules(rules);
+
         let mut tree = Self {
-            root: build_layout_tree(browser, &body_root, &None, cssom),
+            root: build_layout_tree(browser.clone(), &body_root, &None, &stylesheet),
         };
 
         tree.update_layout();
+        Self::report_overflow(&browser, &tree.root);
 
         tree
     }
 
-    fn calculate_node_size(node: &Option<Rc<RefCell<LayoutObject>>>, parent_size: LayoutSize) {
-        if let Some(n) = node {
-            // For block elements, we should layout the size before calling children.
-            if n.borrow().kind() == LayoutObjectKind::Block {
-                n.borrow_mut().compute_size(parent_size);
-            }
+    fn user_agent_stylesheet(browser: &Weak<RefCell<Browser>>) -> StyleSheet {
+        let css = match browser.upgrade() {
+            Some(b) => b.borrow().user_agent_stylesheet(),
+            None => String::from(DEFAULT_USER_AGENT_STYLESHEET),
+        };
+        CssParser::new(browser.clone(), CssTokenizer::new(css)).parse_stylesheet()
+    }
 
-            let first_child = n.borrow().first_child();
-            Self::calculate_node_size(&first_child, n.borrow().size());
+    fn calculate_node_size(
+        node: &Option<Rc<RefCell<LayoutObject>>>,
+        parent_size: LayoutSize,
+        measure: bool,
+    ) {
+        if let Some(n) = node {
+            Self::calculate_box_size(n, parent_size, measure);
 
             let next_sibling = n.borrow().next_sibling();
-            Self::calculate_node_size(&next_sibling, parent_size);
+            Self::calculate_node_size(&next_sibling, parent_size, measure);
+        }
+    }
+
+    /// Calculates the size of `node` and its descendants. When `measure` is true, only the
+    /// sizes which the content widths depend on are needed, so flex items are laid out once.
+    fn calculate_box_size(
+        node: &Rc<RefCell<LayoutObject>>,
+        parent_size: LayoutSize,
+        measure: bool,
+    ) {
+        // For block elements, we should layout the size before calling children.
+        if node.borrow().kind() == LayoutObjectKind::Block {
+            node.borrow_mut().compute_size(parent_size);
+        }
+
+        if node.borrow().style().display() == DisplayType::Table {
+            let columns = node.borrow().table_column_count().max(1);
+            let column_width = node.borrow().size().width() / columns;
+            Self::calculate_table_part_sizes(node, column_width, measure);
+        } else if node.borrow().style().is_flex() {
+            Self::calculate_flex_item_sizes(node, measure);
+        } else {
+            let first_child = node.borrow().first_child();
+            Self::calculate_node_size(&first_child, node.borrow().size(), measure);
+        }
+
+        // TODO: optimize this code because we call compute_size() twice.
+        // For inline, text elements and the height of block elements, we should layout the size after calling children.
+        node.borrow_mut().compute_size(parent_size);
+    }
 
-            // TODO: optimize this code because we call compute_size() twice.
-            // For inline, text elements and the height of block elements, we should layout the size after calling children.
-            n.borrow_mut().compute_size(parent_size);
+    /// Calculates the sizes of the flex items in `container`. The items are measured once to
+    /// find their content widths, and laid out again with the widths of the flex layout. While
+    /// `measure` is true, the second layout is skipped.
+    /// https://www.w3.org/TR/css-flexbox-1/#layout-algorithm
+    fn calculate_flex_item_sizes(container: &Rc<RefCell<LayoutObject>>, measure: bool) {
+        let container_size = container.borrow().size();
+        let items = container.borrow().children();
+        for item in &items {
+            Self::calculate_box_size(item, container_size, true);
+        }
+        if measure {
+            return;
+        }
+
+        let widths = container.borrow().flex_item_widths();
+        for (item, width) in items.iter().zip(widths) {
+            Self::calculate_box_size(item, LayoutSize::new(width, container_size.height()), false);
+        }
+    }
+
+    /// Calculates the sizes of the rows in a table or a row group `node`. Every column is
+    /// `column_width` wide, and a cell is as wide as the columns it spans.
+    /// https://www.w3.org/TR/CSS22/tables.html#fixed-table-layout
+    fn calculate_table_part_sizes(
+        node: &Rc<RefCell<LayoutObject>>,
+        column_width: i64,
+        measure: bool,
+    ) {
+        let size = node.borrow().size();
+        for child in node.borrow().children() {
+            if child.borrow().style().display() != DisplayType::TableRow {
+                child.borrow_mut().compute_size(size);
+                Self::calculate_table_part_sizes(&child, column_width, measure);
+                child.borrow_mut().compute_size(size);
+                continue;
+            }
+
+            child.borrow_mut().compute_size(size);
+            for cell in child.borrow().children() {
+                let span = cell.borrow().column_span();
+                Self::calculate_box_size(&cell, LayoutSize::new(column_width * span, 0), measure);
+            }
+            child.borrow_mut().compute_size(size);
         }
     }
 
     fn calculate_node_position(
         node: &Option<Rc<RefCell<LayoutObject>>>,
         parent_point: LayoutPoint,
+        parent_size: LayoutSize,
         previous_sibling_kind: LayoutObjectKind,
         previous_sibling_point: Option<LayoutPoint>,
         previous_sibling_size: Option<LayoutSize>,
@@ -167,24 +282,19 @@ impl LayoutView {
         if let Some(n) = node {
             n.borrow_mut().compute_position(
                 parent_point,
+                parent_size,
                 previous_sibling_kind,
                 previous_sibling_point,
                 previous_sibling_size,
             );
 
-            let first_child = n.borrow().first_child();
-            Self::calculate_node_position(
-                &first_child,
-                n.borrow().point(),
-                LayoutObjectKind::Block,
-                None,
-                None,
-            );
+            Self::calculate_children_position(n);
 
             let next_sibling = n.borrow().next_sibling();
             Self::calculate_node_position(
                 &next_sibling,
                 parent_point,
+                parent_size,
                 n.borrow().kind(),
                 Some(n.borrow().point()),
                 Some(n.borrow().size()),
@@ -192,19 +302,126 @@ impl LayoutView {
         }
     }
 
+    fn calculate_children_position(node: &Rc<RefCell<LayoutObject>>) {
+        if node.borrow().style().is_flex() {
+            Self::calculate_flex_item_positions(node);
+            return;
+        }
+        if node.borrow().style().display() == DisplayType::TableRow {
+            Self::calculate_table_cell_positions(node);
+            return;
+        }
+
+        let first_child = node.borrow().first_child();
+        Self::calculate_node_position(
+            &first_child,
+            node.borrow().point(),
+            node.borrow().size(),
+            LayoutObjectKind::Block,
+            None,
+            None,
+        );
+    }
+
+    /// Places the flex items in `container` side by side. Each flex line starts below the
+    /// previous one. The items are distributed along the line by `justify-content` and placed
+    /// within the line by `align-items`. A single line is as tall as the container.
+    /// https://www.w3.org/TR/css-flexbox-1/#algo-main-align
+    /// https://www.w3.org/TR/css-flexbox-1/#algo-cross-align
+    fn calculate_flex_item_positions(container: &Rc<RefCell<LayoutObject>>) {
+        let container_point = container.borrow().point();
+        let container_size = container.borrow().size();
+        let style = container.borrow().style();
+        let lines = container.borrow().flex_lines();
+        let single_line = lines.len() == 1;
+
+        let mut y = container_point.y();
+        for line in lines {
+            let sizes: Vec<LayoutSize> = line.iter().map(|item| item.borrow().size()).collect();
+            let line_height = if single_line {
+                container_size.height()
+            } else {
+                sizes.iter().map(|size| size.height()).max().unwrap_or(0)
+            };
+            let free = container_size.width() - sizes.iter().map(|size| size.width()).sum::<i64>();
+            let (offset, gap) = style.justify_content().distribute(free, line.len());
+
+            let mut x = container_point.x() + offset;
+            for (item, size) in line.iter().zip(sizes) {
+                let item_y = y + style.align_items().offset(line_height - size.height());
+                item.borrow_mut().compute_position(
+                    LayoutPoint::new(x, item_y),
+                    size,
+                    LayoutObjectKind::Block,
+                    None,
+                    None,
+                );
+                Self::calculate_children_position(item);
+
+                x += size.width() + gap;
+            }
+            y += line_height;
+        }
+    }
+
+    /// Places the cells of a table `row` side by side from the left edge of the row.
+    fn calculate_table_cell_positions(row: &Rc<RefCell<LayoutObject>>) {
+        let row_point = row.borrow().point();
+        let mut x = row_point.x();
+        for cell in row.borrow().children() {
+            let size = cell.borrow().size();
+            cell.borrow_mut().compute_position(
+                LayoutPoint::new(x, row_point.y()),
+                size,
+                LayoutObjectKind::Block,
+                None,
+                None,
+            );
+            Self::calculate_children_position(&cell);
+            x += size.width();
+        }
+    }
+
     /// Calculate the layout point.
     fn update_layout(&mut self) {
-        Self::calculate_node_size(&self.root, LayoutSize::new(CONTENT_AREA_WIDTH, 0));
+        Self::calculate_node_size(&self.root, LayoutSize::new(CONTENT_AREA_WIDTH, 0), false);
 
         Self::calculate_node_position(
             &self.root,
             LayoutPoint::new(0, 0),
+            LayoutSize::new(CONTENT_AREA_WIDTH, 0),
             LayoutObjectKind::Block,
             None,
             None,
         );
     }
 
+    /// Logs a warning for each box which extends beyond the right edge of the viewport. The
+    /// descendants of an overflowing box are not reported.
+    fn report_overflow(browser: &Weak<RefCell<Browser>>, node: &Option<Rc<RefCell<LayoutObject>>>) {
+        if let Some(n) = node {
+            let right = n.borrow().point().x() + n.borrow().size().width();
+            if right > CONTENT_AREA_WIDTH {
+                let name = match n.borrow().node_kind() {
+                    NodeKind::Element(e) => format!("<{}>", e.kind()),
+                    _ => String::from("text"),
+                };
+                console_warning(
+                    browser,
+                    format!(
+                        "{} overflows the viewport by {}px",
+                        name,
+                        right - CONTENT_AREA_WIDTH
+                    ),
+                );
+            } else {
+                Self::report_overflow(browser, &n.borrow().first_child());
+            }
+
+            Self::report_overflow(browser, &n.borrow().next_sibling());
+        }
+    }
+
     pub fn root(&self) -> Option<Rc<RefCell<LayoutObject>>> {
         self.root.clone()
     }
@@ -265,6 +482,31 @@ impl LayoutView {
         }
     }
 
+    fn find_layout_object_by_node_internal(
+        layout_object: &Option<Rc<RefCell<LayoutObject>>>,
+        node: &Rc<RefCell<Node>>,
+    ) -> Option<Rc<RefCell<LayoutObject>>> {
+        let n = layout_object.as_ref()?;
+        if Rc::ptr_eq(&n.borrow().node(), node) {
+            return Some(n.clone());
+        }
+
+        let first_child = n.borrow().first_child();
+        Self::find_layout_object_by_node_internal(&first_child, node).or_else(|| {
+            let next_sibling = n.borrow().next_sibling();
+            Self::find_layout_object_by_node_internal(&next_sibling, node)
+        })
+    }
+
+    /// Returns the LayoutObject created for a DOM `node`. None if the node has no box, e.g.
+    /// `display: none`.
+    pub fn find_layout_object_by_node(
+        &self,
+        node: &Rc<RefCell<Node>>,
+    ) -> Option<Rc<RefCell<LayoutObject>>> {
+        Self::find_layout_object_by_node_internal(&self.root(), node)
+    }
+
     /// Returns a LayoutObject placed on `position`. None if it doesn't exist.
     pub fn find_node_by_position(&self, position: (i64, i64)) -> Option<Rc<RefCell<LayoutObject>>> {
         Self::find_node_by_position_internal(&self.root(), position)
@@ -275,6 +517,7 @@ impl LayoutView {
 mod tests {
     use super::*;
     use crate::alloc::string::ToString;
+    use crate::constants::CHAR_HEIGHT_WITH_PADDING;
     use crate::renderer::css::cssom::CssParser;
     use crate::renderer::css::token::CssTokenizer;
     use crate::renderer::dom::api::get_style_content;
@@ -282,7 +525,17 @@ mod tests {
     use crate::renderer::dom::node::NodeKind;
     use crate::renderer::html::parser::HtmlParser;
     use crate::renderer::html::token::HtmlTokenizer;
+    use crate::renderer::layout::color::Color;
+    use crate::renderer::layout::computed_style::BorderRadius;
+    use crate::renderer::layout::computed_style::ComputedStyle;
+    use crate::renderer::layout::computed_style::Direction;
+    use crate::renderer::layout::computed_style::FontSize;
+    use crate::renderer::layout::computed_style::FontStyle;
+    use crate::renderer::layout::computed_style::FontWeight;
+    use crate::renderer::layout::computed_style::TextAlign;
+    use crate::renderer::layout::computed_style::TextDecoration;
     use alloc::string::String;
+    use alloc::vec;
 
     fn create_layout_view(html: String) -> LayoutView {
         let browser = Browser::new();
@@ -454,4 +707,1136 @@ mod tests {
             .next_sibling()
             .is_none());
     }
+
+    #[test]
+    fn test_nested_emphasis() {
+        let html = "<html><head></head><body><b><em>text</em></b></body></html>".to_string();
+        let layout_view = create_layout_view(html);
+
+        let display_items = layout_view.paint();
+        let text_style = display_items
+            .iter()
+            .find_map(|item| match item {
+                DisplayItem::Text { style, .. } => Some(style.clone()),
+                _ => None,
+            })
+            .expect("text item should exist");
+        assert_eq!(FontWeight::Bold, text_style.font_weight());
+        assert_eq!(FontStyle::Italic, text_style.font_style());
+    }
+
+    #[test]
+    fn test_visited_link() {
+        let browser = Browser::new();
+        browser
+            .borrow_mut()
+            .push_history("http://example.com/visited".to_string());
+        browser
+            .borrow_mut()
+            .push_history("http://example.com/dir/page".to_string());
+
+        // Relative hrefs are resolved against the current URL.
+        let html = r#"<html><body><a href="http://example.com/visited">visited</a><a href="http://example.com/new">new</a><a href="../visited">relative</a><a href="visited">other</a></body></html>"#.to_string();
+        let t = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+        let window = HtmlParser::new(Rc::downgrade(&browser), t).construct_tree();
+        let dom = window.borrow().document();
+        let cssom = CssParser::new(Rc::downgrade(&browser), CssTokenizer::new(String::new()))
+            .parse_stylesheet();
+        let layout_view = LayoutView::new(Rc::downgrade(&browser), dom, &cssom);
+
+        let texts: Vec<(String, bool)> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, visited, .. } => Some((text, visited)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("visited".to_string(), true),
+                ("new".to_string(), false),
+                ("relative".to_string(), true),
+                ("other".to_string(), false),
+            ],
+            texts
+        );
+    }
+
+    #[test]
+    fn test_inline_block() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.box { display: inline-block; width: 100; }</style></head><body><div>before <div class=\"box\">box</div>after</div></body></html>"
+                .to_string(),
+        );
+
+        let before = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("div should exist")
+            .borrow()
+            .first_child()
+            .expect("text should exist");
+        let inline_block = before
+            .borrow()
+            .next_sibling()
+            .expect("inline-block div should exist");
+        let after = inline_block
+            .borrow()
+            .next_sibling()
+            .expect("text should exist");
+
+        assert_eq!(LayoutObjectKind::Inline, inline_block.borrow().kind());
+        assert_eq!(100, inline_block.borrow().size().width());
+        // The inline-block box flows on the same line as the surrounding texts.
+        assert_eq!(
+            before.borrow().point().y(),
+            inline_block.borrow().point().y()
+        );
+        assert_eq!(
+            before.borrow().point().x() + before.borrow().size().width(),
+            inline_block.borrow().point().x()
+        );
+        assert_eq!(
+            inline_block.borrow().point().x() + 100,
+            after.borrow().point().x()
+        );
+        assert_eq!(before.borrow().point().y(), after.borrow().point().y());
+    }
+
+    #[test]
+    fn test_monospace() {
+        let layout_view = create_layout_view(
+            "<html><body><p>text <code>let x</code></p></body></html>".to_string(),
+        );
+
+        let code = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("p should exist")
+            .borrow()
+            .first_child()
+            .expect("text should exist")
+            .borrow()
+            .next_sibling()
+            .expect("code should exist");
+        assert_eq!(LayoutObjectKind::Inline, code.borrow().kind());
+        assert!(code.borrow().style().is_monospace());
+
+        let text = code.borrow().first_child().expect("text should exist");
+        assert!(text.borrow().style().is_monospace());
+    }
+
+    #[test]
+    fn test_first_child() {
+        let layout_view = create_layout_view(
+            "<html><head><style>li:first-child { color: red; } li:last-child { color: blue; }</style></head><body><ul><li>a</li><li>b</li><li>c</li></ul></body></html>"
+                .to_string(),
+        );
+
+        let colors: Vec<(String, Option<String>)> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, style, .. } => Some((text, style.color().name())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("a".to_string(), Some("red".to_string())),
+                ("b".to_string(), Some("black".to_string())),
+                ("c".to_string(), Some("blue".to_string())),
+            ],
+            colors
+        );
+    }
+
+    #[test]
+    fn test_attribute_selector() {
+        let layout_view = create_layout_view(
+            "<html><head><style>input[type=\"password\"] { color: red; }</style></head><body><input type=\"text\"><input type=\"password\"></body></html>"
+                .to_string(),
+        );
+
+        let colors: Vec<(String, Option<String>)> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Input {
+                    input_type, style, ..
+                } => Some((input_type, style.color().name())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("text".to_string(), Some("black".to_string())),
+                ("password".to_string(), Some("red".to_string())),
+            ],
+            colors
+        );
+    }
+
+    #[test]
+    fn test_no_break_space() {
+        let texts = |html: String| -> Vec<String> {
+            create_layout_view(html)
+                .paint()
+                .into_iter()
+                .filter_map(|item| match item {
+                    DisplayItem::Text { text, .. } => Some(text),
+                    _ => None,
+                })
+                .collect()
+        };
+        // The line is wide enough for the "a"s and "10", but not for "km".
+        let prefix = "a".repeat(71);
+
+        assert_eq!(
+            vec![format!("{} 10", prefix), "km".to_string()],
+            texts(format!(
+                "<html><head></head><body><p>{} 10 km</p></body></html>",
+                prefix
+            ))
+        );
+        assert_eq!(
+            vec![prefix.clone(), "10 km".to_string()],
+            texts(format!(
+                "<html><head></head><body><p>{} 10&nbsp;km</p></body></html>",
+                prefix
+            ))
+        );
+    }
+
+    #[test]
+    fn test_user_agent_stylesheet() {
+        let link_decoration = |browser: &Rc<RefCell<Browser>>| {
+            let html = "<html><body><a href=\"/\">link</a></body></html>".to_string();
+            let t = HtmlTokenizer::new(Rc::downgrade(browser), html);
+            let window = HtmlParser::new(Rc::downgrade(browser), t).construct_tree();
+            let dom = window.borrow().document();
+            let layout_view = LayoutView::new(Rc::downgrade(browser), dom, &StyleSheet::new());
+            layout_view
+                .paint()
+                .iter()
+                .find_map(|item| match item {
+                    DisplayItem::Text { style, .. } => Some(style.text_decoration()),
+                    _ => None,
+                })
+                .expect("text item should exist")
+        };
+
+        let browser = Browser::new();
+        assert_eq!(TextDecoration::Underline, link_decoration(&browser));
+
+        browser
+            .borrow_mut()
+            .set_user_agent_stylesheet("a { text-decoration: none; }".to_string());
+        assert_eq!(TextDecoration::None, link_decoration(&browser));
+    }
+
+    #[test]
+    fn test_rtl_direction() {
+        let html = r#"<html><body><p dir="rtl">text</p></body></html>"#.to_string();
+        let layout_view = create_layout_view(html);
+
+        let p = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("p node should exist");
+        assert_eq!(Direction::Rtl, p.borrow().style().direction());
+
+        let text = p.borrow().first_child().expect("text node should exist");
+        let text_width = text.borrow().size().width();
+        assert_eq!(CONTENT_AREA_WIDTH - text_width, text.borrow().point().x());
+    }
+
+    #[test]
+    fn test_center_element() {
+        let html = "<html><body><center>text</center></body></html>".to_string();
+        let layout_view = create_layout_view(html);
+
+        let center = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("center node should exist");
+        assert_eq!(LayoutObjectKind::Block, center.borrow().kind());
+        assert_eq!(TextAlign::Center, center.borrow().style().text_align());
+
+        let text = center
+            .borrow()
+            .first_child()
+            .expect("text node should exist");
+        let text_width = text.borrow().size().width();
+        assert_eq!(
+            (CONTENT_AREA_WIDTH - text_width) / 2,
+            text.borrow().point().x()
+        );
+    }
+
+    #[test]
+    fn test_table_header_cell() {
+        let html =
+            "<html><body><table><tr><th>head</th></tr><tr><td>data</td></tr></table></body></html>"
+                .to_string();
+        let layout_view = create_layout_view(html);
+
+        let styles: Vec<(String, FontWeight, TextAlign)> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, style, .. } => {
+                    Some((text, style.font_weight(), style.text_align()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("head".to_string(), FontWeight::Bold, TextAlign::Center),
+                ("data".to_string(), FontWeight::Normal, TextAlign::Start),
+            ],
+            styles
+        );
+    }
+
+    #[test]
+    fn test_table_colspan() {
+        let layout_view = create_layout_view(
+            "<html><body><table><tr><th>a</th><th>b</th></tr><tr><td colspan=\"2\">wide</td></tr><tr><td colspan=\"2\">c</td><td>d</td></tr></table></body></html>"
+                .to_string(),
+        );
+
+        let table = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("table should exist");
+        let table_width = table.borrow().size().width();
+        assert_eq!(CONTENT_AREA_WIDTH, table_width);
+        let rows = table.borrow().children();
+        assert_eq!(3, rows.len());
+
+        // The last row has the most columns, so the table has three equal columns.
+        let column_width = table_width / 3;
+        let header = rows[0].borrow().children();
+        assert_eq!(column_width, header[0].borrow().size().width());
+        assert_eq!(column_width, header[1].borrow().size().width());
+        assert_eq!(
+            header[0].borrow().point().x() + column_width,
+            header[1].borrow().point().x()
+        );
+        assert_eq!(
+            header[0].borrow().point().y(),
+            header[1].borrow().point().y()
+        );
+
+        // A spanning cell covers its columns and shifts the following cells.
+        let spanning = rows[1].borrow().first_child().expect("cell should exist");
+        assert_eq!(column_width * 2, spanning.borrow().size().width());
+        let last = rows[2].borrow().children();
+        assert_eq!(column_width * 2, last[1].borrow().point().x());
+        assert_eq!(
+            rows[1].borrow().point().y() + rows[1].borrow().size().height(),
+            rows[2].borrow().point().y()
+        );
+
+        // Without a wider row, a cell spanning both columns is as wide as the table. Cells and
+        // rows end where the next ones start.
+        let layout_view = create_layout_view(
+            "<html><body><table><tr><th>a<th>b<tr><td colspan=\"2\">wide</table></body></html>"
+                .to_string(),
+        );
+        let table = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("table should exist");
+        let spanning = table.borrow().children()[1]
+            .borrow()
+            .first_child()
+            .expect("cell should exist");
+        assert_eq!(
+            table.borrow().size().width(),
+            spanning.borrow().size().width()
+        );
+    }
+
+    #[test]
+    fn test_table_cell_border() {
+        let layout_view = create_layout_view(
+            "<html><body><table><tr><td>cell</td></tr></table></body></html>".to_string(),
+        );
+        let cell = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .and_then(|table| table.borrow().first_child())
+            .and_then(|row| row.borrow().first_child())
+            .expect("cell should exist");
+        let (x, y) = (cell.borrow().point().x(), cell.borrow().point().y());
+        let (w, h) = (cell.borrow().size().width(), cell.borrow().size().height());
+
+        let borders: Vec<(LayoutPoint, LayoutSize)> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Rect {
+                    style,
+                    layout_point,
+                    layout_size,
+                } if style.background_color().name() == Some("lightgray".to_string()) => {
+                    Some((layout_point, layout_size))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (LayoutPoint::new(x, y), LayoutSize::new(w, 1)),
+                (LayoutPoint::new(x, y + h - 1), LayoutSize::new(w, 1)),
+                (LayoutPoint::new(x, y), LayoutSize::new(1, h)),
+                (LayoutPoint::new(x + w - 1, y), LayoutSize::new(1, h)),
+            ],
+            borders
+        );
+
+        // A border needs a line style, and the author can remove the border of a cell.
+        let layout_view = create_layout_view(
+            "<html><head><style>.a { border: 2px solid red; } .b { border: 2px red; } td { border: none; }</style></head><body><div class=\"a\">a</div><div class=\"b\">b</div><table><tr><td>cell</td></tr></table></body></html>"
+                .to_string(),
+        );
+        let border_colors: Vec<String> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Rect { style, .. } if style.background_color() != Color::white() => {
+                    style.background_color().name()
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["red".to_string(); 4], border_colors);
+    }
+
+    #[test]
+    fn test_opacity() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.half { background-color: red; opacity: 0.5; } .hidden { opacity: 0; }</style></head><body><div class=\"half\">half</div><div class=\"hidden\">hidden</div></body></html>"
+                .to_string(),
+        );
+
+        let display_items = layout_view.paint();
+        let rect_colors: Vec<u32> = display_items
+            .iter()
+            .filter_map(|item| match item {
+                DisplayItem::Rect { style, .. } => Some(style.background_color().code_u32()),
+                _ => None,
+            })
+            .collect();
+        // The red box is blended with the white background of the body.
+        assert!(rect_colors.contains(&0xff8080));
+
+        // A fully transparent element and its text are not painted.
+        let texts: Vec<String> = display_items
+            .iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, .. } => Some(text.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["half".to_string()], texts);
+    }
+
+    #[test]
+    fn test_max_width() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.fixed { max-width: 400px; margin: auto; } .half { max-width: 50%; }</style></head><body><div class=\"fixed\">fixed</div><div class=\"half\">half</div></body></html>"
+                .to_string(),
+        );
+
+        let fixed = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("div should exist");
+        assert_eq!(400, fixed.borrow().size().width());
+        // Auto margins center the box.
+        assert_eq!((CONTENT_AREA_WIDTH - 400) / 2, fixed.borrow().point().x());
+
+        let half = fixed.borrow().next_sibling().expect("div should exist");
+        assert_eq!(CONTENT_AREA_WIDTH / 2, half.borrow().size().width());
+        assert_eq!(0, half.borrow().point().x());
+    }
+
+    #[test]
+    fn test_overflow_warning() {
+        let browser = Browser::new();
+        let html = "<html><head><style>.wide { width: 1000; height: 10; }</style></head><body><div class=\"wide\"><p>inner</p></div></body></html>".to_string();
+        let t = HtmlTokenizer::new(Rc::downgrade(&browser), html);
+        let window = HtmlParser::new(Rc::downgrade(&browser), t).construct_tree();
+        let dom = window.borrow().document();
+        let style = get_style_content(dom.clone());
+        let cssom =
+            CssParser::new(Rc::downgrade(&browser), CssTokenizer::new(style)).parse_stylesheet();
+        LayoutView::new(Rc::downgrade(&browser), dom, &cssom);
+
+        let warnings: Vec<String> = browser
+            .borrow()
+            .logs()
+            .iter()
+            .map(|l| l.to_string())
+            .filter(|l| l.contains("overflows"))
+            .collect();
+        assert_eq!(
+            vec!["Warning: <div> overflows the viewport by 410px".to_string()],
+            warnings
+        );
+    }
+
+    #[test]
+    fn test_section() {
+        let layout_view = create_layout_view(
+            "<html><body><section><p>first</p><p>second</p></section><footer>footer</footer></body></html>"
+                .to_string(),
+        );
+
+        let section = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("section should exist");
+        assert_eq!(LayoutObjectKind::Block, section.borrow().kind());
+
+        // Children of the section are stacked vertically.
+        let first = section.borrow().first_child().expect("p should exist");
+        let second = first.borrow().next_sibling().expect("p should exist");
+        assert_eq!(first.borrow().point().x(), second.borrow().point().x());
+        assert!(first.borrow().point().y() < second.borrow().point().y());
+
+        let footer = section
+            .borrow()
+            .next_sibling()
+            .expect("footer should exist");
+        assert_eq!(LayoutObjectKind::Block, footer.borrow().kind());
+        assert_eq!(
+            section.borrow().point().y() + section.borrow().size().height(),
+            footer.borrow().point().y()
+        );
+    }
+
+    #[test]
+    fn test_q() {
+        let layout_view = create_layout_view(
+            "<html><body><p>say <q>hi</q> <abbr title=\"HyperText\">HTML</abbr></p></body></html>"
+                .to_string(),
+        );
+
+        let texts: Vec<String> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert!(texts.contains(&"\"hi\"".to_string()));
+        assert!(texts.contains(&"HTML".to_string()));
+    }
+
+    #[test]
+    fn test_inherit_and_initial() {
+        let layout_view = create_layout_view(
+            "<html><head><style>div { color: green; } p { color: red; } .inherit { color: inherit; } .initial { color: initial; }</style></head><body><div><p class=\"inherit\">x</p><p class=\"initial\">y</p></div></body></html>"
+                .to_string(),
+        );
+
+        let colors: Vec<(String, Color)> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, style, .. } => Some((text, style.color())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "x".to_string(),
+                    Color::from_name("green").expect("failed to parse a color")
+                ),
+                ("y".to_string(), Color::black()),
+            ],
+            colors
+        );
+    }
+
+    #[test]
+    fn test_line_through() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.gone { text-decoration: line-through; }</style></head><body><p><del>a</del><s>b</s><strike>c</strike><em class=\"gone\">d</em>e</p></body></html>"
+                .to_string(),
+        );
+
+        let decorations: Vec<(String, TextDecoration)> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, style, .. } => Some((text, style.text_decoration())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("a".to_string(), TextDecoration::LineThrough),
+                ("b".to_string(), TextDecoration::LineThrough),
+                ("c".to_string(), TextDecoration::LineThrough),
+                ("d".to_string(), TextDecoration::LineThrough),
+                ("e".to_string(), TextDecoration::None),
+            ],
+            decorations
+        );
+    }
+
+    #[test]
+    fn test_multiple_text_decorations() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.both { text-decoration: underline line-through; }</style></head><body><p class=\"both\">a</p><p>b</p></body></html>"
+                .to_string(),
+        );
+
+        let styles: Vec<ComputedStyle> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { style, .. } => Some(style),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(2, styles.len());
+        assert!(styles[0].has_text_decoration(TextDecoration::Underline));
+        assert!(styles[0].has_text_decoration(TextDecoration::LineThrough));
+        assert!(!styles[0].has_text_decoration(TextDecoration::None));
+        assert!(styles[1].has_text_decoration(TextDecoration::None));
+        assert!(!styles[1].has_text_decoration(TextDecoration::Underline));
+    }
+
+    #[test]
+    fn test_base_font_px() {
+        let layout = |browser: &Rc<RefCell<Browser>>| {
+            let html = "<html><body><p>text</p></body></html>".to_string();
+            let t = HtmlTokenizer::new(Rc::downgrade(browser), html);
+            let window = HtmlParser::new(Rc::downgrade(browser), t).construct_tree();
+            let dom = window.borrow().document();
+            let layout_view = LayoutView::new(Rc::downgrade(browser), dom, &StyleSheet::new());
+            let font_size = layout_view
+                .paint()
+                .iter()
+                .find_map(|item| match item {
+                    DisplayItem::Text { style, .. } => Some(style.font_size()),
+                    _ => None,
+                })
+                .expect("text item should exist");
+            let height = layout_view
+                .root()
+                .expect("root should exist")
+                .borrow()
+                .size()
+                .height();
+            (font_size, height)
+        };
+
+        let browser = Browser::new();
+        let (font_size, height) = layout(&browser);
+        assert_eq!(FontSize::Medium, font_size);
+
+        browser.borrow_mut().set_base_font_px(24.0);
+        let (larger_font_size, larger_height) = layout(&browser);
+        assert_eq!(FontSize::XLarge, larger_font_size);
+        assert!(larger_height > height);
+    }
+
+    #[test]
+    fn test_invisible_break_opportunities() {
+        let a = "a".repeat(50);
+        let b = "b".repeat(50);
+        let html = format!(
+            "<html><body><p>{}\u{200B}{}</p><p>{}\u{00AD}{}</p><p>co\u{00AD}op</p></body></html>",
+            a, b, a, b
+        );
+        let layout_view = create_layout_view(html);
+
+        let texts: Vec<String> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                // A zero width space is a break opportunity and is not drawn.
+                a.clone(),
+                b.clone(),
+                // A soft hyphen becomes a hyphen only when the line breaks at it.
+                format!("{}-", a),
+                b.clone(),
+                "coop".to_string(),
+            ],
+            texts
+        );
+    }
+
+    #[test]
+    fn test_wbr() {
+        let a = "a".repeat(40);
+        let b = "b".repeat(60);
+        let html = format!(
+            "<html><body><p>{}<wbr>{}</p><p>{}{}</p></body></html>",
+            a, b, a, b
+        );
+        let layout_view = create_layout_view(html);
+
+        let texts: Vec<String> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect();
+        // The line breaks at <wbr>, while the same word without it is cut at the window width.
+        assert_eq!(a, texts[0]);
+        assert_eq!(b, texts[1]);
+        assert!(texts[2].len() > a.len());
+        assert_eq!(format!("{}{}", a, b), texts[2..].concat());
+    }
+
+    #[test]
+    fn test_font_size_keywords() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.s { font-size: small; } .l { font-size: x-large; } .xl { font-size: xx-large; }</style></head><body><p class=\"s\">a</p><p class=\"l\">b</p><p class=\"xl\">c</p></body></html>"
+                .to_string(),
+        );
+
+        let font_sizes: Vec<(String, FontSize)> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, style, .. } => Some((text, style.font_size())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("a".to_string(), FontSize::Medium),
+                ("b".to_string(), FontSize::XLarge),
+                ("c".to_string(), FontSize::XXLarge),
+            ],
+            font_sizes
+        );
+    }
+
+    #[test]
+    fn test_font_element() {
+        let layout_view = create_layout_view(
+            "<html><body><p><font color=\"blue\" size=\"6\">x</font></p></body></html>".to_string(),
+        );
+
+        let style = layout_view
+            .paint()
+            .into_iter()
+            .find_map(|item| match item {
+                DisplayItem::Text { style, .. } => Some(style),
+                _ => None,
+            })
+            .expect("text should be painted");
+        assert_eq!(
+            Color::from_name("blue").expect("failed to parse a color"),
+            style.color()
+        );
+        assert_eq!(FontSize::XXLarge, style.font_size());
+    }
+
+    #[test]
+    fn test_font_size_percentage() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.p { font-size: 150%; } .q { font-size: 50%; }</style></head><body><p>a</p><p><span class=\"p\">b</span></p><p><span class=\"p\"><span class=\"p\">c</span></span></p><p class=\"p\"><span class=\"q\">d</span></p></body></html>"
+                .to_string(),
+        );
+
+        let font_sizes: Vec<(String, FontSize)> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, style, .. } => Some((text, style.font_size())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("a".to_string(), FontSize::Medium),
+                ("b".to_string(), FontSize::XLarge),
+                ("c".to_string(), FontSize::XXLarge),
+                ("d".to_string(), FontSize::Medium),
+            ],
+            font_sizes
+        );
+    }
+
+    #[test]
+    fn test_pre_line() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.chat { white-space: pre-line; }</style></head><body><div class=\"chat\">a\n b  c</div></body></html>"
+                .to_string(),
+        );
+
+        let texts: Vec<(String, i64)> = layout_view
+            .paint()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text {
+                    text, layout_point, ..
+                } => Some((text, layout_point.y())),
+                _ => None,
+            })
+            .collect();
+        // The newline breaks the line, and the spaces are collapsed.
+        assert_eq!(2, texts.len());
+        assert_eq!("a".to_string(), texts[0].0);
+        assert_eq!("b c".to_string(), texts[1].0);
+        assert_eq!(texts[0].1 + CHAR_HEIGHT_WITH_PADDING, texts[1].1);
+
+        let div = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("div should exist");
+        assert_eq!(CHAR_HEIGHT_WITH_PADDING * 2, div.borrow().size().height());
+    }
+
+    #[test]
+    fn test_pre_tab_expansion() {
+        let texts = |browser: &Rc<RefCell<Browser>>| {
+            let html = "<html><body><pre>\tx\nab\ty</pre></body></html>".to_string();
+            let t = HtmlTokenizer::new(Rc::downgrade(browser), html);
+            let window = HtmlParser::new(Rc::downgrade(browser), t).construct_tree();
+            let dom = window.borrow().document();
+            let layout_view = LayoutView::new(Rc::downgrade(browser), dom, &StyleSheet::new());
+            layout_view
+                .paint()
+                .into_iter()
+                .filter_map(|item| match item {
+                    DisplayItem::Text { text, .. } => Some(text),
+                    _ => None,
+                })
+                .collect::<Vec<String>>()
+        };
+
+        let browser = Browser::new();
+        assert_eq!(
+            vec!["    x".to_string(), "ab  y".to_string()],
+            texts(&browser)
+        );
+
+        browser.borrow_mut().set_tab_size(8);
+        assert_eq!(
+            vec!["        x".to_string(), "ab      y".to_string()],
+            texts(&browser)
+        );
+    }
+
+    #[test]
+    fn test_border_radius() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.a { border-radius: 8px; } .b { border-radius: 2px 4px; }</style></head><body><div class=\"a\">a</div><div class=\"b\">b</div><div>c</div></body></html>"
+                .to_string(),
+        );
+
+        let a = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("first div should exist");
+        assert_eq!(
+            BorderRadius::new(8.0, 8.0, 8.0, 8.0),
+            a.borrow().style().border_radius()
+        );
+
+        let b = a.borrow().next_sibling().expect("second div should exist");
+        assert_eq!(
+            BorderRadius::new(2.0, 4.0, 2.0, 4.0),
+            b.borrow().style().border_radius()
+        );
+
+        let c = b.borrow().next_sibling().expect("third div should exist");
+        assert_eq!(BorderRadius::default(), c.borrow().style().border_radius());
+    }
+
+    #[test]
+    fn test_flex_row() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.row { display: flex; }</style></head><body><div class=\"row\"><div>a</div><div>b</div><div>c</div></div><p>after</p></body></html>"
+                .to_string(),
+        );
+
+        let row = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("flex container should exist");
+        assert_eq!(CONTENT_AREA_WIDTH, row.borrow().size().width());
+
+        let items = row.borrow().children();
+        assert_eq!(3, items.len());
+        // The items are placed side by side and share the width of the container equally.
+        let mut x = 0;
+        for item in &items {
+            assert_eq!(LayoutObjectKind::Block, item.borrow().kind());
+            assert_eq!(x, item.borrow().point().x());
+            assert_eq!(row.borrow().point().y(), item.borrow().point().y());
+            assert_eq!(CONTENT_AREA_WIDTH / 3, item.borrow().size().width());
+            x += item.borrow().size().width();
+        }
+        assert!(x <= CONTENT_AREA_WIDTH);
+
+        // The container is as tall as one line.
+        assert_eq!(
+            items[0].borrow().size().height(),
+            row.borrow().size().height()
+        );
+        let after = row.borrow().next_sibling().expect("p should exist");
+        assert_eq!(
+            row.borrow().point().y() + row.borrow().size().height(),
+            after.borrow().point().y()
+        );
+    }
+
+    #[test]
+    fn test_flex_grow_and_wrap() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.row { display: flex; } .grow { flex-grow: 1; } .wrap { display: flex; flex-wrap: wrap; } .item { width: 250; }</style></head><body><div class=\"row\"><div>a</div><div class=\"grow\">b</div></div><div class=\"wrap\"><div class=\"item\">c</div><div class=\"item\">d</div><div class=\"item\">e</div></div></body></html>"
+                .to_string(),
+        );
+
+        let row = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("flex container should exist");
+        let items = row.borrow().children();
+        // Only the item with flex-grow takes the free space.
+        let a_width = items[0].borrow().size().width();
+        assert_eq!(
+            items[0].borrow().children()[0].borrow().size().width(),
+            a_width
+        );
+        assert_eq!(a_width, items[1].borrow().point().x());
+        assert_eq!(
+            CONTENT_AREA_WIDTH - a_width,
+            items[1].borrow().size().width()
+        );
+
+        let wrap = row
+            .borrow()
+            .next_sibling()
+            .expect("flex container should exist");
+        let items = wrap.borrow().children();
+        // The third item doesn't fit in the first line.
+        assert_eq!(items[0].borrow().point().y(), items[1].borrow().point().y());
+        assert_eq!(
+            items[0].borrow().size().width(),
+            items[1].borrow().point().x()
+        );
+        assert_eq!(0, items[2].borrow().point().x());
+        assert_eq!(
+            items[0].borrow().point().y() + items[0].borrow().size().height(),
+            items[2].borrow().point().y()
+        );
+        assert_eq!(
+            items[0].borrow().size().height() * 2,
+            wrap.borrow().size().height()
+        );
+    }
+
+    #[test]
+    fn test_flex_text_item() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.row { display: flex; justify-content: space-between; }</style></head><body><div class=\"row\">a<div>b</div></div></body></html>"
+                .to_string(),
+        );
+
+        let row = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("flex container should exist");
+        let items = row.borrow().children();
+        assert_eq!(2, items.len());
+        // The text is wrapped in an anonymous block box which is placed as a flex item.
+        assert_eq!(LayoutObjectKind::Block, items[0].borrow().kind());
+        let text = items[0].borrow().first_child().expect("text should exist");
+        assert_eq!(LayoutObjectKind::Text, text.borrow().kind());
+        assert_eq!(
+            NodeKind::Text("a".to_string()),
+            text.borrow().node().borrow().kind()
+        );
+        assert_eq!(
+            text.borrow().size().width(),
+            items[0].borrow().size().width()
+        );
+        assert_eq!(0, items[0].borrow().point().x());
+        assert_eq!(row.borrow().point(), text.borrow().point());
+        assert_eq!(
+            CONTENT_AREA_WIDTH,
+            items[1].borrow().point().x() + items[1].borrow().size().width()
+        );
+    }
+
+    #[test]
+    fn test_nested_flex() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.row { display: flex; justify-content: space-between; }</style></head><body><div class=\"row\"><div class=\"row\"><div>ab</div><div>cd</div></div><div>e</div></div></body></html>"
+                .to_string(),
+        );
+
+        let row = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("flex container should exist");
+        let items = row.borrow().children();
+        // A nested flex container is as wide as its items side by side.
+        let inner_items = items[0].borrow().children();
+        assert_eq!(
+            inner_items[0].borrow().size().width() + inner_items[1].borrow().size().width(),
+            items[0].borrow().size().width()
+        );
+        assert_eq!(
+            inner_items[0].borrow().size().width(),
+            inner_items[1].borrow().point().x()
+        );
+
+        // Deeply nested flex containers are laid out without measuring each level again.
+        let depth = 64;
+        let html = format!(
+            "<html><head><style>div {{ display: flex; }}</style></head><body>{}a{}</body></html>",
+            "<div>".repeat(depth),
+            "</div>".repeat(depth)
+        );
+        let layout_view = create_layout_view(html);
+        let mut node = layout_view.root().expect("root should exist");
+        for _ in 0..depth {
+            let child = node.borrow().first_child().expect("child should exist");
+            node = child;
+        }
+        assert_eq!(CONTENT_AREA_WIDTH, node.borrow().size().width());
+    }
+
+    #[test]
+    fn test_justify_content_space_between() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.row { display: flex; justify-content: space-between; }</style></head><body><div class=\"row\"><div>a</div><div>b</div><div>c</div></div></body></html>"
+                .to_string(),
+        );
+
+        let row = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("flex container should exist");
+        let items = row.borrow().children();
+        let width = items[0].borrow().size().width();
+        // The items keep their content widths.
+        assert_eq!(
+            items[0].borrow().children()[0].borrow().size().width(),
+            width
+        );
+        // The first and last items are pushed to the edges, and the rest is in between.
+        assert_eq!(0, items[0].borrow().point().x());
+        assert_eq!(
+            CONTENT_AREA_WIDTH,
+            items[2].borrow().point().x() + items[2].borrow().size().width()
+        );
+        let gap = (CONTENT_AREA_WIDTH - width * 3) / 2;
+        assert_eq!(width + gap, items[1].borrow().point().x());
+    }
+
+    #[test]
+    fn test_justify_content_and_align_items_center() {
+        let layout_view = create_layout_view(
+            "<html><head><style>.row { display: flex; justify-content: center; align-items: center; height: 100; }</style></head><body><div class=\"row\"><div>a</div><div>b</div></div></body></html>"
+                .to_string(),
+        );
+
+        let row = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("flex container should exist");
+        assert_eq!(100, row.borrow().size().height());
+
+        let items = row.borrow().children();
+        let size = items[0].borrow().size();
+        // The items are grouped in the middle of the container.
+        let left = (CONTENT_AREA_WIDTH - size.width() * 2) / 2;
+        assert_eq!(left, items[0].borrow().point().x());
+        assert_eq!(left + size.width(), items[1].borrow().point().x());
+        let top = row.borrow().point().y() + (100 - size.height()) / 2;
+        assert_eq!(top, items[0].borrow().point().y());
+        assert_eq!(top, items[1].borrow().point().y());
+    }
+
+    #[test]
+    fn test_definition_list() {
+        let layout_view = create_layout_view(
+            "<html><head></head><body><dl><dt>term</dt><dd>description</dd></dl></body></html>"
+                .to_string(),
+        );
+
+        let dl = layout_view
+            .root()
+            .expect("root should exist")
+            .borrow()
+            .first_child()
+            .expect("dl should exist");
+        assert_eq!(LayoutObjectKind::Block, dl.borrow().kind());
+
+        let dt = dl.borrow().first_child().expect("dt should exist");
+        let dd = dt.borrow().next_sibling().expect("dd should exist");
+        assert_eq!(LayoutObjectKind::Block, dt.borrow().kind());
+        assert_eq!(LayoutObjectKind::Block, dd.borrow().kind());
+
+        // The description is indented by its left margin and starts below the term.
+        assert_eq!(0, dt.borrow().point().x());
+        assert_eq!(40, dd.borrow().point().x());
+        assert_eq!(
+            dt.borrow().point().y() + dt.borrow().size().height(),
+            dd.borrow().point().y()
+        );
+        assert_eq!(CONTENT_AREA_WIDTH - 40, dd.borrow().size().width());
+    }
 }
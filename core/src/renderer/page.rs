@@ -7,13 +7,23 @@
 
 use crate::alloc::string::ToString;
 use crate::browser::Browser;
+use crate::constants::CONTENT_AREA_HEIGHT;
+use crate::constants::DEFAULT_MAX_BODY_BYTES;
+use crate::display_item::is_button_input;
+use crate::display_item::is_checkable_input;
 use crate::display_item::DisplayItem;
+use crate::error::Error;
 use crate::http::HttpResponse;
 use crate::renderer::css::cssom::CssParser;
 use crate::renderer::css::cssom::StyleSheet;
 use crate::renderer::css::token::CssTokenizer;
-use crate::renderer::dom::api::{get_js_content, get_style_content};
+use crate::renderer::dom::api::{
+    get_element_by_id, get_js_content, get_style_contents, get_viewport_content,
+    non_inert_first_child,
+};
+use crate::renderer::dom::node::Element;
 use crate::renderer::dom::node::ElementKind;
+use crate::renderer::dom::node::Node;
 use crate::renderer::dom::node::NodeKind;
 use crate::renderer::dom::window::Window;
 use crate::renderer::html::html_builder::dom_to_html;
@@ -21,17 +31,32 @@ use crate::renderer::html::parser::HtmlParser;
 use crate::renderer::html::token::HtmlTokenizer;
 use crate::renderer::js::ast::JsParser;
 use crate::renderer::js::runtime::JsRuntime;
+use crate::renderer::js::runtime::RuntimeValue;
 use crate::renderer::js::token::JsLexer;
+use crate::renderer::layout::computed_style::Cursor;
+use crate::renderer::layout::layout_object::LayoutObject;
+use crate::renderer::layout::layout_object::LayoutObjectKind;
 use crate::renderer::layout::layout_view::LayoutView;
+use crate::url::Url;
 use crate::utils::console_debug;
+use crate::utils::console_warning;
 use crate::utils::convert_dom_to_string;
 use crate::utils::convert_layout_tree_to_string;
 use alloc::format;
 use alloc::rc::{Rc, Weak};
 use alloc::string::String;
+use alloc::vec;
 use alloc::vec::Vec;
 use core::cell::RefCell;
 
+/// The page shown instead of a response whose body is not text.
+const BINARY_CONTENT_HTML: &str =
+    "<html><body><p>This content is binary and can't be displayed.</p></body></html>";
+
+/// The page shown instead of a response whose body exceeds `Browser::max_body_bytes`.
+const TOO_LARGE_CONTENT_HTML: &str =
+    "<html><body><p>This content is too large to be displayed.</p></body></html>";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Subresource {
     src: String,
@@ -47,6 +72,172 @@ impl Subresource {
     }
 }
 
+/// What pressing Enter in a focused form field does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnterAction {
+    /// Moves the focus to the field at this index.
+    FocusField(usize),
+    /// Submits the form.
+    Submit,
+}
+
+/// A movement of the caret in a focused text input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaretMove {
+    /// Moves the caret one character to the left.
+    Left,
+    /// Moves the caret one character to the right.
+    Right,
+    /// Moves the caret to the start of the value.
+    Home,
+    /// Moves the caret to the end of the value.
+    End,
+}
+
+/// What a click on a page did. A UI navigates or re-renders the page depending on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClickAction {
+    /// Navigates to the URL, e.g. by a link or a submit button.
+    Navigate(String),
+    /// Navigates to the URL in a new page, e.g. by a submit button of a form whose target is
+    /// `_blank`. The current page is kept as is.
+    NavigateInNewPage(String),
+    /// Focused an input, directly or through its label.
+    FocusInput,
+    /// Ran click handlers or reset a form without navigating. The page may have changed.
+    RanHandler,
+    /// Nothing happened.
+    None,
+}
+
+/// Where a navigation opens. It's given by the `target` attribute.
+/// https://html.spec.whatwg.org/multipage/document-sequences.html#valid-navigable-target-name-or-keyword
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationTarget {
+    /// Replaces the current page, e.g. without a target or with `_self`.
+    CurrentPage,
+    /// Opens a new page for `_blank`.
+    NewPage,
+}
+
+impl NavigationTarget {
+    /// Returns the target for a `target` attribute value. Named targets are not supported yet,
+    /// so they navigate the current page.
+    pub fn from_attribute(target: Option<&str>) -> Self {
+        match target {
+            Some(target) if target.eq_ignore_ascii_case("_blank") => NavigationTarget::NewPage,
+            _ => NavigationTarget::CurrentPage,
+        }
+    }
+}
+
+/// Decides whether Enter in the field at `focus_index` moves the focus to the next field or
+/// submits the form because it's the last one of `field_count` fields.
+pub fn next_field_or_submit(focus_index: usize, field_count: usize) -> EnterAction {
+    if focus_index + 1 < field_count {
+        EnterAction::FocusField(focus_index + 1)
+    } else {
+        EnterAction::Submit
+    }
+}
+
+/// A hyperlink created by an `<a>` element.
+/// https://html.spec.whatwg.org/multipage/links.html#hyperlink
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hyperlink {
+    href: String,
+    rel: Vec<String>,
+}
+
+impl Hyperlink {
+    pub fn href(&self) -> String {
+        self.href.clone()
+    }
+
+    pub fn rel(&self) -> Vec<String> {
+        self.rel.clone()
+    }
+
+    /// Returns true if the link has the link type, e.g. "noopener".
+    /// https://html.spec.whatwg.org/multipage/links.html#linkTypes
+    pub fn has_rel(&self, link_type: &str) -> bool {
+        self.rel.iter().any(|t| t == link_type)
+    }
+}
+
+/// The width of the viewport requested by a viewport meta tag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewportWidth {
+    /// The width of the device's screen.
+    DeviceWidth,
+    /// A width in CSS pixels.
+    Px(f64),
+}
+
+/// The viewport settings given by `<meta name="viewport">`. A directive which is absent or
+/// invalid is None.
+/// https://drafts.csswg.org/css-viewport/#viewport-meta
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Viewport {
+    width: Option<ViewportWidth>,
+    initial_scale: Option<f64>,
+}
+
+impl Viewport {
+    /// Parses the `content` of a viewport meta tag, e.g. "width=device-width, initial-scale=1".
+    /// Directives are separated by commas or semicolons, and unknown directives are skipped.
+    /// https://drafts.csswg.org/css-viewport/#parsing-algorithm
+    pub fn parse(content: &str) -> Self {
+        let mut viewport = Self::default();
+        for directive in content.split([',', ';']) {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => continue,
+            };
+            let value = value.to_ascii_lowercase();
+            match name.to_ascii_lowercase().as_str() {
+                "width" => {
+                    viewport.width = if value == "device-width" {
+                        Some(ViewportWidth::DeviceWidth)
+                    } else {
+                        value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|width| *width > 0.0)
+                            .map(|width| ViewportWidth::Px(width.clamp(1.0, 10000.0)))
+                    };
+                }
+                "initial-scale" => {
+                    viewport.initial_scale = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|scale| *scale > 0.0)
+                        .map(|scale| scale.clamp(0.1, 10.0));
+                }
+                _ => {}
+            }
+        }
+        viewport
+    }
+
+    pub fn width(&self) -> Option<ViewportWidth> {
+        self.width
+    }
+
+    pub fn initial_scale(&self) -> Option<f64> {
+        self.initial_scale
+    }
+}
+
+/// A major phase of rendering a page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderingPhase {
+    Parse,
+    Style,
+    Layout,
+    Paint,
+}
+
 /// Represents a page.
 #[derive(Debug, Clone)]
 pub struct Page {
@@ -57,9 +248,23 @@ pub struct Page {
     layout_view: Option<LayoutView>,
     subresources: Vec<Subresource>,
     display_items: Vec<DisplayItem>,
-    modified: bool,
+    /// True if the last paint produced different display items from the previous paint.
+    display_items_changed: bool,
+    /// The number of responses this page has received.
+    generation: u64,
+    /// True if a script inserted markup, which needs the DOM tree to be parsed again.
+    markup_inserted: bool,
+    /// The elapsed time of each rendering phase since the last response was received, in the
+    /// order the phases ran. A relayout appends its phases.
+    phase_timings: Vec<(RenderingPhase, u64)>,
+    /// The JS runtime which ran the scripts of this page. It runs event handlers too.
+    js_runtime: Option<JsRuntime>,
     /// Currently focused input element (for text input)
     focused_input: Option<Rc<RefCell<crate::renderer::dom::node::Node>>>,
+    /// The viewport settings of the page's viewport meta tag.
+    viewport: Viewport,
+    /// True if editing the focused input repaints its display item right away.
+    rerender_on_input: bool,
 }
 
 impl Page {
@@ -71,16 +276,34 @@ impl Page {
             layout_view: None,
             subresources: Vec::new(),
             display_items: Vec::new(),
-            modified: false,
+            display_items_changed: false,
+            generation: 0,
+            markup_inserted: false,
+            phase_timings: Vec::new(),
+            js_runtime: None,
             focused_input: None,
+            viewport: Viewport::default(),
+            rerender_on_input: true,
         }
     }
 
-    /// Called when this page is clicked.
-    pub fn clicked(&mut self, position: (i64, i64)) -> Option<String> {
+    /// Called when this page is clicked. Returns what the click did.
+    pub fn clicked(&mut self, position: (i64, i64)) -> ClickAction {
+        self.click(position).0
+    }
+
+    /// Handles a click at `position` like `clicked` and returns the clicked hyperlink with its
+    /// link types.
+    pub fn clicked_link(&mut self, position: (i64, i64)) -> Option<Hyperlink> {
+        self.click(position).1
+    }
+
+    /// Handles a click at `position`. The hyperlink to follow is returned with the action when
+    /// the click navigates.
+    fn click(&mut self, position: (i64, i64)) -> (ClickAction, Option<Hyperlink>) {
         let view = match &self.layout_view {
             Some(v) => v,
-            None => return None,
+            None => return (ClickAction::None, None),
         };
 
         if let Some(n) = view.find_node_by_position(position) {
@@ -92,51 +315,338 @@ impl Page {
             // Check if clicked node is an input element
             if let NodeKind::Element(e) = n.borrow().node().borrow().kind() {
                 if e.kind() == ElementKind::Input {
+                    let input_type = e.get_attribute("type").unwrap_or_default();
+                    if is_button_input(&input_type) {
+                        return self.activate_button(&n.borrow().node(), &input_type);
+                    }
+                    if is_checkable_input(&input_type) {
+                        return self.activate_checkable(&n.borrow().node(), &input_type);
+                    }
+
                     // Set focus to this input element
-                    self.focused_input = Some(n.borrow().node());
+                    self.set_focused_input(Some(n.borrow().node()));
                     console_debug(&self.browser, "Input element focused".to_string());
-                    return None;
+                    return (ClickAction::FocusInput, None);
+                }
+            }
+
+            // Clicking a label focuses its labeled control.
+            if let Some(label) = enclosing_label(&n.borrow().node()) {
+                if let Some(control) = self.labeled_control(&label) {
+                    let control_type = control
+                        .borrow()
+                        .get_element()
+                        .and_then(|e| e.get_attribute("type"))
+                        .unwrap_or_default();
+                    if is_checkable_input(&control_type) {
+                        return self.activate_checkable(&control, &control_type);
+                    }
+                    self.set_focused_input(Some(control));
+                    console_debug(&self.browser, "Input element focused by label".to_string());
+                    return (ClickAction::FocusInput, None);
                 }
             }
 
             // Clear focus if clicked elsewhere
-            self.focused_input = None;
+            self.set_focused_input(None);
 
-            if let Some(parent) = n.borrow().parent().upgrade() {
-                if let NodeKind::Element(e) = parent.borrow().node().borrow().kind() {
-                    if e.kind() == ElementKind::A {
-                        return e.get_attribute("href");
-                    }
+            // The clicked node may be nested in inline content inside an anchor.
+            let mut link = None;
+            if let Some(anchor) = Node::closest(&n.borrow().node(), "a") {
+                if let NodeKind::Element(e) = anchor.borrow().kind() {
+                    link = e.get_attribute("href").map(|href| Hyperlink {
+                        href,
+                        rel: e.rel_tokens(),
+                    });
+                }
+            }
+
+            // The event target is the clicked element. A click on a text targets its parent.
+            let mut target = Some(n.borrow().node());
+            while let Some(t) = target.clone() {
+                if t.borrow().element_kind().is_some() {
+                    break;
                 }
+                target = t.borrow().parent().upgrade();
+            }
+            // "If event's canceled flag is unset, then run activationTarget's activation
+            // behavior with event."
+            // https://dom.spec.whatwg.org/#concept-event-dispatch
+            let (handled, canceled) = match target {
+                Some(target) => self.dispatch_click(&target),
+                None => (false, false),
+            };
+            if canceled {
+                console_debug(&self.browser, "click default action prevented".to_string());
+                return (ClickAction::RanHandler, None);
             }
+            return match link {
+                Some(link) => (ClickAction::Navigate(link.href()), Some(link)),
+                None if handled => (ClickAction::RanHandler, None),
+                None => (ClickAction::None, None),
+            };
         }
 
         console_debug(&self.browser, "clicked but node not found".to_string());
+        (ClickAction::None, None)
+    }
+
+    /// Runs the activation behavior of a button input. A submit button submits its form and
+    /// returns the URL to navigate to as a hyperlink, and a reset button resets its form. Click
+    /// handlers run first and can cancel it.
+    /// https://html.spec.whatwg.org/multipage/input.html#submit-button-state-(type=submit)
+    /// https://html.spec.whatwg.org/multipage/input.html#reset-button-state-(type=reset)
+    fn activate_button(
+        &mut self,
+        button: &Rc<RefCell<Node>>,
+        input_type: &str,
+    ) -> (ClickAction, Option<Hyperlink>) {
+        self.set_focused_input(None);
+        let (handled, canceled) = self.dispatch_click(button);
+        let no_activation = if handled {
+            ClickAction::RanHandler
+        } else {
+            ClickAction::None
+        };
+        if canceled {
+            console_debug(&self.browser, "click default action prevented".to_string());
+            return (no_activation, None);
+        }
+
+        let form = match enclosing_form(button) {
+            Some(form) => form,
+            None => return (no_activation, None),
+        };
+        match input_type {
+            "submit" => {
+                let href = self.submit_url(&form);
+                let link = Hyperlink {
+                    href: href.clone(),
+                    rel: Vec::new(),
+                };
+                let action = match self.submit_target(&form) {
+                    NavigationTarget::CurrentPage => ClickAction::Navigate(href),
+                    NavigationTarget::NewPage => ClickAction::NavigateInNewPage(href),
+                };
+                (action, Some(link))
+            }
+            "reset" => {
+                self.reset_form(&form);
+                (ClickAction::RanHandler, None)
+            }
+            _ => (no_activation, None),
+        }
+    }
+
+    /// Checks or unchecks a checkbox, or checks a radio button and unchecks the others in its
+    /// group. The checkedness is changed before click handlers run and is restored if a handler
+    /// cancels the click.
+    /// https://html.spec.whatwg.org/multipage/input.html#the-input-element:legacy-pre-activation-behavior
+    fn activate_checkable(
+        &mut self,
+        input: &Rc<RefCell<Node>>,
+        input_type: &str,
+    ) -> (ClickAction, Option<Hyperlink>) {
+        self.set_focused_input(None);
+        let group = if input_type == "radio" {
+            self.radio_group(input)
+        } else {
+            vec![input.clone()]
+        };
+        let elements: Vec<Element> = group
+            .iter()
+            .filter_map(|n| n.borrow().get_element())
+            .collect();
+        let previous: Vec<bool> = elements.iter().map(|e| e.is_checked()).collect();
+        for (n, e) in group.iter().zip(elements.iter()) {
+            if !Rc::ptr_eq(n, input) {
+                e.set_checked(false);
+            } else if input_type == "radio" {
+                e.set_checked(true);
+            } else {
+                e.set_checked(!e.is_checked());
+            }
+        }
+
+        let (_, canceled) = self.dispatch_click(input);
+        if canceled {
+            console_debug(&self.browser, "click default action prevented".to_string());
+            for (e, checked) in elements.iter().zip(previous) {
+                e.set_checked(checked);
+            }
+        }
+        self.refresh_display();
+        (ClickAction::RanHandler, None)
+    }
+
+    /// Returns the radio buttons in the group of `radio`: the ones with the same name in the same
+    /// form. A radio button without a name is in a group by itself.
+    /// https://html.spec.whatwg.org/multipage/input.html#radio-button-group
+    fn radio_group(&self, radio: &Rc<RefCell<Node>>) -> Vec<Rc<RefCell<Node>>> {
+        let name = match radio
+            .borrow()
+            .get_element()
+            .and_then(|e| e.get_attribute("name"))
+        {
+            Some(name) if !name.is_empty() => name,
+            _ => return vec![radio.clone()],
+        };
+        let form = enclosing_form(radio);
+        let root = match (&form, &self.frame) {
+            (Some(form), _) => form.borrow().first_child(),
+            (None, Some(frame)) => Some(frame.borrow().document()),
+            (None, None) => return vec![radio.clone()],
+        };
+
+        let mut inputs = Vec::new();
+        collect_inputs(&root, &mut inputs);
+        inputs
+            .into_iter()
+            .filter(|n| {
+                let same_form = match (enclosing_form(n), &form) {
+                    (Some(a), Some(b)) => Rc::ptr_eq(&a, b),
+                    (None, None) => true,
+                    _ => false,
+                };
+                let e = match n.borrow().get_element() {
+                    Some(e) => e,
+                    None => return false,
+                };
+                same_form
+                    && e.get_attribute("type").as_deref() == Some("radio")
+                    && e.get_attribute("name").as_deref() == Some(name.as_str())
+            })
+            .collect()
+    }
+
+    /// Returns the advisory information (the `title` attribute) of the node at `position` or its
+    /// nearest ancestor, e.g. the expansion of an <abbr> element. A UI can show it in a status
+    /// display.
+    /// https://html.spec.whatwg.org/multipage/dom.html#the-title-attribute
+    pub fn title_at(&self, position: (i64, i64)) -> Option<String> {
+        let view = self.layout_view.as_ref()?;
+        let mut current = view
+            .find_node_by_position(position)
+            .map(|n| n.borrow().node());
+        while let Some(n) = current {
+            if let NodeKind::Element(e) = n.borrow().kind() {
+                if let Some(title) = e.get_attribute("title") {
+                    return Some(title);
+                }
+            }
+            current = n.borrow().parent().upgrade();
+        }
         None
     }
 
-    /// Handle keyboard input for focused input element
+    /// Returns the cursor to show over `position`. The `auto` cursor is a pointer over a link and
+    /// the default arrow elsewhere.
+    /// https://drafts.csswg.org/css-ui/#valdef-cursor-auto
+    pub fn cursor_at(&self, position: (i64, i64)) -> Cursor {
+        let object = match self
+            .layout_view
+            .as_ref()
+            .and_then(|view| view.find_node_by_position(position))
+        {
+            Some(object) => object,
+            None => return Cursor::Default,
+        };
+        let cursor = object.borrow().style().cursor();
+        if cursor != Cursor::Auto {
+            return cursor;
+        }
+
+        let mut current = Some(object.borrow().node());
+        while let Some(n) = current {
+            if let NodeKind::Element(e) = n.borrow().kind() {
+                if e.kind() == ElementKind::A && e.get_attribute("href").is_some() {
+                    return Cursor::Pointer;
+                }
+            }
+            current = n.borrow().parent().upgrade();
+        }
+        Cursor::Default
+    }
+
+    /// Returns the input element associated with `label`. If the label has a `for` attribute,
+    /// it's the input with the ID. Otherwise, it's the first input inside the label.
+    /// https://html.spec.whatwg.org/multipage/forms.html#labeled-control
+    fn labeled_control(&self, label: &Rc<RefCell<Node>>) -> Option<Rc<RefCell<Node>>> {
+        let for_id = match label.borrow().kind() {
+            NodeKind::Element(e) => e.get_attribute("for"),
+            _ => return None,
+        };
+
+        match for_id {
+            Some(id) => {
+                let dom = self.frame.as_ref()?.borrow().document();
+                get_element_by_id(Some(dom), &id)
+                    .filter(|n| n.borrow().element_kind() == Some(ElementKind::Input))
+            }
+            None => {
+                let mut inputs = Vec::new();
+                collect_inputs(&label.borrow().first_child(), &mut inputs);
+                inputs.into_iter().next()
+            }
+        }
+    }
+
+    /// Sets whether `handle_input` and `move_caret` repaint the display item of the focused
+    /// input. When it's on, the UI only needs to redraw if `display_items_changed` is true. When
+    /// it's off, the UI needs to call `refresh_display` to show the change.
+    pub fn set_rerender_on_input(&mut self, rerender_on_input: bool) {
+        self.rerender_on_input = rerender_on_input;
+    }
+
+    /// Handles keyboard input for the focused input element. Returns true if the key is handled.
     pub fn handle_input(&mut self, key: char) -> bool {
+        let handled = self.edit_focused_input(key);
+        if handled && self.rerender_on_input {
+            self.repaint_focused_input();
+        }
+        handled
+    }
+
+    fn edit_focused_input(&mut self, key: char) -> bool {
         if let Some(focused_node) = &self.focused_input {
             console_debug(&self.browser, format!("handle_input called with key: {:?} (0x{:02X})", key, key as u32));
 
             if let NodeKind::Element(e) = focused_node.borrow().kind() {
                 if e.kind() == ElementKind::Input {
+                    // A button is not typed into.
+                    if is_button_input(&e.get_attribute("type").unwrap_or_default()) {
+                        return false;
+                    }
+
                     let current_value = e.get_value().unwrap_or_default();
                     console_debug(&self.browser, format!("Current value before update: {:?}", current_value));
 
+                    // Characters are inserted and deleted at the caret.
+                    let mut chars: Vec<char> = current_value.chars().collect();
+                    let caret = e.caret().unwrap_or(chars.len());
+
                     // Handle backspace/delete
                     if key == 0x7F as char || key == 0x08 as char {
-                        let mut chars: Vec<char> = current_value.chars().collect();
-                        if !chars.is_empty() {
-                            chars.pop();
+                        if caret > 0 {
+                            chars.remove(caret - 1);
                             e.set_value(chars.iter().collect());
+                            e.set_caret(Some(caret - 1));
                         }
+                    } else if e.get_attribute("type").as_deref() == Some("number")
+                        && !(key.is_ascii_digit() || key == '-' || key == '.')
+                    {
+                        // A number input rejects characters which can't be a part of a number.
+                        // https://html.spec.whatwg.org/multipage/input.html#number-state-(type=number)
+                        console_debug(
+                            &self.browser,
+                            format!("rejected {:?} for a number input", key),
+                        );
+                        return false;
                     } else if key.is_ascii_graphic() || key == ' ' {
-                        // Append printable characters
-                        let mut new_value = current_value;
-                        new_value.push(key);
-                        e.set_value(new_value);
+                        // Insert printable characters
+                        chars.insert(caret, key);
+                        e.set_value(chars.iter().collect());
+                        e.set_caret(Some(caret + 1));
                     }
 
                     console_debug(&self.browser, format!("Input value after update: {:?}", e.get_value()));
@@ -147,31 +657,325 @@ impl Page {
         false
     }
 
+    /// Moves the caret in the focused input. Returns true if the caret moved.
+    pub fn move_caret(&mut self, movement: CaretMove) -> bool {
+        let focused = match &self.focused_input {
+            Some(focused) => focused.clone(),
+            None => return false,
+        };
+        let e = match focused.borrow().get_element() {
+            Some(e) => e,
+            None => return false,
+        };
+
+        let len = e.get_value().unwrap_or_default().chars().count();
+        let caret = e.caret().unwrap_or(len);
+        let new_caret = match movement {
+            CaretMove::Left => caret.saturating_sub(1),
+            CaretMove::Right => (caret + 1).min(len),
+            CaretMove::Home => 0,
+            CaretMove::End => len,
+        };
+        e.set_caret(Some(new_caret));
+
+        let moved = new_caret != caret;
+        if moved && self.rerender_on_input {
+            self.repaint_focused_input();
+        }
+        moved
+    }
+
+    /// Rebuilds the display item of the focused input from its current value and caret without
+    /// a relayout, so the input keeps its position and size.
+    fn repaint_focused_input(&mut self) {
+        let layout_object = match (&self.focused_input, &self.layout_view) {
+            (Some(focused), Some(layout_view)) => layout_view.find_layout_object_by_node(focused),
+            _ => None,
+        };
+        let layout_object = match layout_object {
+            Some(layout_object) => layout_object,
+            None => return,
+        };
+
+        let point = layout_object.borrow().point();
+        let new_item = match layout_object.borrow_mut().paint().into_iter().next() {
+            Some(item) => item,
+            None => return,
+        };
+        let item = self.display_items.iter_mut().find(|item| {
+            matches!(item, DisplayItem::Input { layout_point, .. } if *layout_point == point)
+        });
+        if let Some(item) = item {
+            self.display_items_changed = *item != new_item;
+            *item = new_item;
+        }
+    }
+
+    /// Moves the focus to `input`, or removes the focus if it's None. The caret of a newly
+    /// focused input is placed at the end of its value.
+    fn set_focused_input(&mut self, input: Option<Rc<RefCell<Node>>>) {
+        let element = |node: &Option<Rc<RefCell<Node>>>| node.as_ref()?.borrow().get_element();
+        if let Some(e) = element(&self.focused_input) {
+            e.set_caret(None);
+        }
+        if let Some(e) = element(&input) {
+            e.set_caret(Some(e.get_value().unwrap_or_default().chars().count()));
+        }
+        self.focused_input = input;
+    }
+
+    /// Returns the first input element with the `autofocus` attribute in tree order.
+    fn autofocus_input(&self) -> Option<Rc<RefCell<Node>>> {
+        let document = self.frame.as_ref()?.borrow().document();
+        let mut inputs = Vec::new();
+        collect_inputs(&document.borrow().first_child(), &mut inputs);
+        inputs
+            .into_iter()
+            .find(|input| match input.borrow().kind() {
+                NodeKind::Element(e) => e.get_attribute("autofocus").is_some(),
+                _ => false,
+            })
+    }
+
+    /// Moves the focus to the next input which is typed into, in tree order, like the Tab key.
+    /// The first one gets the focus when no input has focus, and the focus is removed after the
+    /// last one. Returns true if an input has focus now. The UI needs to call `refresh_display`
+    /// to show the moved caret.
+    /// https://html.spec.whatwg.org/multipage/interaction.html#sequential-focus-navigation
+    pub fn focus_next_input(&mut self) -> bool {
+        let document = match &self.frame {
+            Some(frame) => frame.borrow().document(),
+            None => return false,
+        };
+        let mut inputs = Vec::new();
+        collect_inputs(&document.borrow().first_child(), &mut inputs);
+        inputs.retain(|input| {
+            let input_type = input
+                .borrow()
+                .get_element()
+                .and_then(|e| e.get_attribute("type"))
+                .unwrap_or_default();
+            !is_button_input(&input_type) && !is_checkable_input(&input_type)
+        });
+
+        let next = match &self.focused_input {
+            Some(focused) => inputs
+                .iter()
+                .position(|n| Rc::ptr_eq(n, focused))
+                .and_then(|i| inputs.get(i + 1).cloned()),
+            None => inputs.first().cloned(),
+        };
+        let focused = next.is_some();
+        self.set_focused_input(next);
+        focused
+    }
+
+    /// Removes the focus from the focused input, e.g. when Esc is pressed.
+    pub fn blur_input(&mut self) {
+        self.set_focused_input(None);
+    }
+
     /// Returns true if an input element has focus
     pub fn has_focused_input(&self) -> bool {
         self.focused_input.is_some()
     }
 
+    /// Handles the Enter key in the focused input. The focus moves to the next input in the
+    /// same form, or the form is submitted when the focused input is the last one. Returns the
+    /// URL to navigate to and where it opens if the form is submitted.
+    pub fn handle_enter(&mut self) -> Option<(String, NavigationTarget)> {
+        let focused = self.focused_input.clone()?;
+        let form = enclosing_form(&focused);
+        let scope = match &form {
+            Some(form) => form.clone(),
+            None => self.frame.as_ref()?.borrow().document(),
+        };
+
+        let mut inputs = Vec::new();
+        collect_inputs(&scope.borrow().first_child(), &mut inputs);
+        let index = inputs.iter().position(|n| Rc::ptr_eq(n, &focused))?;
+
+        match next_field_or_submit(index, inputs.len()) {
+            EnterAction::FocusField(i) => {
+                self.set_focused_input(Some(inputs[i].clone()));
+                None
+            }
+            EnterAction::Submit => {
+                form.map(|form| (self.submit_url(&form), self.submit_target(&form)))
+            }
+        }
+    }
+
+    /// Restores the value and the checkedness of each input in `form` to the state parsed from
+    /// the document, and repaints the page.
+    /// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#concept-form-reset
+    pub fn reset_form(&mut self, form: &Rc<RefCell<Node>>) {
+        let mut inputs = Vec::new();
+        collect_inputs(&form.borrow().first_child(), &mut inputs);
+        for input in inputs {
+            if let NodeKind::Element(e) = input.borrow().kind() {
+                e.reset_value();
+            }
+        }
+        self.refresh_display();
+    }
+
+    /// Returns where the result of submitting `form` opens.
+    /// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#attr-fs-target
+    pub fn submit_target(&self, form: &Rc<RefCell<Node>>) -> NavigationTarget {
+        let target = form
+            .borrow()
+            .get_element()
+            .and_then(|e| e.get_attribute("target"));
+        NavigationTarget::from_attribute(target.as_deref())
+    }
+
+    /// Returns the URL to navigate to when `form` is submitted with the GET method.
+    /// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#submit-mutate-action
+    pub fn submit_url(&self, form: &Rc<RefCell<Node>>) -> String {
+        let action = match form.borrow().kind() {
+            NodeKind::Element(e) => e.get_attribute("action").unwrap_or_default(),
+            _ => String::new(),
+        };
+
+        let mut inputs = Vec::new();
+        collect_inputs(&form.borrow().first_child(), &mut inputs);
+        let mut pairs = Vec::new();
+        for input in inputs {
+            if let NodeKind::Element(e) = input.borrow().kind() {
+                // Buttons are not a part of the form data.
+                let input_type = e.get_attribute("type").unwrap_or_default();
+                if is_button_input(&input_type) {
+                    continue;
+                }
+                // An unchecked checkbox or radio button is not a part of the form data either, and
+                // a checked one without a value submits "on".
+                // https://html.spec.whatwg.org/multipage/input.html#dom-input-value-default-on
+                let value = if is_checkable_input(&input_type) {
+                    if !e.is_checked() {
+                        continue;
+                    }
+                    e.get_value().unwrap_or_else(|| String::from("on"))
+                } else {
+                    e.get_value().unwrap_or_default()
+                };
+                if let Some(name) = e.get_attribute("name") {
+                    pairs.push(format!(
+                        "{}={}",
+                        form_urlencode(&name),
+                        form_urlencode(&value)
+                    ));
+                }
+            }
+        }
+
+        // The fragment of the action is dropped, and the form data is appended to the query of
+        // the action if it already has one.
+        let action = match action.find('#') {
+            Some(i) => &action[..i],
+            None => &action,
+        };
+        let query = pairs.join("&");
+        if !action.contains('?') {
+            format!("{}?{}", action, query)
+        } else if query.is_empty() || action.ends_with('?') || action.ends_with('&') {
+            format!("{}{}", action, query)
+        } else {
+            format!("{}&{}", action, query)
+        }
+    }
+
     /// Refresh the display items by rebuilding layout and repainting
     pub fn refresh_display(&mut self) {
         self.set_layout_view();
         self.paint_tree();
     }
 
+    /// Re-runs style, layout and paint on the existing DOM tree. Unlike re-creating a frame,
+    /// this doesn't serialize and re-parse the DOM, so the state held by DOM nodes (e.g. the
+    /// focused input) survives. Use it when only styles or text content were changed.
+    pub fn relayout(&mut self) {
+        let start = self.now();
+        self.update_style();
+        self.record_phase(RenderingPhase::Style, start);
+
+        console_debug(&self.browser, "Setting layout view...".to_string());
+        let start = self.now();
+        self.set_layout_view();
+        self.record_phase(RenderingPhase::Layout, start);
+
+        console_debug(&self.browser, "Painting tree...".to_string());
+        let start = self.now();
+        self.paint_tree();
+        self.record_phase(RenderingPhase::Paint, start);
+    }
+
+    /// Runs `js` against the DOM tree of this page with the runtime which ran the page's
+    /// scripts, and re-renders the page if the DOM tree was changed. Returns the value of the
+    /// last statement, or None if it's undefined. Input which can't be lexed or parsed is an
+    /// error.
+    pub fn execute_script(&mut self, js: &str) -> Result<Option<RuntimeValue>, Error> {
+        let runtime = match &mut self.js_runtime {
+            Some(runtime) => runtime,
+            None => return Err(Error::Other("no document is loaded".to_string())),
+        };
+
+        // The runtime doesn't track every change, e.g. `textContent`, so compare the serialized
+        // DOM trees instead.
+        let dom = runtime.dom_root();
+        let before = dom_to_html(&Some(dom.clone()));
+
+        // Check the input before parsing, because the lexer ends the input at an unsupported
+        // character instead of reporting it to the parser.
+        JsLexer::new(js.to_string()).tokenize()?;
+        let mut parser = JsParser::new(JsLexer::new(js.to_string()));
+        let program = parser.parse_program()?;
+        let result = runtime.evaluate_in_console(&program);
+
+        if dom_to_html(&Some(dom)) != before {
+            self.relayout();
+        }
+        Ok(result)
+    }
+
     /// Called when HTTP response is received.
     pub fn receive_response(&mut self, response: HttpResponse) {
         console_debug(&self.browser, "receive_response start".to_string());
         console_debug(&self.browser, format!("Response body length: {}", response.body().len()));
+        self.phase_timings.clear();
+        self.generation += 1;
+
+        let html = if response.is_binary() {
+            console_warning(
+                &self.browser,
+                "The response body is binary and can't be displayed".to_string(),
+            );
+            BINARY_CONTENT_HTML.to_string()
+        } else if response.body().len() > self.max_body_bytes() {
+            console_warning(
+                &self.browser,
+                format!(
+                    "The response body exceeds the limit of {} bytes",
+                    self.max_body_bytes()
+                ),
+            );
+            TOO_LARGE_CONTENT_HTML.to_string()
+        } else {
+            response.body()
+        };
 
         console_debug(&self.browser, "Creating frame from HTML...".to_string());
-        self.create_frame(response.body());
+        self.create_frame(html);
         console_debug(&self.browser, "Frame created successfully".to_string());
 
         console_debug(&self.browser, "Executing JavaScript...".to_string());
         self.execute_js();
         console_debug(&self.browser, "JavaScript execution complete".to_string());
 
-        while self.modified {
+        // Markup inserted by `innerHTML` becomes nodes only by parsing the serialized DOM tree
+        // again. Other changes by scripts are made to the DOM tree in place and rendered by the
+        // relayout below.
+        while self.markup_inserted {
             let dom = match &self.frame {
                 Some(frame) => frame.borrow().document(),
                 None => panic!("frame should exist"),
@@ -181,17 +985,24 @@ impl Page {
 
             self.create_frame(modified_html);
 
-            self.modified = false;
+            self.markup_inserted = false;
 
             self.execute_js();
         }
 
-        console_debug(&self.browser, "Setting layout view...".to_string());
-        self.set_layout_view();
-        console_debug(&self.browser, "Layout view set successfully".to_string());
+        let dom = self.frame.as_ref().map(|frame| frame.borrow().document());
+        self.viewport = match get_viewport_content(dom) {
+            Some(content) => Viewport::parse(&content),
+            None => Viewport::default(),
+        };
 
-        console_debug(&self.browser, "Painting tree...".to_string());
-        self.paint_tree();
+        // The first input with the `autofocus` attribute in tree order gets focus.
+        // https://html.spec.whatwg.org/multipage/interaction.html#the-autofocus-attribute
+        if let Some(input) = self.autofocus_input() {
+            self.set_focused_input(Some(input));
+        }
+
+        self.relayout();
         console_debug(&self.browser, format!("Paint complete. Display items count: {}", self.display_items.len()));
 
         // デバッグ: DisplayItemを詳細に確認
@@ -205,25 +1016,100 @@ impl Page {
         }
     }
 
+    /// Returns the DOM tree of this page as an indented string for debugging. It's empty when no
+    /// document is loaded.
+    pub fn dump_dom(&self) -> String {
+        match &self.frame {
+            Some(frame) => convert_dom_to_string(&Some(frame.borrow().document())),
+            None => String::new(),
+        }
+    }
+
+    /// Returns the HTML serialized from the DOM tree of this page, e.g. for viewing the source. It's
+    /// empty when no document is loaded.
+    pub fn source(&self) -> String {
+        match &self.frame {
+            Some(frame) => dom_to_html(&Some(frame.borrow().document())),
+            None => String::new(),
+        }
+    }
+
+    /// Returns the layout tree of this page as an indented string for debugging. It's empty when
+    /// no layout has been done.
+    pub fn dump_layout(&self) -> String {
+        match &self.layout_view {
+            Some(layout_view) => convert_layout_tree_to_string(&layout_view.root()),
+            None => String::new(),
+        }
+    }
+
     pub fn set_browser(&mut self, browser: Weak<RefCell<Browser>>) {
         self.browser = browser;
     }
 
     fn create_frame(&mut self, html: String) {
+        let start = self.now();
         let html_tokenizer = HtmlTokenizer::new(self.browser.clone(), html);
 
         let frame = HtmlParser::new(self.browser.clone(), html_tokenizer).construct_tree();
         let dom = frame.borrow().document();
+        self.record_phase(RenderingPhase::Parse, start);
 
         // for debug.
-        let debug = convert_dom_to_string(&Some(dom.clone()));
+        let debug = convert_dom_to_string(&Some(dom));
         console_debug(&self.browser, debug);
 
-        let style = get_style_content(dom);
-        let css_tokenizer = CssTokenizer::new(style);
-        let cssom = CssParser::new(self.browser.clone(), css_tokenizer).parse_stylesheet();
-
         self.frame = Some(frame);
+    }
+
+    /// Returns the elapsed time of each rendering phase since the last response was received, in
+    /// the unit of the browser's clock. A relayout, e.g. by a click handler, appends its phases.
+    /// It's empty if no clock is set by `Browser::set_clock`.
+    pub fn phase_timings(&self) -> Vec<(RenderingPhase, u64)> {
+        self.phase_timings.clone()
+    }
+
+    /// Returns the viewport settings of the page. The layout or a UI's zoom can consult them.
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    fn max_body_bytes(&self) -> usize {
+        match self.browser.upgrade() {
+            Some(browser) => browser.borrow().max_body_bytes(),
+            None => DEFAULT_MAX_BODY_BYTES,
+        }
+    }
+
+    fn now(&self) -> Option<u64> {
+        self.browser
+            .upgrade()
+            .and_then(|browser| browser.borrow().now())
+    }
+
+    fn record_phase(&mut self, phase: RenderingPhase, start: Option<u64>) {
+        if let (Some(start), Some(end)) = (start, self.now()) {
+            self.phase_timings.push((phase, end.saturating_sub(start)));
+        }
+    }
+
+    fn update_style(&mut self) {
+        let dom = match &self.frame {
+            Some(frame) => frame.borrow().document(),
+            None => return,
+        };
+
+        // Merge all stylesheets in source order so that a later rule wins over an earlier one
+        // with the same selector.
+        let mut rules = Vec::new();
+        for style in get_style_contents(dom) {
+            let css_tokenizer = CssTokenizer::new(style);
+            let sheet = CssParser::new(self.browser.clone(), css_tokenizer).parse_stylesheet();
+            rules.extend(sheet.rules);
+        }
+        let mut cssom = StyleSheet::new();
+        cssom.set_rules(rules);
+
         self.style = Some(cssom);
     }
 
@@ -262,7 +1148,42 @@ impl Page {
         let mut runtime = JsRuntime::new(dom);
         runtime.execute(&ast);
 
-        self.modified = runtime.dom_modified();
+        self.markup_inserted = runtime.markup_inserted();
+        self.js_runtime = Some(runtime);
+    }
+
+    /// Dispatches a click event to `target` and runs the `onclick` handlers of `target` and its
+    /// ancestors in bubbling order. Returns whether any handler ran and whether a handler
+    /// canceled the event.
+    /// https://html.spec.whatwg.org/multipage/webappapis.html#event-handler-attributes
+    fn dispatch_click(&mut self, target: &Rc<RefCell<Node>>) -> (bool, bool) {
+        let runtime = match &mut self.js_runtime {
+            Some(runtime) => runtime,
+            None => return (false, false),
+        };
+
+        let mut canceled = false;
+        let mut handled = false;
+        let mut current = Some(target.clone());
+        while let Some(n) = current {
+            let handler = n
+                .borrow()
+                .get_element()
+                .and_then(|e| e.get_attribute("onclick"));
+            if let Some(handler) = handler {
+                let mut parser = JsParser::new(JsLexer::new(handler));
+                let program = parser.parse_ast();
+                canceled |= runtime.run_event_handler(&program, target.clone());
+                handled = true;
+            }
+            current = n.borrow().parent().upgrade();
+        }
+
+        // A handler may have changed the DOM.
+        if handled {
+            self.relayout();
+        }
+        (handled, canceled)
     }
 
     pub fn push_url_for_subresource(&mut self, src: String) {
@@ -270,6 +1191,62 @@ impl Page {
         self.subresources.push(Subresource::new(src));
     }
 
+    /// Pushes the sources of the images to fetch when the viewport is scrolled to `scroll_y`. A
+    /// lazy image is skipped until its top is above the bottom of the viewport. Sources which
+    /// are already pushed are skipped, so call this again after scrolling.
+    /// https://html.spec.whatwg.org/multipage/urls-and-fetching.html#will-lazy-load-element-steps
+    pub fn push_image_subresources(&mut self, scroll_y: i64) {
+        let viewport_bottom = scroll_y + CONTENT_AREA_HEIGHT;
+        for item in &self.display_items {
+            if let DisplayItem::Img {
+                src,
+                layout_point,
+                lazy,
+                ..
+            } = item
+            {
+                if *lazy && layout_point.y() >= viewport_bottom {
+                    continue;
+                }
+                if self.subresources.iter().any(|s| s.src == *src) {
+                    continue;
+                }
+                self.subresources.push(Subresource::new(src.clone()));
+            }
+        }
+    }
+
+    /// Fetches the subresources which are not loaded yet with the browser's fetcher. If the
+    /// browser is requested to stop, the remaining subresources are not fetched.
+    pub fn fetch_subresources(&mut self) {
+        let browser = match self.browser.upgrade() {
+            Some(browser) => browser,
+            None => return,
+        };
+
+        for subresource in self.subresources.iter_mut() {
+            if !subresource.resource.is_empty() {
+                continue;
+            }
+            if browser.borrow_mut().poll_stop() {
+                console_debug(
+                    &self.browser,
+                    format!("stopped before fetching {}", subresource.src),
+                );
+                return;
+            }
+
+            let result = browser.borrow().fetch(subresource.src.clone());
+            match result {
+                Ok(response) => subresource.resource = response.body(),
+                Err(e) => console_warning(
+                    &self.browser,
+                    format!("failed to fetch {}: {:?}", subresource.src, e),
+                ),
+            }
+        }
+    }
+
     pub fn subresource(&self, src: String) -> String {
         for s in &self.subresources {
             if s.src == src {
@@ -283,14 +1260,1455 @@ impl Page {
         self.display_items.clone()
     }
 
-    pub fn clear_display_items(&mut self) {
+    /// Returns the text shown in the page in paint order, e.g. for search. Texts in the same
+    /// block are separated by a space, and a new block starts a new line.
+    pub fn visible_text(&self) -> String {
+        let mut visible_text = String::new();
+        let mut new_block = false;
+        for item in &self.display_items {
+            match item {
+                DisplayItem::Rect { .. } => new_block = true,
+                DisplayItem::Text { text, .. } => {
+                    let text = text.trim();
+                    if text.is_empty() {
+                        continue;
+                    }
+                    if !visible_text.is_empty() {
+                        visible_text.push(if new_block { '\n' } else { ' ' });
+                    }
+                    visible_text.push_str(text);
+                    new_block = false;
+                }
+                _ => {}
+            }
+        }
+        visible_text
+    }
+
+    /// Returns the language of the document from the `lang` attribute of the root html element.
+    /// https://html.spec.whatwg.org/multipage/dom.html#the-lang-and-xml:lang-attributes
+    pub fn document_language(&self) -> Option<String> {
+        let dom = match &self.frame {
+            Some(frame) => frame.borrow().document(),
+            None => return None,
+        };
+        let html = dom.borrow().first_child()?;
+        let lang = match html.borrow().kind() {
+            NodeKind::Element(e) if e.kind() == ElementKind::Html => e.get_attribute("lang"),
+            _ => None,
+        };
+        // The empty string means that the language is unknown.
+        lang.filter(|lang| !lang.is_empty())
+    }
+
+    /// Returns the readable text of the page. Text in different blocks is separated by
+    /// newlines and inline text in the same block is joined with spaces. Elements which are
+    /// not rendered (e.g. script and style) are excluded.
+    pub fn as_plain_text(&self) -> String {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        if let Some(layout_view) = &self.layout_view {
+            collect_plain_text(&layout_view.root(), &mut lines, &mut line);
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    /// Returns the text and the destination of each hyperlink in tree order, e.g. for a list of
+    /// links to jump to. A relative `href` is resolved against the current URL of the browser.
+    /// https://html.spec.whatwg.org/multipage/links.html#following-hyperlinks-2
+    pub fn links(&self) -> Vec<(String, String)> {
+        let dom = match &self.frame {
+            Some(frame) => frame.borrow().document(),
+            None => return Vec::new(),
+        };
+        let base_url = self
+            .browser
+            .upgrade()
+            .and_then(|browser| browser.borrow().current_url())
+            .and_then(|url| Url::new(url).parse().ok());
+
+        let mut anchors = Vec::new();
+        collect_anchors(&dom.borrow().first_child(), &mut anchors);
+        anchors
+            .iter()
+            .filter_map(|anchor| {
+                let href = anchor.borrow().get_element()?.get_attribute("href")?;
+                let href = match base_url.as_ref().map(|base| base.join(&href)) {
+                    Some(Ok(url)) => url.href(),
+                    // Keep what can't be resolved, e.g. a URL of an unsupported scheme.
+                    _ => href,
+                };
+                let mut text = String::new();
+                collect_text(&anchor.borrow().first_child(), &mut text);
+                let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+                Some((text, href))
+            })
+            .collect()
+    }
+
+    /// Returns true if the last paint changed the display items. A UI can skip redrawing when
+    /// it's false.
+    pub fn display_items_changed(&self) -> bool {
+        self.display_items_changed
+    }
+
+    /// Returns a counter which is incremented every time this page receives a response. A UI
+    /// can compare it with the value it saw last to detect new content, e.g. to scroll back to
+    /// the top.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn clear_display_items(&mut self) {
         self.display_items = Vec::new();
     }
 
     /// https://source.chromium.org/chromium/chromium/src/+/main:third_party/blink/renderer/core/frame/local_frame_view.h;drc=0e9a0b6e9bb6ec59521977eec805f5d0bca833e0;bpv=1;bpt=1;l=907
     fn paint_tree(&mut self) {
         if let Some(layout_view) = &self.layout_view {
-            self.display_items = layout_view.paint();
+            let display_items = layout_view.paint();
+            self.display_items_changed = display_items != self.display_items;
+            self.display_items = display_items;
+        }
+    }
+}
+
+/// Returns the nearest form element containing `node`.
+fn enclosing_form(node: &Rc<RefCell<Node>>) -> Option<Rc<RefCell<Node>>> {
+    let mut current = node.borrow().parent().upgrade();
+    while let Some(n) = current {
+        if n.borrow().element_kind() == Some(ElementKind::Form) {
+            return Some(n);
+        }
+        current = n.borrow().parent().upgrade();
+    }
+    None
+}
+
+/// Returns `node` itself or its nearest ancestor if it's a label element.
+fn enclosing_label(node: &Rc<RefCell<Node>>) -> Option<Rc<RefCell<Node>>> {
+    let mut current = Some(node.clone());
+    while let Some(n) = current {
+        if n.borrow().element_kind() == Some(ElementKind::Label) {
+            return Some(n);
+        }
+        current = n.borrow().parent().upgrade();
+    }
+    None
+}
+
+/// Collects input elements under `node` and its following siblings in tree order. The contents
+/// of templates are skipped.
+fn collect_inputs(node: &Option<Rc<RefCell<Node>>>, inputs: &mut Vec<Rc<RefCell<Node>>>) {
+    let n = match node {
+        Some(n) => n,
+        None => return,
+    };
+
+    if n.borrow().element_kind() == Some(ElementKind::Input) {
+        inputs.push(n.clone());
+    }
+
+    collect_inputs(&non_inert_first_child(n), inputs);
+    collect_inputs(&n.borrow().next_sibling(), inputs);
+}
+
+/// Collects a elements under `node` and its following siblings in tree order. The contents of
+/// templates are skipped.
+fn collect_anchors(node: &Option<Rc<RefCell<Node>>>, anchors: &mut Vec<Rc<RefCell<Node>>>) {
+    let n = match node {
+        Some(n) => n,
+        None => return,
+    };
+
+    if n.borrow().element_kind() == Some(ElementKind::A) {
+        anchors.push(n.clone());
+    }
+
+    collect_anchors(&non_inert_first_child(n), anchors);
+    collect_anchors(&n.borrow().next_sibling(), anchors);
+}
+
+/// Appends the text under `node` and its following siblings to `text`.
+fn collect_text(node: &Option<Rc<RefCell<Node>>>, text: &mut String) {
+    let n = match node {
+        Some(n) => n,
+        None => return,
+    };
+
+    if let NodeKind::Text(s) = n.borrow().kind() {
+        text.push_str(&s);
+        text.push(' ');
+    }
+
+    collect_text(&n.borrow().first_child(), text);
+    collect_text(&n.borrow().next_sibling(), text);
+}
+
+/// https://url.spec.whatwg.org/#concept-urlencoded-byte-serializer
+fn form_urlencode(s: &str) -> String {
+    let mut result = String::new();
+    for b in s.bytes() {
+        match b {
+            b' ' => result.push('+'),
+            b'*' | b'-' | b'.' | b'_' | b'0'..=b'9' | b'A'..=b'Z' | b'a'..=b'z' => {
+                result.push(b as char)
+            }
+            _ => result.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    result
+}
+
+fn collect_plain_text(
+    node: &Option<Rc<RefCell<LayoutObject>>>,
+    lines: &mut Vec<String>,
+    line: &mut String,
+) {
+    let n = match node {
+        Some(n) => n,
+        None => return,
+    };
+
+    let is_block = n.borrow().kind() == LayoutObjectKind::Block;
+    if is_block && !line.is_empty() {
+        lines.push(core::mem::take(line));
+    }
+
+    if let NodeKind::Text(text) = n.borrow().node_kind() {
+        for word in text.split_whitespace() {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+    }
+
+    collect_plain_text(&n.borrow().first_child(), lines, line);
+
+    if is_block && !line.is_empty() {
+        lines.push(core::mem::take(line));
+    }
+
+    collect_plain_text(&n.borrow().next_sibling(), lines, line);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display_item::input_text;
+    use crate::display_item::InputText;
+    use crate::error::Error;
+    use crate::renderer::dom::api::get_select_options;
+    use crate::renderer::dom::api::get_target_element_node;
+    use crate::renderer::layout::color::Color;
+    use crate::renderer::layout::computed_style::ComputedStyle;
+    use crate::renderer::layout::layout_point::LayoutPoint;
+    use alloc::vec;
+    use core::sync::atomic::AtomicU64;
+    use core::sync::atomic::Ordering;
+
+    /// Loads `html` into the current page of a new browser.
+    fn load(html: &str) -> (Rc<RefCell<Browser>>, Rc<RefCell<Page>>) {
+        let browser = Browser::new();
+        let response = HttpResponse::new(format!("HTTP/1.1 200 OK\n\n{}", html))
+            .expect("failed to parse a response");
+        let page = browser.borrow().current_page();
+        page.borrow_mut().receive_response(response);
+        (browser, page)
+    }
+
+    fn document(page: &Rc<RefCell<Page>>) -> Rc<RefCell<Node>> {
+        page.borrow()
+            .frame
+            .as_ref()
+            .expect("frame should exist")
+            .borrow()
+            .document()
+    }
+
+    #[test]
+    fn test_text_highlight() {
+        let mut page = Page::new();
+        let item = DisplayItem::Text {
+            text: "found".to_string(),
+            style: ComputedStyle::new(),
+            layout_point: LayoutPoint::new(0, 0),
+            visited: false,
+            highlight: Some(Color::from_name("yellow").expect("failed to parse a color")),
+        };
+        page.display_items = vec![item.clone()];
+
+        let items = page.display_items();
+        assert_eq!(vec![item], items);
+        match &items[0] {
+            DisplayItem::Text { highlight, .. } => assert_eq!(
+                Some(Color::from_name("yellow").expect("failed to parse a color")),
+                *highlight
+            ),
+            _ => panic!("expected a text item"),
+        }
+    }
+
+    #[test]
+    fn test_later_stylesheet_wins() {
+        let html = "<html><head><style>.x { color: red; }</style><style>.x { color: blue; }</style></head><body><p class=\"x\">text</p></body></html>";
+        let (_browser, page) = load(html);
+
+        let items = page.borrow().display_items();
+        let text = items
+            .iter()
+            .find(|item| item.is_text())
+            .expect("text item should exist");
+        match text {
+            DisplayItem::Text { style, .. } => assert_eq!(
+                Color::from_name("blue").expect("failed to parse a color"),
+                style.color()
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_relayout_keeps_focus() {
+        let html = "<html><body><p id=\"target\">before</p><input type=\"text\" /></body></html>";
+        let (_browser, page) = load(html);
+
+        let dom = document(&page);
+        let input = get_target_element_node(Some(dom.clone()), ElementKind::Input)
+            .expect("input should exist");
+        page.borrow_mut().focused_input = Some(input.clone());
+
+        page.borrow_mut()
+            .execute_script("document.getElementById(\"target\").textContent = \"after\";")
+            .expect("failed to execute a script");
+
+        let focused = page
+            .borrow()
+            .focused_input
+            .clone()
+            .expect("focus should survive relayout");
+        assert!(Rc::ptr_eq(&input, &focused));
+        assert!(page.borrow().display_items().iter().any(|item| matches!(
+            item,
+            DisplayItem::Text { text, .. } if text == "after"
+        )));
+    }
+
+    #[test]
+    fn test_as_plain_text() {
+        let html = "<html><head><style>p { color: red; }</style></head><body><h1>Title</h1><p>first paragraph</p><p>second</p><script>var a=1;</script></body></html>";
+        let (_browser, page) = load(html);
+
+        assert_eq!(
+            "Title\nfirst paragraph\nsecond".to_string(),
+            page.borrow().as_plain_text()
+        );
+    }
+
+    #[test]
+    fn test_document_language() {
+        let browser = Browser::new();
+        let page = browser.borrow().current_page();
+        let response = HttpResponse::new(
+            "HTTP/1.1 200 OK\n\n<html lang=\"ja\"><body><p>text</p></body></html>".to_string(),
+        )
+        .expect("failed to parse a response");
+        page.borrow_mut().receive_response(response);
+        assert_eq!(Some("ja".to_string()), page.borrow().document_language());
+
+        let response = HttpResponse::new(
+            "HTTP/1.1 200 OK\n\n<html><body><p>text</p></body></html>".to_string(),
+        )
+        .expect("failed to parse a response");
+        page.borrow_mut().receive_response(response);
+        assert_eq!(None, page.borrow().document_language());
+    }
+
+    #[test]
+    fn test_next_field_or_submit() {
+        assert_eq!(EnterAction::FocusField(1), next_field_or_submit(0, 2));
+        assert_eq!(EnterAction::FocusField(2), next_field_or_submit(1, 3));
+        assert_eq!(EnterAction::Submit, next_field_or_submit(1, 2));
+        assert_eq!(EnterAction::Submit, next_field_or_submit(0, 1));
+    }
+
+    #[test]
+    fn test_handle_enter() {
+        let html = "<html><body><form action=\"/search\"><input type=\"text\" name=\"q\" /><input type=\"text\" name=\"lang\" /></form></body></html>";
+        let (_browser, page) = load(html);
+
+        let dom = document(&page);
+        let mut inputs = Vec::new();
+        collect_inputs(&Some(dom), &mut inputs);
+        assert_eq!(2, inputs.len());
+        page.borrow_mut().focused_input = Some(inputs[0].clone());
+        for c in "rust lang".chars() {
+            page.borrow_mut().handle_input(c);
+        }
+
+        // Enter in the first input moves the focus to the second one.
+        assert_eq!(None, page.borrow_mut().handle_enter());
+        let focused = page
+            .borrow()
+            .focused_input
+            .clone()
+            .expect("should be focused");
+        assert!(Rc::ptr_eq(&inputs[1], &focused));
+
+        // Enter in the last input submits the form.
+        assert_eq!(
+            Some((
+                "/search?q=rust+lang&lang=".to_string(),
+                NavigationTarget::CurrentPage
+            )),
+            page.borrow_mut().handle_enter()
+        );
+    }
+
+    #[test]
+    fn test_submit_target() {
+        assert_eq!(
+            NavigationTarget::CurrentPage,
+            NavigationTarget::from_attribute(None)
+        );
+        assert_eq!(
+            NavigationTarget::CurrentPage,
+            NavigationTarget::from_attribute(Some("_self"))
+        );
+        assert_eq!(
+            NavigationTarget::NewPage,
+            NavigationTarget::from_attribute(Some("_BLANK"))
+        );
+
+        let html = "<html><body><form action=\"/search\" target=\"_blank\"><input type=\"text\" name=\"q\" autofocus /></form></body></html>";
+        let (_browser, page) = load(html);
+
+        assert_eq!(
+            Some(("/search?q=".to_string(), NavigationTarget::NewPage)),
+            page.borrow_mut().handle_enter()
+        );
+    }
+
+    #[test]
+    fn test_label_focuses_input() {
+        let html = "<html><body><label for=\"name\">Name</label><input type=\"text\" id=\"name\" /><label><input type=\"text\" id=\"wrapped\" /></label></body></html>";
+        let (_browser, page) = load(html);
+
+        // Clicking the text of the first label focuses the input referenced by `for`.
+        assert_eq!(ClickAction::FocusInput, page.borrow_mut().clicked((1, 1)));
+        let dom = document(&page);
+        let input =
+            get_element_by_id(Some(dom.clone()), &"name".to_string()).expect("input should exist");
+        let focused = page
+            .borrow()
+            .focused_input
+            .clone()
+            .expect("should be focused");
+        assert!(Rc::ptr_eq(&input, &focused));
+
+        // A label without `for` is associated with the input inside it.
+        let label = get_target_element_node(input.borrow().next_sibling(), ElementKind::Label)
+            .expect("label should exist");
+        let wrapped =
+            get_element_by_id(Some(dom), &"wrapped".to_string()).expect("input should exist");
+        let control = page
+            .borrow()
+            .labeled_control(&label)
+            .expect("label should have a control");
+        assert!(Rc::ptr_eq(&wrapped, &control));
+    }
+
+    #[test]
+    fn test_password_input() {
+        let html = "<html><body><input type=\"password\" name=\"pw\" /></body></html>";
+        let (_browser, page) = load(html);
+        page.borrow_mut().clicked((1, 1));
+        for c in "abc".chars() {
+            assert!(page.borrow_mut().handle_input(c));
+        }
+        page.borrow_mut().refresh_display();
+
+        let input = page
+            .borrow()
+            .focused_input
+            .clone()
+            .expect("should be focused");
+        let value = match input.borrow().kind() {
+            NodeKind::Element(e) => e.get_value(),
+            _ => None,
+        };
+        assert_eq!(Some("abc".to_string()), value);
+
+        // The UIs draw the masked value, which keeps the length of the value.
+        let displayed = page
+            .borrow()
+            .display_items()
+            .iter()
+            .find_map(|item| match item {
+                DisplayItem::Input {
+                    input_type,
+                    value,
+                    placeholder,
+                    ..
+                } => Some(input_text(input_type, value, placeholder)),
+                _ => None,
+            });
+        assert_eq!(
+            Some(InputText::Value("\u{2022}\u{2022}\u{2022}".to_string())),
+            displayed
+        );
+
+        // Other inputs and buttons aren't masked.
+        assert_eq!(
+            InputText::Value("abc".to_string()),
+            input_text("text", &Some("abc".to_string()), &None)
+        );
+        assert_eq!(
+            InputText::Value("Submit".to_string()),
+            input_text("submit", &Some("Submit".to_string()), &None)
+        );
+        assert_eq!(
+            InputText::Placeholder("secret".to_string()),
+            input_text(
+                "password",
+                &Some(String::new()),
+                &Some("secret".to_string())
+            )
+        );
+        assert_eq!(InputText::Empty, input_text("password", &None, &None));
+    }
+
+    #[test]
+    fn test_number_input() {
+        let html = "<html><body><input type=\"number\" name=\"n\" /></body></html>";
+        let (_browser, page) = load(html);
+        page.borrow_mut().clicked((1, 1));
+
+        assert!(page.borrow_mut().handle_input('4'));
+        assert!(!page.borrow_mut().handle_input('x'));
+        assert!(page.borrow_mut().handle_input('.'));
+        assert!(page.borrow_mut().handle_input('2'));
+
+        let input = page
+            .borrow()
+            .focused_input
+            .clone()
+            .expect("should be focused");
+        let value = match input.borrow().kind() {
+            NodeKind::Element(e) => e.get_value(),
+            _ => None,
+        };
+        assert_eq!(Some("4.2".to_string()), value);
+    }
+
+    #[test]
+    fn test_rerender_on_input() {
+        let html = "<html><body><input autofocus /></body></html>";
+        let (_browser, page) = load(html);
+
+        let input_value = || {
+            page.borrow()
+                .display_items()
+                .into_iter()
+                .find_map(|item| match item {
+                    DisplayItem::Input { value, caret, .. } => Some((value, caret)),
+                    _ => None,
+                })
+                .expect("input should be painted")
+        };
+        assert_eq!((None, Some(0)), input_value());
+
+        // The display item is updated without refreshing the display.
+        assert!(page.borrow_mut().handle_input('a'));
+        assert!(page.borrow().display_items_changed());
+        assert_eq!((Some("a".to_string()), Some(1)), input_value());
+        assert!(page.borrow_mut().move_caret(CaretMove::Home));
+        assert_eq!((Some("a".to_string()), Some(0)), input_value());
+
+        page.borrow_mut().set_rerender_on_input(false);
+        assert!(page.borrow_mut().handle_input('b'));
+        assert_eq!((Some("a".to_string()), Some(0)), input_value());
+        page.borrow_mut().refresh_display();
+        assert_eq!((Some("ba".to_string()), Some(1)), input_value());
+    }
+
+    #[test]
+    fn test_input_value_attribute() {
+        let html = "<html><body><input name=\"q\" value=\"hi\" /><input name=\"r\" placeholder=\"hint\" /></body></html>";
+        let (_browser, page) = load(html);
+
+        let inputs = || {
+            page.borrow()
+                .display_items()
+                .into_iter()
+                .filter_map(|item| match item {
+                    DisplayItem::Input {
+                        value, placeholder, ..
+                    } => Some((value, placeholder)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        // The `value` attribute is rendered as the value, not as a placeholder.
+        assert_eq!(
+            vec![
+                (Some("hi".to_string()), None),
+                (None, Some("hint".to_string()))
+            ],
+            inputs()
+        );
+
+        // The value is edited from the initial value.
+        page.borrow_mut().clicked((1, 1));
+        assert!(page.borrow_mut().handle_input('!'));
+        page.borrow_mut().refresh_display();
+        assert_eq!(Some("hi!".to_string()), inputs()[0].0);
+        let input = page
+            .borrow()
+            .focused_input
+            .clone()
+            .expect("should be focused");
+        let attribute = input
+            .borrow()
+            .get_element()
+            .and_then(|e| e.get_attribute("value"));
+        assert_eq!(Some("hi".to_string()), attribute);
+    }
+
+    #[test]
+    fn test_caret() {
+        let html = "<html><body><input name=\"q\" value=\"ac\" /></body></html>";
+        let (_browser, page) = load(html);
+        page.borrow_mut().clicked((1, 1));
+
+        let input = page
+            .borrow()
+            .focused_input
+            .clone()
+            .expect("should be focused");
+        let value = || input.borrow().get_element().and_then(|e| e.get_value());
+        let caret = || input.borrow().get_element().and_then(|e| e.caret());
+        assert_eq!(Some(2), caret());
+
+        // A character is inserted in the middle of the value.
+        assert!(page.borrow_mut().move_caret(CaretMove::Left));
+        assert!(page.borrow_mut().handle_input('b'));
+        assert_eq!(Some("abc".to_string()), value());
+        assert_eq!(Some(2), caret());
+
+        assert!(page.borrow_mut().move_caret(CaretMove::Home));
+        assert!(!page.borrow_mut().move_caret(CaretMove::Left));
+        assert!(page.borrow_mut().handle_input('x'));
+        assert_eq!(Some("xabc".to_string()), value());
+
+        // Backspace deletes the character before the caret.
+        assert!(page.borrow_mut().move_caret(CaretMove::End));
+        assert!(page.borrow_mut().move_caret(CaretMove::Left));
+        assert!(page.borrow_mut().move_caret(CaretMove::Left));
+        assert!(page.borrow_mut().handle_input(0x08 as char));
+        assert_eq!(Some("xbc".to_string()), value());
+        assert_eq!(Some(1), caret());
+
+        page.borrow_mut().refresh_display();
+        let display_items = page.borrow().display_items();
+        let displayed_caret = display_items.into_iter().find_map(|item| match item {
+            DisplayItem::Input { caret, .. } => caret,
+            _ => None,
+        });
+        assert_eq!(Some(1), displayed_caret);
+
+        // The caret is removed when the input loses focus.
+        page.borrow_mut().set_focused_input(None);
+        assert_eq!(None, caret());
+    }
+
+    #[test]
+    fn test_clicked_link_rel() {
+        let html = "<html><body><a href=\"/next\" rel=\"noopener NoFollow noopener\">link</a></body></html>";
+        let (_browser, page) = load(html);
+
+        let link = page
+            .borrow_mut()
+            .clicked_link((1, 1))
+            .expect("link should be clicked");
+        assert_eq!("/next", link.href());
+        assert_eq!(
+            vec!["noopener".to_string(), "nofollow".to_string()],
+            link.rel()
+        );
+        assert!(link.has_rel("noopener"));
+        assert!(!link.has_rel("noreferrer"));
+        assert_eq!(
+            ClickAction::Navigate("/next".to_string()),
+            page.borrow_mut().clicked((1, 1))
+        );
+    }
+
+    #[test]
+    fn test_abbr_title() {
+        let html =
+            "<html><body><abbr title=\"HyperText Markup Language\">HTML</abbr></body></html>";
+        let (_browser, page) = load(html);
+
+        assert_eq!(
+            Some("HyperText Markup Language".to_string()),
+            page.borrow().title_at((1, 1))
+        );
+        assert_eq!(None, page.borrow().title_at((1, 500)));
+    }
+
+    #[test]
+    fn test_cursor_at() {
+        let html = "<html><head><style>.p { cursor: pointer; } .d { cursor: default; }</style></head><body><p><a href=\"/next\">link</a></p><p>text</p><p class=\"p\">button</p><p><a href=\"/next\" class=\"d\">plain</a></p></body></html>";
+        let (_browser, page) = load(html);
+
+        let cursors: Vec<(String, Cursor)> = page
+            .borrow()
+            .display_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text {
+                    text, layout_point, ..
+                } => Some((text, layout_point)),
+                _ => None,
+            })
+            .map(|(text, point)| {
+                let cursor = page.borrow().cursor_at((point.x() + 1, point.y() + 1));
+                (text, cursor)
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("link".to_string(), Cursor::Pointer),
+                ("text".to_string(), Cursor::Default),
+                ("button".to_string(), Cursor::Pointer),
+                ("plain".to_string(), Cursor::Default),
+            ],
+            cursors
+        );
+        assert_eq!(Cursor::Default, page.borrow().cursor_at((1, 500)));
+    }
+
+    #[test]
+    fn test_style_in_body() {
+        let html =
+            "<html><head></head><body><style>p { color: green; }</style><p>text</p></body></html>";
+        let (_browser, page) = load(html);
+
+        // The style element itself is not rendered.
+        let texts: Vec<(String, Color)> = page
+            .borrow()
+            .display_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, style, .. } => Some((text, style.color())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![(
+                "text".to_string(),
+                Color::from_name("green").expect("failed to parse a color")
+            )],
+            texts
+        );
+    }
+
+    #[test]
+    fn test_viewport_meta() {
+        let browser = Browser::new();
+        let html = "<html><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1\"><style>p { color: red; }</style></head><body><p>text</p></body></html>";
+        let response = HttpResponse::new(format!("HTTP/1.1 200 OK\n\n{}", html))
+            .expect("failed to parse a response");
+        let page = browser.borrow().current_page();
+        assert_eq!(Viewport::default(), page.borrow().viewport());
+        page.borrow_mut().receive_response(response);
+
+        let viewport = page.borrow().viewport();
+        assert_eq!(Some(ViewportWidth::DeviceWidth), viewport.width());
+        assert_eq!(Some(1.0), viewport.initial_scale());
+        // The meta elements don't end the head, so the style still applies.
+        assert_eq!("text", page.borrow().visible_text());
+
+        // Unknown directives and invalid values are skipped.
+        let viewport = Viewport::parse("width=320; user-scalable=no; initial-scale=abc");
+        assert_eq!(Some(ViewportWidth::Px(320.0)), viewport.width());
+        assert_eq!(None, viewport.initial_scale());
+        let viewport = Viewport::parse(" Initial-Scale = 2.5 ,foo");
+        assert_eq!(None, viewport.width());
+        assert_eq!(Some(2.5), viewport.initial_scale());
+    }
+
+    #[test]
+    fn test_click_actions() {
+        let html = "<html><body><p><a href=\"/next\">link</a></p><p><input name=\"q\" /></p><p onclick='event.target.textContent = \"clicked\"'>handler</p><p>text</p></body></html>";
+        let (_browser, page) = load(html);
+
+        let points: Vec<LayoutPoint> = page
+            .borrow()
+            .display_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { layout_point, .. } => Some(layout_point),
+                DisplayItem::Input { layout_point, .. } => Some(layout_point),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(4, points.len());
+        let click = |point: &LayoutPoint| page.borrow_mut().clicked((point.x() + 1, point.y() + 1));
+
+        assert_eq!(
+            ClickAction::Navigate("/next".to_string()),
+            click(&points[0])
+        );
+        assert_eq!(ClickAction::FocusInput, click(&points[1]));
+        // The handler changes the page without navigating.
+        assert_eq!(ClickAction::RanHandler, click(&points[2]));
+        assert!(page.borrow().visible_text().contains("clicked"));
+        assert_eq!(ClickAction::None, click(&points[3]));
+        assert!(!page.borrow().has_focused_input());
+    }
+
+    #[test]
+    fn test_click_prevent_default() {
+        let html = "<html><body><a href=\"/next\" onclick='event.preventDefault(); event.target.textContent = \"clicked\"'>link</a></body></html>";
+        let (_browser, page) = load(html);
+
+        // The handler runs, but the navigation is canceled.
+        assert_eq!(ClickAction::RanHandler, page.borrow_mut().clicked((1, 1)));
+        let texts: Vec<String> = page
+            .borrow()
+            .display_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["clicked".to_string()], texts);
+    }
+
+    #[test]
+    fn test_click_unknown_event_member() {
+        let html = "<html><body><a href=\"/next\" onclick='event.foo.bar; event.foo.bar(); event.preventDefault()'>link</a></body></html>";
+        let (_browser, page) = load(html);
+
+        // The unknown members are undefined and the rest of the handler still runs.
+        assert_eq!(ClickAction::RanHandler, page.borrow_mut().clicked((1, 1)));
+    }
+
+    #[test]
+    fn test_click_handler_without_prevent_default() {
+        let html = "<html><body><a href=\"/next\" onclick='event.target.textContent = \"clicked\"'>link</a></body></html>";
+        let (_browser, page) = load(html);
+
+        assert_eq!(
+            ClickAction::Navigate("/next".to_string()),
+            page.borrow_mut().clicked((1, 1))
+        );
+    }
+
+    #[test]
+    fn test_set_attribute() {
+        let html = "<html><body><a id=\"link\" href=\"/old\">link</a><script>document.getElementById(\"link\").setAttribute(\"href\", \"/new\")</script></body></html>";
+        let (_browser, page) = load(html);
+
+        assert_eq!(
+            ClickAction::Navigate("/new".to_string()),
+            page.borrow_mut().clicked((1, 1))
+        );
+    }
+
+    #[test]
+    fn test_script_types() {
+        let html = "<html><body><a id=\"a\" href=\"/old\">a</a><a id=\"b\" href=\"/old\">b</a><a id=\"c\" href=\"/old\">c</a><script type=\"text/javascript\">document.getElementById(\"a\").setAttribute(\"href\", \"/new\");</script><script type=\"module\">document.getElementById(\"b\").setAttribute(\"href\", \"/new\");</script><script type=\"application/json\">document.getElementById(\"c\").setAttribute(\"href\", \"/new\");</script></body></html>";
+        let (_browser, page) = load(html);
+
+        let dom = document(&page);
+        let href = |id: &str| {
+            get_element_by_id(Some(dom.clone()), &id.to_string())
+                .and_then(|n| n.borrow().get_element())
+                .and_then(|e| e.get_attribute("href"))
+        };
+        assert_eq!(Some("/new".to_string()), href("a"));
+        assert_eq!(Some("/new".to_string()), href("b"));
+        assert_eq!(Some("/old".to_string()), href("c"));
+    }
+
+    #[test]
+    fn test_template() {
+        let html =
+            "<html><body><p>shown</p><template id=\"t\"><p>hidden</p></template></body></html>";
+        let (_browser, page) = load(html);
+
+        let texts: Vec<String> = page
+            .borrow()
+            .display_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["shown".to_string()], texts);
+
+        let dom = document(&page);
+        let template =
+            get_element_by_id(Some(dom), &"t".to_string()).expect("template should exist");
+        assert_eq!(
+            Some(ElementKind::Template),
+            template.borrow().element_kind()
+        );
+        let p = template.borrow().first_child().expect("p should exist");
+        assert_eq!(Some(ElementKind::P), p.borrow().element_kind());
+        let text = p.borrow().first_child().expect("text should exist");
+        assert_eq!(NodeKind::Text("hidden".to_string()), text.borrow().kind());
+    }
+
+    #[test]
+    fn test_template_contents_are_inert() {
+        let html = "<html><body><p id=\"x\">text</p><template><style>p { color: red; }</style><script>document.getElementById(\"x\").textContent = \"changed\";</script><p id=\"inner\"><a href=\"/inner\">inner</a><input name=\"q\"></p></template></body></html>";
+        let (_browser, page) = load(html);
+
+        // The script doesn't run and the style doesn't apply.
+        let texts: Vec<(String, Color)> = page
+            .borrow()
+            .display_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, style, .. } => Some((text, style.color())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec![("text".to_string(), Color::black())], texts);
+
+        let dom = document(&page);
+        assert!(get_element_by_id(Some(dom.clone()), &"inner".to_string()).is_none());
+        let mut inputs = Vec::new();
+        collect_inputs(&Some(dom), &mut inputs);
+        assert!(inputs.is_empty());
+        assert!(page.borrow().links().is_empty());
+    }
+
+    #[test]
+    fn test_select_optgroup() {
+        let html = "<html><body><select id=\"s\"><optgroup label=\"Fruits\"><option value=\"a\">Apple<option>Banana</optgroup><optgroup label=\"Vegetables\"><option value=\"c\"> Carrot </option></optgroup></select></body></html>";
+        let (_browser, page) = load(html);
+
+        let dom = document(&page);
+        let select = get_element_by_id(Some(dom), &"s".to_string()).expect("select should exist");
+        let options: Vec<(String, String, Option<String>)> = get_select_options(&select)
+            .into_iter()
+            .map(|o| (o.value(), o.text(), o.group()))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "a".to_string(),
+                    "Apple".to_string(),
+                    Some("Fruits".to_string())
+                ),
+                (
+                    "Banana".to_string(),
+                    "Banana".to_string(),
+                    Some("Fruits".to_string())
+                ),
+                (
+                    "c".to_string(),
+                    "Carrot".to_string(),
+                    Some("Vegetables".to_string())
+                ),
+            ],
+            options
+        );
+    }
+
+    #[test]
+    fn test_visible_text() {
+        let browser = Browser::new();
+        let html = "<html><body><h1>Title</h1><p>Hello <b>bold</b> world</p></body></html>";
+        let response = HttpResponse::new(format!("HTTP/1.1 200 OK\n\n{}", html))
+            .expect("failed to parse a response");
+        let page = browser.borrow().current_page();
+        assert_eq!("", page.borrow().visible_text());
+
+        page.borrow_mut().receive_response(response);
+        assert_eq!("Title\nHello bold world", page.borrow().visible_text());
+    }
+
+    #[test]
+    fn test_class_list_toggle() {
+        let html = "<html><head><style>.red { color: red; }</style></head><body><p class=\"big\" onclick='event.target.classList.toggle(\"red\")'>text</p></body></html>";
+        let (_browser, page) = load(html);
+
+        let text_color = |page: &Rc<RefCell<Page>>| {
+            page.borrow()
+                .display_items()
+                .into_iter()
+                .find_map(|item| match item {
+                    DisplayItem::Text { style, .. } => Some(style.color()),
+                    _ => None,
+                })
+                .expect("text item should exist")
+        };
+        assert_eq!(Color::black(), text_color(&page));
+
+        page.borrow_mut().clicked((1, 1));
+        assert_eq!(
+            Color::from_name("red").expect("failed to parse a color"),
+            text_color(&page)
+        );
+
+        page.borrow_mut().clicked((1, 1));
+        assert_eq!(Color::black(), text_color(&page));
+    }
+
+    static FAKE_TIME: AtomicU64 = AtomicU64::new(0);
+
+    /// A fake clock which advances by 1 on every read.
+    fn fake_clock() -> u64 {
+        FAKE_TIME.fetch_add(1, Ordering::SeqCst)
+    }
+
+    #[test]
+    fn test_phase_timings() {
+        let browser = Browser::new();
+        let html =
+            "<html><head><style>p { color: red; }</style></head><body><p>text</p></body></html>";
+        let response = HttpResponse::new(format!("HTTP/1.1 200 OK\n\n{}", html))
+            .expect("failed to parse a response");
+        let page = browser.borrow().current_page();
+
+        // Nothing is recorded without a clock.
+        page.borrow_mut().receive_response(response.clone());
+        assert!(page.borrow().phase_timings().is_empty());
+
+        browser.borrow_mut().set_clock(fake_clock);
+        page.borrow_mut().receive_response(response);
+        let timings = page.borrow().phase_timings();
+        let phases: Vec<RenderingPhase> = timings.iter().map(|(phase, _)| *phase).collect();
+        assert_eq!(
+            vec![
+                RenderingPhase::Parse,
+                RenderingPhase::Style,
+                RenderingPhase::Layout,
+                RenderingPhase::Paint
+            ],
+            phases
+        );
+        assert!(timings.iter().all(|(_, duration)| *duration > 0));
+    }
+
+    #[test]
+    fn test_script_changes_without_reparse() {
+        let browser = Browser::new();
+        browser.borrow_mut().set_clock(fake_clock);
+        let html = "<html><head><style>.done { color: red; }</style></head><body><p id=\"target\">before</p><script>var p = document.getElementById(\"target\"); p.setAttribute(\"class\", \"done\"); p.textContent = \"after\";</script></body></html>";
+        let response = HttpResponse::new(format!("HTTP/1.1 200 OK\n\n{}", html))
+            .expect("failed to parse a response");
+        let page = browser.borrow().current_page();
+        page.borrow_mut().receive_response(response);
+
+        // The document is parsed only once.
+        let phases: Vec<RenderingPhase> = page
+            .borrow()
+            .phase_timings()
+            .iter()
+            .map(|(phase, _)| *phase)
+            .collect();
+        assert_eq!(
+            vec![
+                RenderingPhase::Parse,
+                RenderingPhase::Style,
+                RenderingPhase::Layout,
+                RenderingPhase::Paint
+            ],
+            phases
+        );
+        let items = page.borrow().display_items();
+        let text = items
+            .iter()
+            .find(|item| matches!(item, DisplayItem::Text { text, .. } if text == "after"))
+            .expect("changed text should be painted");
+        match text {
+            DisplayItem::Text { style, .. } => assert_eq!(
+                Color::from_name("red").expect("failed to parse a color"),
+                style.color()
+            ),
+            _ => unreachable!(),
         }
     }
+
+    fn fetch_image(_url: String) -> Result<HttpResponse, Error> {
+        HttpResponse::new("HTTP/1.1 200 OK\nContent-Length: 5\n\nimage".to_string())
+    }
+
+    #[test]
+    fn test_stop_before_subresources() {
+        let browser = Browser::new();
+        browser.borrow_mut().set_handle_url(fetch_image);
+        let page = browser.borrow().current_page();
+        page.borrow_mut()
+            .push_url_for_subresource("http://example.com/a.png".to_string());
+
+        // No subresource is fetched after the navigation is stopped.
+        browser.borrow_mut().stop();
+        page.borrow_mut().fetch_subresources();
+        assert_eq!(
+            "",
+            page.borrow()
+                .subresource("http://example.com/a.png".to_string())
+        );
+
+        browser.borrow_mut().clear_stop();
+        page.borrow_mut().fetch_subresources();
+        assert_eq!(
+            "image",
+            page.borrow()
+                .subresource("http://example.com/a.png".to_string())
+        );
+    }
+
+    fn esc_pressed() -> bool {
+        true
+    }
+
+    #[test]
+    fn test_stop_poller() {
+        let browser = Browser::new();
+        browser.borrow_mut().set_handle_url(fetch_image);
+        let page = browser.borrow().current_page();
+        page.borrow_mut()
+            .push_url_for_subresource("http://example.com/a.png".to_string());
+
+        // A stop requested while the UI was blocked is seen before the next fetch.
+        browser.borrow_mut().set_stop_poller(esc_pressed);
+        page.borrow_mut().fetch_subresources();
+        assert!(browser.borrow().is_stopped());
+        assert_eq!(
+            "",
+            page.borrow()
+                .subresource("http://example.com/a.png".to_string())
+        );
+    }
+
+    #[test]
+    fn test_display_items_changed() {
+        let html = "<html><body><p>text</p><input type=\"text\" name=\"q\" /></body></html>";
+        let (_browser, page) = load(html);
+        assert!(page.borrow().display_items_changed());
+
+        // Refreshing without any DOM change yields the same display items.
+        let before = page.borrow().display_items();
+        page.borrow_mut().refresh_display();
+        assert_eq!(before, page.borrow().display_items());
+        assert!(!page.borrow().display_items_changed());
+
+        let dom = document(&page);
+        let mut inputs = Vec::new();
+        collect_inputs(&Some(dom), &mut inputs);
+        page.borrow_mut().focused_input = Some(inputs[0].clone());
+        // Leave repainting the input to `refresh_display`.
+        page.borrow_mut().set_rerender_on_input(false);
+        page.borrow_mut().handle_input('a');
+        page.borrow_mut().refresh_display();
+        assert!(page.borrow().display_items_changed());
+    }
+
+    #[test]
+    fn test_clicked_link_in_span() {
+        let html = "<html><body><a href=\"/next\"><span>link</span></a></body></html>";
+        let (_browser, page) = load(html);
+
+        assert_eq!(
+            ClickAction::Navigate("/next".to_string()),
+            page.borrow_mut().clicked((1, 1))
+        );
+    }
+
+    #[test]
+    fn test_clicked_link_nested_two_levels() {
+        let html = "<html><body><a href=\"/deep\"><b><em>deep</em></b></a></body></html>";
+        let (_browser, page) = load(html);
+
+        let link = page
+            .borrow_mut()
+            .clicked_link((1, 1))
+            .expect("link should be clicked");
+        assert_eq!("/deep", link.href());
+    }
+
+    #[test]
+    fn test_autofocus() {
+        let html = "<html><body><input name=\"a\"><input name=\"b\" autofocus><input name=\"c\" autofocus></body></html>";
+        let (_browser, page) = load(html);
+
+        assert!(page.borrow().has_focused_input());
+        let name = match page
+            .borrow()
+            .focused_input
+            .as_ref()
+            .expect("should be focused")
+            .borrow()
+            .kind()
+        {
+            NodeKind::Element(e) => e.get_attribute("name"),
+            _ => None,
+        };
+        assert_eq!(Some("b".to_string()), name);
+
+        // Typing works without clicking the field.
+        assert!(page.borrow_mut().handle_input('x'));
+    }
+
+    #[test]
+    fn test_lazy_image() {
+        let browser = Browser::new();
+        browser.borrow_mut().set_handle_url(fetch_image);
+        let html = format!(
+            "<html><body><img src=\"top.png\" loading=\"lazy\">{}<img src=\"eager.png\"><img src=\"lazy.png\" loading=\"lazy\"></body></html>",
+            "<p>line</p>".repeat(30)
+        );
+        let response = HttpResponse::new(format!("HTTP/1.1 200 OK\n\n{}", html))
+            .expect("failed to parse a response");
+        let page = browser.borrow().current_page();
+        page.borrow_mut().receive_response(response);
+
+        // The lazy image below the fold is not fetched on the initial render.
+        page.borrow_mut().push_image_subresources(0);
+        page.borrow_mut().fetch_subresources();
+        assert_eq!("image", page.borrow().subresource("top.png".to_string()));
+        assert_eq!("image", page.borrow().subresource("eager.png".to_string()));
+        assert_eq!("", page.borrow().subresource("lazy.png".to_string()));
+
+        // It's fetched once the viewport reaches it.
+        page.borrow_mut().push_image_subresources(10000);
+        page.borrow_mut().fetch_subresources();
+        assert_eq!("image", page.borrow().subresource("lazy.png".to_string()));
+    }
+
+    #[test]
+    fn test_unknown_element_text() {
+        let html = "<html><body><p>a <my-widget>hi</my-widget> b</p></body></html>";
+        let (_browser, page) = load(html);
+
+        let texts: Vec<String> = page
+            .borrow()
+            .display_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert!(texts.contains(&"hi".to_string()));
+        assert_eq!(1, texts.iter().filter(|t| t.as_str() == "hi").count());
+    }
+
+    #[test]
+    fn test_submit_url_with_query() {
+        let submit_url = |action: &str| {
+            let html = format!(
+                "<html><body><form action=\"{}\"><input type=\"text\" name=\"q\" /></form></body></html>",
+                action
+            );
+            let (_browser, page) = load(&html);
+
+            let dom = document(&page);
+            let form = get_target_element_node(dom.borrow().first_child(), ElementKind::Form)
+                .expect("failed to get form");
+            let mut inputs = Vec::new();
+            collect_inputs(&form.borrow().first_child(), &mut inputs);
+            if let NodeKind::Element(e) = inputs[0].borrow().kind() {
+                e.set_value("rust".to_string());
+            }
+            let url = page.borrow().submit_url(&form);
+            url
+        };
+
+        assert_eq!("/search?q=rust", submit_url("/search"));
+        assert_eq!("/search?lang=en&q=rust", submit_url("/search?lang=en"));
+        assert_eq!("/search?q=rust", submit_url("/search?"));
+        assert_eq!(
+            "/search?lang=en&q=rust",
+            submit_url("/search?lang=en#results")
+        );
+    }
+
+    #[test]
+    fn test_generation() {
+        let browser = Browser::new();
+        let page = browser.borrow().current_page();
+        assert_eq!(0, page.borrow().generation());
+
+        for expected in 1..=2 {
+            let response =
+                HttpResponse::new("HTTP/1.1 200 OK\n\n<html><body>a</body></html>".to_string())
+                    .expect("failed to parse a response");
+            page.borrow_mut().receive_response(response);
+            assert_eq!(expected, page.borrow().generation());
+        }
+    }
+
+    #[test]
+    fn test_binary_body() {
+        let browser = Browser::new();
+        let mut raw = b"HTTP/1.1 200 OK\nContent-Length: 6\n\n".to_vec();
+        raw.extend_from_slice(&[0x89, b'P', b'N', b'G', 0xff, 0x00]);
+        let response = HttpResponse::from_bytes(&raw).expect("failed to parse a response");
+        assert!(response.is_binary());
+        let page = browser.borrow().current_page();
+        page.borrow_mut().receive_response(response);
+
+        let texts: Vec<String> = page
+            .borrow()
+            .display_items()
+            .into_iter()
+            .filter_map(|item| match item {
+                DisplayItem::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec!["This content is binary and can't be displayed.".to_string()],
+            texts
+        );
+    }
+
+    #[test]
+    fn test_submit_button() {
+        let html = "<html><body><form action=\"/search\"><input type=\"submit\" value=\"Go\"><input type=\"text\" name=\"q\" value=\"rust\"></form></body></html>";
+        let (_browser, page) = load(html);
+
+        let (label, point) = page
+            .borrow()
+            .display_items()
+            .into_iter()
+            .find_map(|item| match item {
+                DisplayItem::Input {
+                    input_type,
+                    value,
+                    layout_point,
+                    ..
+                } if input_type == "submit" => Some((value, layout_point)),
+                _ => None,
+            })
+            .expect("submit button should be painted");
+        assert_eq!(Some("Go".to_string()), label);
+
+        // The button itself is not a part of the form data.
+        assert_eq!(
+            ClickAction::Navigate("/search?q=rust".to_string()),
+            page.borrow_mut().clicked((point.x() + 1, point.y() + 1))
+        );
+        assert!(!page.borrow().has_focused_input());
+    }
+
+    #[test]
+    fn test_checkable_inputs() {
+        let html = "<html><body><form action=\"/order\"><input type=\"checkbox\" name=\"gift\"><input type=\"radio\" name=\"size\" value=\"s\" checked><input type=\"radio\" name=\"size\" value=\"l\" onclick='event.preventDefault()'><input type=\"radio\" name=\"size\" value=\"m\"></form></body></html>";
+        let (_browser, page) = load(html);
+
+        let inputs = || -> Vec<(Option<String>, LayoutPoint)> {
+            page.borrow()
+                .display_items()
+                .into_iter()
+                .filter_map(|item| match item {
+                    DisplayItem::Input {
+                        value,
+                        layout_point,
+                        ..
+                    } => Some((value, layout_point)),
+                    _ => None,
+                })
+                .collect()
+        };
+        let marks = || -> Vec<String> {
+            inputs()
+                .into_iter()
+                .filter_map(|(value, _)| value)
+                .collect()
+        };
+        let points: Vec<LayoutPoint> = inputs().into_iter().map(|(_, point)| point).collect();
+        let click = |point: &LayoutPoint| page.borrow_mut().clicked((point.x() + 1, point.y() + 1));
+        assert_eq!(vec!["[ ]", "(*)", "( )", "( )"], marks());
+
+        assert_eq!(ClickAction::RanHandler, click(&points[0]));
+        // The handler cancels the click, so the checked radio button is kept.
+        assert_eq!(ClickAction::RanHandler, click(&points[2]));
+        assert_eq!(vec!["[x]", "(*)", "( )", "( )"], marks());
+        assert_eq!(ClickAction::RanHandler, click(&points[3]));
+        assert_eq!(vec!["[x]", "( )", "( )", "(*)"], marks());
+
+        let dom = document(&page);
+        let form = get_target_element_node(dom.borrow().first_child(), ElementKind::Form)
+            .expect("failed to get form");
+        assert_eq!("/order?gift=on&size=m", page.borrow().submit_url(&form));
+    }
+
+    #[test]
+    fn test_focus_next_input() {
+        let html = "<html><body><input name=\"a\" value=\"x\"><input type=\"submit\"><input type=\"checkbox\" name=\"c\"><input name=\"b\"></body></html>";
+        let (_browser, page) = load(html);
+
+        let focused_name = || {
+            page.borrow()
+                .focused_input
+                .as_ref()
+                .and_then(|n| n.borrow().get_element())
+                .and_then(|e| e.get_attribute("name"))
+        };
+
+        // Buttons and checkboxes are skipped, and the caret is placed at the end.
+        assert!(page.borrow_mut().focus_next_input());
+        assert_eq!(Some("a".to_string()), focused_name());
+        assert!(page.borrow_mut().handle_input('y'));
+        assert!(page.borrow_mut().focus_next_input());
+        assert_eq!(Some("b".to_string()), focused_name());
+        assert!(!page.borrow_mut().focus_next_input());
+        assert_eq!(None, focused_name());
+        assert!(page.borrow_mut().focus_next_input());
+        assert_eq!(Some("a".to_string()), focused_name());
+        assert!(page.borrow_mut().move_caret(CaretMove::Left));
+        assert!(page.borrow_mut().handle_input('z'));
+        assert_eq!(
+            Some("xzy".to_string()),
+            page.borrow()
+                .focused_input
+                .as_ref()
+                .and_then(|n| n.borrow().get_element())
+                .and_then(|e| e.get_value())
+        );
+    }
+
+    #[test]
+    fn test_reset_form() {
+        let html = "<html><body><form><input name=\"a\" value=\"x\"><input name=\"b\"><input type=\"checkbox\" name=\"c\" checked></form></body></html>";
+        let (_browser, page) = load(html);
+
+        let dom = document(&page);
+        let form = get_target_element_node(dom.borrow().first_child(), ElementKind::Form)
+            .expect("failed to get form");
+        let mut inputs = Vec::new();
+        collect_inputs(&form.borrow().first_child(), &mut inputs);
+        let elements: Vec<_> = inputs
+            .iter()
+            .filter_map(|input| input.borrow().get_element())
+            .collect();
+
+        elements[0].set_value("edited".to_string());
+        elements[1].set_value("typed".to_string());
+        elements[2].set_checked(false);
+        assert_eq!(Some("edited".to_string()), elements[0].get_value());
+        assert!(!elements[2].is_checked());
+
+        page.borrow_mut().reset_form(&form);
+        assert_eq!(Some("x".to_string()), elements[0].get_value());
+        assert_eq!(None, elements[1].get_value());
+        assert!(elements[2].is_checked());
+    }
 }
@@ -15,6 +15,7 @@ fn print_dom_tree(node: &Option<alloc::rc::Rc<core::cell::RefCell<saba_core::ren
             NodeKind::Document => println!("{}Document", indent),
             NodeKind::Element(e) => println!("{}Element: {}", indent, e.kind()),
             NodeKind::Text(t) => println!("{}Text: '{}'", indent, t),
+            NodeKind::Comment(t) => println!("{}Comment: '{}'", indent, t),
         }
 
         print_dom_tree(&n.borrow().first_child(), depth + 1);
@@ -55,6 +55,7 @@ fn debug_print_dom(node: Rc<RefCell<saba_core::renderer::dom::node::Node>>, dept
             println!("{}Element: {} (attributes: {:?})", indent, element.kind(), element.attributes());
         }
         NodeKind::Text(text) => println!("{}Text: {:?}", indent, text),
+        NodeKind::Comment(text) => println!("{}Comment: {:?}", indent, text),
     }
 
     let mut current = node.borrow().first_child();
@@ -49,6 +49,7 @@ fn test_input_rendering_with_real_html() {
         name,
         placeholder,
         value: _,
+        caret: _,
         style: _,
         layout_point: _,
         layout_size: _,
@@ -48,6 +48,7 @@ fn test_input_element_rendering() {
             name,
             placeholder,
             value: _,
+            caret: _,
             style: _,
             layout_point: _,
             layout_size: _,
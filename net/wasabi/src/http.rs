@@ -8,7 +8,6 @@
 //! https://tools.ietf.org/html/rfc7235
 
 extern crate alloc;
-use alloc::format;
 use alloc::string::String;
 use alloc::string::ToString;
 use alloc::vec::Vec;
@@ -91,10 +90,8 @@ impl HttpClient {
             received.extend_from_slice(&buf[..bytes_read]);
         }
 
-        match core::str::from_utf8(&received) {
-            Ok(response) => HttpResponse::new(response.to_string()),
-            Err(e) => Err(Error::Network(format!("Invalid received response: {}", e))),
-        }
+        // The body may be binary, e.g. an image, so it's not required to be valid UTF-8.
+        HttpResponse::from_bytes(&received)
     }
 
     pub fn post(&self) {}
@@ -50,9 +50,9 @@ fn handle_url(url: String) -> core::result::Result<HttpResponse, Error> {
         parsed_url.path(),
     ) {
         Ok(res) => {
-            println!("Received response with status code: {}", res.status_code());
+            println!("Received response with status: {} {}", res.status_code(), res.reason_phrase());
             // redirect to Location
-            if res.status_code() == 302 {
+            if res.is_redirect() {
                 let location = match res.header_value("Location") {
                     Ok(value) => {
                         println!("Redirecting to: {}", value);
@@ -60,14 +60,22 @@ fn handle_url(url: String) -> core::result::Result<HttpResponse, Error> {
                     }
                     Err(_) => return Ok(res),
                 };
-                let redirect_parsed_url = Url::new(location);
+                // Resolve a relative Location against the URL that produced the redirect.
+                let redirect_parsed_url = match parsed_url.join(&location) {
+                    Ok(url) => url,
+                    Err(e) => {
+                        let error_msg = format!("Failed to parse redirect URL: {:?}", e);
+                        println!("{}", error_msg);
+                        return Err(Error::UnexpectedInput(error_msg));
+                    }
+                };
 
                 let redirect_client = HttpClient::new();
                 let redirect_res = match redirect_client.get(
                     redirect_parsed_url.host(),
                     redirect_parsed_url.port().parse::<u16>().expect(&format!(
                         "port number should be u16 but got {}",
-                        parsed_url.port()
+                        redirect_parsed_url.port()
                     )),
                     redirect_parsed_url.path(),
                 ) {
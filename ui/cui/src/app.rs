@@ -10,9 +10,18 @@ use crossterm::{
     },
 };
 use saba_core::browser::Browser;
+use saba_core::display_item::input_text;
+use saba_core::display_item::is_button_input;
+use saba_core::display_item::InputText;
 use saba_core::http::HttpResponse;
+use saba_core::log::Log;
+use saba_core::log::LogLevel;
+use saba_core::renderer::layout::computed_style::ComputedStyle;
 use saba_core::renderer::layout::computed_style::FontSize;
+use saba_core::renderer::layout::computed_style::FontStyle;
+use saba_core::renderer::layout::computed_style::FontWeight;
 use saba_core::renderer::layout::computed_style::TextDecoration;
+use saba_core::renderer::page::CaretMove;
 use saba_core::utils::*;
 use saba_core::{display_item::DisplayItem, error::Error};
 use std::io;
@@ -26,6 +35,44 @@ use tui::{
 };
 use unicode_width::UnicodeWidthStr;
 
+/// Returns modifiers to emphasize text. Nested emphasis (e.g. <b><em>..</em></b>) combines
+/// modifiers because font-weight and font-style are inherited independently.
+fn text_modifier(style: &ComputedStyle) -> Modifier {
+    let mut modifier = Modifier::empty();
+    // Terminals can't change a font size, so a large text is shown as bold instead.
+    if style.font_weight() == FontWeight::Bold || style.font_size() != FontSize::Medium {
+        modifier |= Modifier::BOLD;
+    }
+    if style.font_style() == FontStyle::Italic {
+        modifier |= Modifier::ITALIC;
+    }
+    if style.has_text_decoration(TextDecoration::LineThrough) {
+        modifier |= Modifier::CROSSED_OUT;
+    }
+    modifier
+}
+
+/// Returns the milliseconds since the UNIX epoch. It's the clock to measure rendering phases.
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Returns true if Esc was pressed while a fetch blocked the event loop. Other keys pressed
+/// meanwhile are dropped. It's the stop poller of the browser.
+fn esc_pressed() -> bool {
+    while let Ok(true) = event::poll(std::time::Duration::ZERO) {
+        if let Ok(Event::Key(key)) = event::read() {
+            if key.code == KeyCode::Esc {
+                return true;
+            }
+        }
+    }
+    false
+}
+
 #[derive(Clone, Copy, Debug)]
 enum InputMode {
     Normal,
@@ -51,6 +98,14 @@ pub struct Tui {
     input_mode: InputMode,
     // A user can focus only a link now.
     focus: Option<Link>,
+    // The generation of the page content which is shown now.
+    page_generation: u64,
+    // True if the content pane shows the HTML source instead of the rendered page.
+    view_source: bool,
+    // The number of lines the source view is scrolled by.
+    source_scroll: u16,
+    // True if the console pane shows debug logs as well as warnings and errors.
+    show_debug_logs: bool,
 }
 
 impl Tui {
@@ -60,6 +115,10 @@ impl Tui {
             input_url: String::new(),
             input_mode: InputMode::Normal,
             focus: None,
+            page_generation: 0,
+            view_source: false,
+            source_scroll: 0,
+            show_debug_logs: false,
         }
     }
 
@@ -67,6 +126,10 @@ impl Tui {
         &mut self,
         handle_url: fn(String) -> Result<HttpResponse, Error>,
     ) -> Result<(), Error> {
+        self.browser.borrow_mut().set_handle_url(handle_url);
+        self.browser.borrow_mut().set_clock(now_millis);
+        self.browser.borrow_mut().set_stop_poller(esc_pressed);
+
         // set up terminal
         match enable_raw_mode() {
             Ok(_) => {}
@@ -98,7 +161,7 @@ impl Tui {
         };
 
         // never return unless a user quit the tui app
-        let result = self.run_app(handle_url, &mut terminal);
+        let result = self.run_app(&mut terminal);
 
         // restore terminal
         match disable_raw_mode() {
@@ -143,8 +206,10 @@ impl Tui {
                     text,
                     style,
                     layout_point: _,
+                    visited: _,
+                    highlight: _,
                 } => {
-                    if style.text_decoration() != TextDecoration::Underline {
+                    if !style.has_text_decoration(TextDecoration::Underline) {
                         continue;
                     }
                     match &self.focus {
@@ -185,8 +250,10 @@ impl Tui {
                     text,
                     style,
                     layout_point: _,
+                    visited: _,
+                    highlight: _,
                 } => {
-                    if style.text_decoration() != TextDecoration::Underline {
+                    if !style.has_text_decoration(TextDecoration::Underline) {
                         continue;
                     }
                     // TODO: get correct destination link from Node.
@@ -215,31 +282,13 @@ impl Tui {
         }
     }
 
-    fn start_navigation(
-        &mut self,
-        handle_url: fn(String) -> Result<HttpResponse, Error>,
-        destination: String,
-    ) -> Result<(), Error> {
-        match handle_url(destination.clone()) {
-            Ok(response) => {
-                self.browser.borrow_mut().clear_logs();
-
-                let page = self.browser.borrow().current_page();
-                page.borrow_mut().clear_display_items();
-                page.borrow_mut().receive_response(response);
-
-                console_debug(
-                    &Rc::downgrade(&self.browser),
-                    format!("Successfully loaded page: {}", destination),
-                );
-            }
-            Err(e) => {
-                console_error(
-                    &Rc::downgrade(&self.browser),
-                    format!("Failed to load page: {:?}", e)
-                );
-                return Err(e);
-            }
+    fn start_navigation(&mut self, destination: String) -> Result<(), Error> {
+        if let Err(e) = Browser::navigate(&self.browser, &destination) {
+            console_error(
+                &Rc::downgrade(&self.browser),
+                format!("Failed to load page: {:?}", e)
+            );
+            return Err(e);
         }
         Ok(())
     }
@@ -258,11 +307,7 @@ impl Tui {
     }
     */
 
-    fn run_app<B: Backend>(
-        &mut self,
-        handle_url: fn(String) -> Result<HttpResponse, Error>,
-        terminal: &mut Terminal<B>,
-    ) -> Result<(), Error> {
+    fn run_app<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<(), Error> {
         loop {
             match terminal.draw(|frame| self.ui(frame)) {
                 Ok(_) => {}
@@ -278,13 +323,34 @@ impl Tui {
                 Event::Key(key) => {
                     //self.push_key_event(key.code);
 
+                    if matches!(self.input_mode, InputMode::Normal)
+                        && !self.view_source
+                        && self.handle_page_input(key.code)
+                    {
+                        continue;
+                    }
+
                     match self.input_mode {
                         InputMode::Normal => match key.code {
                             KeyCode::Up => {
-                                self.move_focus_to_up();
+                                if self.view_source {
+                                    self.source_scroll = self.source_scroll.saturating_sub(1);
+                                } else {
+                                    self.move_focus_to_up();
+                                }
                             }
                             KeyCode::Down => {
-                                self.move_focus_to_down();
+                                if self.view_source {
+                                    self.source_scroll = self.source_scroll.saturating_add(1);
+                                } else {
+                                    self.move_focus_to_down();
+                                }
+                            }
+                            KeyCode::Char('u') => {
+                                self.toggle_view_source();
+                            }
+                            KeyCode::Char('d') => {
+                                self.show_debug_logs = !self.show_debug_logs;
                             }
                             KeyCode::Enter => {
                                 // do nothing when there is no focused item;
@@ -301,10 +367,7 @@ impl Tui {
                                         &Rc::downgrade(&self.browser),
                                         format!("Navigating to link: {}", focus_item.destination),
                                     );
-                                    match self.start_navigation(
-                                        handle_url,
-                                        focus_item.destination.clone(),
-                                    ) {
+                                    match self.start_navigation(focus_item.destination.clone()) {
                                         Ok(_) => {}
                                         Err(_) => {
                                             // Error is already logged in start_navigation
@@ -313,6 +376,9 @@ impl Tui {
                                     }
                                 }
                             }
+                            KeyCode::Esc => {
+                                self.browser.borrow_mut().stop();
+                            }
                             KeyCode::Char('e') => {
                                 self.input_mode = InputMode::Editing;
                             }
@@ -342,7 +408,7 @@ impl Tui {
                                     &Rc::downgrade(&self.browser),
                                     format!("Starting navigation to: {}", url),
                                 );
-                                self.start_navigation(handle_url, url.clone())?;
+                                self.start_navigation(url.clone())?;
                             }
                             KeyCode::Char(c) => {
                                 self.input_url.push(c);
@@ -365,7 +431,96 @@ impl Tui {
         }
     }
 
+    /// Handles a key for the page. Tab moves the focus to the next input, and while an input has
+    /// focus, the other keys type into it or move its caret. Esc removes the focus from the input.
+    /// Returns true if the key is handled. A key which the input doesn't use, e.g. a character
+    /// for a button, is left to the browser commands.
+    fn handle_page_input(&mut self, key_code: KeyCode) -> bool {
+        let page = self.browser.borrow().current_page();
+        if key_code == KeyCode::Tab {
+            page.borrow_mut().focus_next_input();
+            page.borrow_mut().refresh_display();
+            return true;
+        }
+        if !page.borrow().has_focused_input() {
+            return false;
+        }
+
+        // The page repaints the input itself.
+        match key_code {
+            KeyCode::Left => {
+                page.borrow_mut().move_caret(CaretMove::Left);
+            }
+            KeyCode::Right => {
+                page.borrow_mut().move_caret(CaretMove::Right);
+            }
+            KeyCode::Home => {
+                page.borrow_mut().move_caret(CaretMove::Home);
+            }
+            KeyCode::End => {
+                page.borrow_mut().move_caret(CaretMove::End);
+            }
+            KeyCode::Char(c) => {
+                return page.borrow_mut().handle_input(c);
+            }
+            KeyCode::Backspace => {
+                return page.borrow_mut().handle_input(0x08 as char);
+            }
+            KeyCode::Esc => {
+                page.borrow_mut().blur_input();
+                page.borrow_mut().refresh_display();
+            }
+            KeyCode::Enter => {
+                let destination = page.borrow_mut().handle_enter();
+                match destination {
+                    // Only one page is shown, so a new page opens in place of the current one.
+                    Some((url, _target)) => {
+                        // An error is already logged in start_navigation.
+                        let _ = self.start_navigation(url);
+                    }
+                    None => page.borrow_mut().refresh_display(),
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Resets the state bound to the previous content when the page has received a new
+    /// response.
+    fn sync_page_generation(&mut self) {
+        let generation = self.browser.borrow().current_page().borrow().generation();
+        if generation != self.page_generation {
+            self.page_generation = generation;
+            self.focus = None;
+            self.source_scroll = 0;
+        }
+    }
+
+    /// Switches the content pane between the rendered page and its HTML source.
+    fn toggle_view_source(&mut self) {
+        self.view_source = !self.view_source;
+        self.source_scroll = 0;
+    }
+
+    fn page_source(&self) -> String {
+        self.browser.borrow().current_page().borrow().source()
+    }
+
+    /// Returns the logs shown in the console pane. Debug logs are hidden unless they're toggled
+    /// on.
+    fn console_logs(&self) -> Vec<Log> {
+        self.browser
+            .borrow()
+            .logs()
+            .into_iter()
+            .filter(|log| self.show_debug_logs || log.level() != LogLevel::Debug)
+            .collect()
+    }
+
     fn ui<B: Backend>(&mut self, frame: &mut Frame<B>) {
+        self.sync_page_generation();
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints(
@@ -393,10 +548,18 @@ impl Tui {
                         Style::default().add_modifier(Modifier::BOLD),
                     ),
                     Span::raw(" to move down a focused link, "),
+                    Span::styled("Tab", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(" to focus the next input, "),
+                    Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(" to leave the input, "),
                     Span::styled("q", Style::default().add_modifier(Modifier::BOLD)),
                     Span::raw(" to exit, "),
                     Span::styled("e", Style::default().add_modifier(Modifier::BOLD)),
                     Span::raw(" to start editing, "),
+                    Span::styled("u", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(" to toggle the page source, "),
+                    Span::styled("d", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(" to toggle debug logs, "),
                     Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
                     Span::raw(" to navigation to a focused link."),
                 ],
@@ -502,40 +665,55 @@ impl Tui {
                     text,
                     style,
                     layout_point: _,
+                    visited,
+                    highlight,
                 } => {
-                    if style.text_decoration() == TextDecoration::Underline {
-                        // link text.
+                    let mut base_style = Style::default();
+                    if let Some(highlight) = highlight {
+                        let code = highlight.code_u32();
+                        base_style = base_style.bg(Color::Rgb(
+                            (code >> 16) as u8,
+                            (code >> 8) as u8,
+                            code as u8,
+                        ));
+                    }
+
+                    if style.has_text_decoration(TextDecoration::Underline) {
+                        // link text. A visited link is dimmed.
+                        let link_modifier = if visited {
+                            text_modifier(&style) | Modifier::DIM
+                        } else {
+                            text_modifier(&style)
+                        };
                         if let Some(focus_item) = &self.focus {
                             if focus_item.text == text {
                                 spans.push(Spans::from(Span::styled(
                                     text,
-                                    Style::default()
+                                    base_style
                                         .fg(Color::Blue)
-                                        .add_modifier(Modifier::UNDERLINED),
+                                        .add_modifier(Modifier::UNDERLINED)
+                                        .add_modifier(link_modifier),
                                 )));
                                 continue;
                             }
                         }
                         spans.push(Spans::from(Span::styled(
                             text,
-                            Style::default().fg(Color::Blue),
+                            base_style.fg(Color::Blue).add_modifier(link_modifier),
                         )));
                     } else {
                         // normal text.
-                        spans.push(if style.font_size() != FontSize::Medium {
-                            Spans::from(Span::styled(
-                                text,
-                                Style::default().add_modifier(Modifier::BOLD),
-                            ))
-                        } else {
-                            Spans::from(Span::raw(text))
-                        });
+                        spans.push(Spans::from(Span::styled(
+                            text,
+                            base_style.add_modifier(text_modifier(&style)),
+                        )));
                     }
                 }
                 DisplayItem::Img {
                     src: _,
                     style: _,
                     layout_point: _,
+                    lazy: _,
                 } => {
                     // Do not support images in CUI.
                 }
@@ -544,14 +722,33 @@ impl Tui {
                     name: _,
                     placeholder,
                     value,
+                    caret,
                     style: _,
                     layout_point: _,
                     layout_size: _,
                 } => {
-                    let display_text = match (value, placeholder) {
-                        (Some(val), _) if !val.is_empty() => val.clone(),
-                        (_, Some(ph)) => format!("[{}]", ph),
-                        _ => format!("[{}]", input_type),
+                    // A button shows its label.
+                    if is_button_input(&input_type) {
+                        spans.push(Spans::from(Span::styled(
+                            format!("[ {} ] ", value.unwrap_or_default()),
+                            Style::default()
+                                .fg(Color::Cyan)
+                                .add_modifier(Modifier::BOLD),
+                        )));
+                        continue;
+                    }
+
+                    let display_text = match input_text(&input_type, &value, &placeholder) {
+                        InputText::Value(text) => {
+                            let mut text: Vec<char> = text.chars().collect();
+                            // A focused input shows the caret as a bar.
+                            if let Some(caret) = caret {
+                                text.insert(caret.min(text.len()), '|');
+                            }
+                            text.into_iter().collect()
+                        }
+                        InputText::Placeholder(ph) => format!("[{}]", ph),
+                        InputText::Empty => format!("[{}]", input_type),
                     };
                     spans.push(Spans::from(Span::styled(
                         format!("<{}> ", display_text),
@@ -561,15 +758,21 @@ impl Tui {
             }
         }
 
-        let contents = Paragraph::new(spans)
-            .block(Block::default().title("Content").borders(Borders::ALL))
-            .wrap(Wrap { trim: true });
-        frame.render_widget(contents, chunks[2]);
+        if self.view_source {
+            let source = Paragraph::new(self.page_source())
+                .block(Block::default().title("Source").borders(Borders::ALL))
+                .wrap(Wrap { trim: false })
+                .scroll((self.source_scroll, 0));
+            frame.render_widget(source, chunks[2]);
+        } else {
+            let contents = Paragraph::new(spans)
+                .block(Block::default().title("Content").borders(Borders::ALL))
+                .wrap(Wrap { trim: true });
+            frame.render_widget(contents, chunks[2]);
+        }
 
         let logs: Vec<ListItem> = self
-            .browser
-            .borrow()
-            .logs()
+            .console_logs()
             .iter()
             .enumerate()
             .map(|(_, log)| {
@@ -581,3 +784,147 @@ impl Tui {
         frame.render_widget(logs, chunks[3]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_modifier_bold_italic() {
+        let mut style = ComputedStyle::new();
+        style.set_font_size(FontSize::Medium);
+        style.set_font_weight(FontWeight::Bold);
+        style.set_font_style(FontStyle::Italic);
+        style.set_text_decoration(TextDecoration::None);
+
+        assert_eq!(text_modifier(&style), Modifier::BOLD | Modifier::ITALIC);
+    }
+
+    #[test]
+    fn test_text_modifier_normal() {
+        let mut style = ComputedStyle::new();
+        style.set_font_size(FontSize::Medium);
+        style.set_font_weight(FontWeight::Normal);
+        style.set_font_style(FontStyle::Normal);
+        style.set_text_decoration(TextDecoration::None);
+
+        assert_eq!(text_modifier(&style), Modifier::empty());
+    }
+
+    #[test]
+    fn test_text_modifier_line_through() {
+        let mut style = ComputedStyle::new();
+        style.set_font_size(FontSize::Medium);
+        style.set_font_weight(FontWeight::Normal);
+        style.set_font_style(FontStyle::Normal);
+        style.set_text_decoration(TextDecoration::LineThrough);
+
+        assert_eq!(text_modifier(&style), Modifier::CROSSED_OUT);
+    }
+
+    #[test]
+    fn test_aria_label_link_focus() {
+        let browser = Browser::new();
+        let html = "<html><body><a aria-label=\"Home\" href=\"/\"></a></body></html>";
+        let response = HttpResponse::new(format!("HTTP/1.1 200 OK\n\n{}", html))
+            .expect("failed to parse a response");
+        let page = browser.borrow().current_page();
+        page.borrow_mut().receive_response(response);
+
+        let mut tui = Tui::new(browser);
+        tui.move_focus_to_down();
+        assert_eq!(Some("Home".to_string()), tui.focus.map(|link| link.text));
+    }
+
+    #[test]
+    fn test_edit_input_at_caret() {
+        let browser = Browser::new();
+        let html = "<html><body><input name=\"q\" value=\"ac\"></body></html>";
+        let response = HttpResponse::new(format!("HTTP/1.1 200 OK\n\n{}", html))
+            .expect("failed to parse a response");
+        let page = browser.borrow().current_page();
+        page.borrow_mut().receive_response(response);
+
+        let mut tui = Tui::new(browser);
+        let value = || {
+            page.borrow()
+                .display_items()
+                .into_iter()
+                .find_map(|item| match item {
+                    DisplayItem::Input { value, .. } => value,
+                    _ => None,
+                })
+        };
+        // Keys are not typed into the page until an input has focus.
+        assert!(!tui.handle_page_input(KeyCode::Char('x')));
+
+        for key_code in [
+            KeyCode::Tab,
+            KeyCode::Left,
+            KeyCode::Char('b'),
+            KeyCode::Home,
+            KeyCode::Char('_'),
+            KeyCode::End,
+            KeyCode::Backspace,
+        ] {
+            assert!(tui.handle_page_input(key_code));
+        }
+        assert_eq!(Some("_ab".to_string()), value());
+
+        // Esc leaves the input, and the command keys work again.
+        assert!(tui.handle_page_input(KeyCode::Esc));
+        assert!(!page.borrow().has_focused_input());
+        assert!(!tui.handle_page_input(KeyCode::Char('q')));
+        assert_eq!(Some("_ab".to_string()), value());
+    }
+
+    #[test]
+    fn test_view_source() {
+        let browser = Browser::new();
+        let html = "<html><head></head><body><p>text</p></body></html>";
+        let response = HttpResponse::new(format!("HTTP/1.1 200 OK\n\n{}", html))
+            .expect("failed to parse a response");
+        let page = browser.borrow().current_page();
+        page.borrow_mut().receive_response(response);
+
+        let mut tui = Tui::new(browser);
+        assert!(!tui.view_source);
+
+        tui.toggle_view_source();
+        assert!(tui.view_source);
+        assert!(tui.page_source().contains("<p>text</p>"));
+
+        tui.toggle_view_source();
+        assert!(!tui.view_source);
+    }
+
+    #[test]
+    fn test_console_logs() {
+        let browser = Browser::new();
+        browser.borrow_mut().console_debug("debug".to_string());
+        browser.borrow_mut().console_warning("warning".to_string());
+        browser.borrow_mut().console_error("error".to_string());
+
+        let mut tui = Tui::new(browser);
+        let messages = |tui: &Tui| -> Vec<String> {
+            tui.console_logs()
+                .iter()
+                .map(|log| log.to_string())
+                .collect()
+        };
+        assert_eq!(
+            vec!["Warning: warning".to_string(), "Error: error".to_string()],
+            messages(&tui)
+        );
+
+        tui.show_debug_logs = true;
+        assert_eq!(
+            vec![
+                "Debug: debug".to_string(),
+                "Warning: warning".to_string(),
+                "Error: error".to_string()
+            ],
+            messages(&tui)
+        );
+    }
+}
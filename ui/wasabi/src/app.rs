@@ -23,11 +23,16 @@ use noli::window::Window;
 use saba_core::{
     browser::Browser,
     constants::*,
+    display_item::input_text,
     display_item::DisplayItem,
+    display_item::InputText,
     error::Error,
     http::HttpResponse,
-    renderer::layout::computed_style::{FontSize, TextDecoration},
     renderer::layout::color::Color,
+    renderer::layout::computed_style::{Cursor as CursorShape, FontSize, TextDecoration},
+    renderer::page::CaretMove,
+    renderer::page::ClickAction,
+    renderer::page::NavigationTarget,
 };
 use tinybmp::{Bmp, RawBmp};
 
@@ -37,6 +42,41 @@ fn convert_color(color: Color) -> Rgb888 {
     Rgb888::new(r as u8, g as u8, b as u8)
 }
 
+/// Returns true if Esc was pressed while a fetch blocked the event loop. Other keys pressed
+/// meanwhile are dropped. It's the stop poller of the browser.
+fn esc_pressed() -> bool {
+    while let Some(c) = Api::read_key() {
+        if c == 0x1B as char {
+            return true;
+        }
+    }
+    false
+}
+
+/// Reads the rest of an escape sequence after ESC and returns the caret movement of the left
+/// and right arrow, Home and End keys, e.g. "\x1b[D" for the left arrow. Returns None for other
+/// sequences.
+fn read_caret_key_sequence() -> Option<CaretMove> {
+    if Api::read_key() != Some('[') {
+        return None;
+    }
+    let caret_move = match Api::read_key()? {
+        'D' => return Some(CaretMove::Left),
+        'C' => return Some(CaretMove::Right),
+        'H' => return Some(CaretMove::Home),
+        'F' => return Some(CaretMove::End),
+        // Some terminals send "\x1b[1~" and "\x1b[4~" for Home and End.
+        '1' | '7' => CaretMove::Home,
+        '4' | '8' => CaretMove::End,
+        _ => return None,
+    };
+    if Api::read_key() == Some('~') {
+        Some(caret_move)
+    } else {
+        None
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum InputMode {
     Normal,
@@ -75,10 +115,12 @@ impl WasabiUI {
         &mut self,
         handle_url: fn(String) -> Result<HttpResponse, Error>,
     ) -> Result<(), Error> {
+        self.browser.borrow_mut().set_handle_url(handle_url);
+        self.browser.borrow_mut().set_stop_poller(esc_pressed);
         self.setup()?;
 
         // never return unless a user quits the app.
-        self.run_app(handle_url)?;
+        self.run_app()?;
 
         Ok(())
     }
@@ -137,10 +179,7 @@ impl WasabiUI {
         Ok(())
     }
 
-    fn handle_key_input(
-        &mut self,
-        handle_url: fn(String) -> Result<HttpResponse, Error>,
-    ) -> Result<(), Error> {
+    fn handle_key_input(&mut self) -> Result<(), Error> {
         match self.input_mode {
             InputMode::Normal => {
                 // Check if an input element has focus
@@ -149,19 +188,66 @@ impl WasabiUI {
 
                 if has_focused_input {
                     if let Some(c) = Api::read_key() {
+                        if c == 0x0A as char {
+                            // enter key moves the focus to the next input or submits the form
+                            let destination = page.borrow_mut().handle_enter();
+                            match destination {
+                                Some((url, target)) => {
+                                    if let Err(e) = self.start_navigation(url, target) {
+                                        println!("Form submission failed: {:?}", e);
+                                    }
+                                }
+                                None => {
+                                    page.borrow_mut().refresh_display();
+                                    if page.borrow().display_items_changed() {
+                                        self.clear_content_area()?;
+                                        self.update_ui()?;
+                                    }
+                                }
+                            }
+                            return Ok(());
+                        }
+
+                        // The arrow, Home and End keys move the caret. Ctrl-B, Ctrl-F, Ctrl-A
+                        // and Ctrl-E do the same for keyboards without them.
+                        let caret_move = match c as u32 {
+                            0x1B => read_caret_key_sequence(),
+                            0x01 => Some(CaretMove::Home),
+                            0x05 => Some(CaretMove::End),
+                            0x02 => Some(CaretMove::Left),
+                            0x06 => Some(CaretMove::Right),
+                            _ => None,
+                        };
+                        if c == 0x1B as char && caret_move.is_none() {
+                            return Ok(());
+                        }
+                        if let Some(caret_move) = caret_move {
+                            // The page repaints the input itself.
+                            if page.borrow_mut().move_caret(caret_move)
+                                && page.borrow().display_items_changed()
+                            {
+                                self.clear_content_area()?;
+                                self.update_ui()?;
+                            }
+                            return Ok(());
+                        }
+
                         // Handle input to focused element
                         if page.borrow_mut().handle_input(c) {
-                            // Refresh display items by rebuilding layout tree
-                            page.borrow_mut().refresh_display();
-
-                            // Re-render the page to show updated input value
-                            self.clear_content_area()?;
-                            self.update_ui()?;
+                            // The page repaints the input itself, so re-render the page to show
+                            // the updated input value.
+                            if page.borrow().display_items_changed() {
+                                self.clear_content_area()?;
+                                self.update_ui()?;
+                            }
                         }
                     }
                 } else {
-                    // ignore a key when input_mode is Normal and no input is focused
-                    let _ = Api::read_key();
+                    // Esc stops the current navigation. Other keys are ignored when input_mode is
+                    // Normal and no input is focused.
+                    if Api::read_key() == Some(0x1B as char) {
+                        self.browser.borrow_mut().stop();
+                    }
                 }
             }
             InputMode::Editing => {
@@ -175,7 +261,10 @@ impl WasabiUI {
                             self.input_mode = InputMode::Normal;
                         } else {
                             println!("Starting navigation to: {}", self.input_url);
-                            match self.start_navigation(handle_url, self.input_url.clone()) {
+                            match self.start_navigation(
+                                self.input_url.clone(),
+                                NavigationTarget::CurrentPage,
+                            ) {
                                 Ok(_) => {
                                     println!("Navigation successful");
                                 }
@@ -201,13 +290,11 @@ impl WasabiUI {
         Ok(())
     }
 
-    fn handle_mouse_input(
-        &mut self,
-        handle_url: fn(String) -> Result<HttpResponse, Error>,
-    ) -> Result<(), Error> {
+    fn handle_mouse_input(&mut self) -> Result<(), Error> {
         if let Some(MouseEvent { button, position }) = Api::get_mouse_cursor_info() {
             self.window.flush_area(self.cursor.rect());
             self.cursor.set_position(position.x, position.y);
+            self.update_cursor_shape(position.x, position.y);
             self.window.flush_area(self.cursor.rect());
             self.cursor.flush();
 
@@ -252,7 +339,7 @@ impl WasabiUI {
                     relative_pos.1 - TITLE_BAR_HEIGHT - TOOLBAR_HEIGHT,
                 );
                 let page = self.browser.borrow().current_page();
-                let next_destination = page.borrow_mut().clicked(position_in_content_area);
+                let click_action = page.borrow_mut().clicked(position_in_content_area);
 
                 // clear logs.
                 for log in self.browser.borrow().logs() {
@@ -260,18 +347,22 @@ impl WasabiUI {
                 }
                 self.browser.borrow_mut().clear_logs();
 
-                if let Some(url) = next_destination {
-                    // navigate to the next url.
-                    self.input_url = url.clone();
-                    self.update_address_bar()?;
-                    match self.start_navigation(handle_url, url) {
-                        Ok(_) => {
-                            println!("Link navigation successful");
-                        }
-                        Err(e) => {
-                            println!("Link navigation failed: {:?}", e);
+                match click_action {
+                    ClickAction::Navigate(url) => {
+                        self.follow_link(url, NavigationTarget::CurrentPage)?;
+                    }
+                    ClickAction::NavigateInNewPage(url) => {
+                        self.follow_link(url, NavigationTarget::NewPage)?;
+                    }
+                    // A handler may have changed the page, and a focused input shows its caret.
+                    ClickAction::RanHandler | ClickAction::FocusInput => {
+                        page.borrow_mut().refresh_display();
+                        if page.borrow().display_items_changed() {
+                            self.clear_content_area()?;
+                            self.update_ui()?;
                         }
                     }
+                    ClickAction::None => {}
                 }
             }
         }
@@ -279,39 +370,64 @@ impl WasabiUI {
         Ok(())
     }
 
-    fn run_app(
-        &mut self,
-        handle_url: fn(String) -> Result<HttpResponse, Error>,
-    ) -> Result<(), Error> {
+    /// Shows the cursor for the content under the mouse, e.g. a pointer over a link.
+    fn update_cursor_shape(&mut self, x: i64, y: i64) {
+        let position_in_content_area = (
+            x - WINDOW_INIT_X_POS,
+            y - WINDOW_INIT_Y_POS - TITLE_BAR_HEIGHT - TOOLBAR_HEIGHT,
+        );
+        let shape = if position_in_content_area.0 < 0
+            || position_in_content_area.0 > WINDOW_WIDTH
+            || position_in_content_area.1 < 0
+            || position_in_content_area.1 > CONTENT_AREA_HEIGHT
+        {
+            CursorShape::Default
+        } else {
+            let page = self.browser.borrow().current_page();
+            let shape = page.borrow().cursor_at(position_in_content_area);
+            shape
+        };
+        self.cursor.set_shape(shape);
+    }
+
+    fn run_app(&mut self) -> Result<(), Error> {
         loop {
-            self.handle_key_input(handle_url)?;
-            self.handle_mouse_input(handle_url)?;
+            self.handle_key_input()?;
+            self.handle_mouse_input()?;
         }
     }
 
+    /// Navigates to `url` clicked in the page and shows it in the address bar.
+    fn follow_link(&mut self, url: String, target: NavigationTarget) -> Result<(), Error> {
+        let url = self.browser.borrow().resolve_url(&url);
+        self.input_url = url.clone();
+        self.update_address_bar()?;
+        match self.start_navigation(url, target) {
+            Ok(_) => {
+                println!("Link navigation successful");
+            }
+            Err(e) => {
+                println!("Link navigation failed: {:?}", e);
+            }
+        }
+        Ok(())
+    }
+
     fn start_navigation(
         &mut self,
-        handle_url: fn(String) -> Result<HttpResponse, Error>,
         destination: String,
+        target: NavigationTarget,
     ) -> Result<(), Error> {
         self.clear_content_area()?;
 
-        match handle_url(destination.clone()) {
-            Ok(response) => {
-                println!("Successfully received response for: {}", destination);
-                self.browser.borrow_mut().clear_logs();
-
-                let page = self.browser.borrow().current_page();
-                page.borrow_mut().clear_display_items();
-                page.borrow_mut().receive_response(response);
-
-                println!("Page rendering complete");
-            }
-            Err(e) => {
-                println!("Navigation error: {:?}", e);
-                self.display_error_message(format!("{:?}", e))?;
-                return Err(e);
-            }
+        let result = match target {
+            NavigationTarget::CurrentPage => Browser::navigate(&self.browser, &destination),
+            NavigationTarget::NewPage => Browser::navigate_in_new_page(&self.browser, &destination),
+        };
+        if let Err(e) = result {
+            println!("Navigation error: {:?}", e);
+            self.display_error_message(format!("{:?}", e))?;
+            return Err(e);
         }
 
         self.update_ui()?;
@@ -356,12 +472,14 @@ impl WasabiUI {
     }
 
     fn update_ui(&mut self) -> Result<(), Error> {
-        let display_items = self
-            .browser
-            .borrow()
-            .current_page()
-            .borrow()
-            .display_items();
+        // Fetch the images which a repaint brings into the viewport, e.g. after a script or an
+        // input changes the layout. There is no scrolling yet, so the viewport is always at the
+        // top of the page, and a lazy image below it is never loaded.
+        let page = self.browser.borrow().current_page();
+        page.borrow_mut().push_image_subresources(0);
+        page.borrow_mut().fetch_subresources();
+
+        let display_items = page.borrow().display_items();
 
         for item in display_items {
             match item {
@@ -370,23 +488,18 @@ impl WasabiUI {
                     layout_point,
                     layout_size,
                 } => {
-                    let x = layout_point.x() + WINDOW_PADDING;
-                    let y = layout_point.y() + WINDOW_PADDING + TOOLBAR_HEIGHT;
-                    let mut width = layout_size.width();
-                    let mut height = layout_size.height();
+                    let point =
+                        layout_point.translate(WINDOW_PADDING, WINDOW_PADDING + TOOLBAR_HEIGHT);
+                    let (x, y) = (point.x(), point.y());
                     let color = style.background_color().code_u32();
 
                     // Clamp rectangle size to window bounds
-                    // Account for TITLE_BAR_HEIGHT (24) in wasabi OS
-                    let max_width = WINDOW_WIDTH - WINDOW_PADDING - x;
-                    let max_height = WINDOW_HEIGHT - WINDOW_PADDING - y - 24; // Reserve space for title bar
-
-                    if width > max_width {
-                        width = max_width;
-                    }
-                    if height > max_height {
-                        height = max_height;
-                    }
+                    // Account for TITLE_BAR_HEIGHT in wasabi OS
+                    let size = layout_size.clamp(
+                        WINDOW_WIDTH - WINDOW_PADDING - x,
+                        WINDOW_HEIGHT - WINDOW_PADDING - y - TITLE_BAR_HEIGHT,
+                    );
+                    let (width, height) = (size.width(), size.height());
 
                     // Skip drawing if rectangle is too small or outside bounds
                     if width <= 0 || height <= 0 || x < 0 || y < 0 {
@@ -413,10 +526,18 @@ impl WasabiUI {
                     text,
                     style,
                     layout_point,
+                    visited,
+                    highlight,
                 } => {
-                    let x = layout_point.x() + WINDOW_PADDING;
-                    let y = layout_point.y() + WINDOW_PADDING + TOOLBAR_HEIGHT;
-                    let color = style.color().code_u32();
+                    let point =
+                        layout_point.translate(WINDOW_PADDING, WINDOW_PADDING + TOOLBAR_HEIGHT);
+                    let (x, y) = (point.x(), point.y());
+                    // A visited link is drawn in purple.
+                    let color = if visited {
+                        PURPLE
+                    } else {
+                        style.color().code_u32()
+                    };
 
                     // Check if text is within bounds
                     // Account for TITLE_BAR_HEIGHT (24) and text height
@@ -430,6 +551,25 @@ impl WasabiUI {
 
                     println!("Drawing text: '{}' at x={}, y={}, color=0x{:x}", text, x, y, color);
 
+                    if let Some(highlight) = highlight {
+                        let char_width = match style.font_size() {
+                            FontSize::Medium => CHAR_WIDTH,
+                            FontSize::XLarge => CHAR_WIDTH * 2,
+                            FontSize::XXLarge => CHAR_WIDTH * 3,
+                        };
+                        let width = (char_width * text.len() as i64).min(WINDOW_WIDTH - x);
+                        if self
+                            .window
+                            .fill_rect(highlight.code_u32(), x, y, width, CHAR_HEIGHT)
+                            .is_err()
+                        {
+                            return Err(Error::InvalidUI(format!(
+                                "failed to draw highlight for text: '{}'",
+                                text
+                            )));
+                        }
+                    }
+
                     if self
                         .window
                         .draw_string(
@@ -438,22 +578,41 @@ impl WasabiUI {
                             y,
                             &text,
                             convert_font_size(style.font_size()),
-                            style.text_decoration() == TextDecoration::Underline,
+                            style.has_text_decoration(TextDecoration::Underline),
                         )
                         .is_err()
                     {
                         return Err(Error::InvalidUI(format!("failed to draw text: '{}'", text)));
                     }
+
+                    // Strike through the middle of the text.
+                    if style.has_text_decoration(TextDecoration::LineThrough) {
+                        let (char_width, char_height) = match style.font_size() {
+                            FontSize::Medium => (CHAR_WIDTH, CHAR_HEIGHT),
+                            FontSize::XLarge => (CHAR_WIDTH * 2, CHAR_HEIGHT * 2),
+                            FontSize::XXLarge => (CHAR_WIDTH * 3, CHAR_HEIGHT * 3),
+                        };
+                        let width = (char_width * text.len() as i64).min(WINDOW_WIDTH - x);
+                        if self
+                            .window
+                            .fill_rect(color, x, y + char_height / 2, width, 1)
+                            .is_err()
+                        {
+                            return Err(Error::InvalidUI(format!(
+                                "failed to draw a line through text: '{}'",
+                                text
+                            )));
+                        }
+                    }
                 }
                 DisplayItem::Img {
                     src,
                     style: _,
                     layout_point,
+                    lazy: _,
                 } => {
                     print!("DisplayItem::Img src: {}\n", src);
 
-                    self.browser.borrow_mut().push_url_for_subresource(src);
-
                     let data = include_bytes!("./youtube.bmp");
                     let bmp = match Bmp::<Rgb888>::from_slice(data) {
                         Ok(bmp) => bmp,
@@ -468,13 +627,9 @@ impl WasabiUI {
                         }
                     };
 
-                    let image = Image::new(
-                        &bmp,
-                        Point::new(
-                            (layout_point.x() + WINDOW_PADDING) as i32,
-                            (layout_point.y() + WINDOW_PADDING + TOOLBAR_HEIGHT) as i32,
-                        ),
-                    );
+                    let point =
+                        layout_point.translate(WINDOW_PADDING, WINDOW_PADDING + TOOLBAR_HEIGHT);
+                    let image = Image::new(&bmp, Point::new(point.x() as i32, point.y() as i32));
                     //print!("image: {:#?}\n", image);
 
                     if image.draw(&mut self.window).is_err() {
@@ -486,6 +641,7 @@ impl WasabiUI {
                     name: _,
                     placeholder,
                     value,
+                    caret,
                     style,
                     layout_point,
                     layout_size,
@@ -494,11 +650,10 @@ impl WasabiUI {
                         input_type, value, placeholder);
 
                     // Draw input border
+                    let point =
+                        layout_point.translate(WINDOW_PADDING, WINDOW_PADDING + TOOLBAR_HEIGHT);
                     let rect = Rectangle::new(
-                        Point::new(
-                            (layout_point.x() + WINDOW_PADDING) as i32,
-                            (layout_point.y() + WINDOW_PADDING + TOOLBAR_HEIGHT) as i32,
-                        ),
+                        Point::new(point.x() as i32, point.y() as i32),
                         Size::new(layout_size.width() as u32, layout_size.height() as u32),
                     );
 
@@ -510,15 +665,15 @@ impl WasabiUI {
                     }
 
                     // Draw input text (placeholder or value)
-                    let display_text = match (value, placeholder) {
-                        (Some(val), _) if !val.is_empty() => val.clone(),
-                        (_, Some(ph)) => ph.clone(),
-                        _ => format!("Enter {}", input_type),
+                    // A button shows its label.
+                    let display_text = match input_text(input_type, value, placeholder) {
+                        InputText::Value(text) | InputText::Placeholder(text) => text,
+                        InputText::Empty => format!("Enter {}", input_type),
                     };
 
                     // Calculate text position
                     // Add padding from left edge and vertically center the text
-                    let text_x = layout_point.x() + WINDOW_PADDING + 5; // 5px padding inside input
+                    let text_x = point.x() + 5; // 5px padding inside input
 
                     // Estimate font height based on font size for vertical centering
                     let font_height = match style.font_size() {
@@ -529,7 +684,7 @@ impl WasabiUI {
 
                     // Center text vertically within the input box
                     let vertical_offset = ((layout_size.height() as i64 - font_height) / 2).max(0);
-                    let text_y = layout_point.y() + WINDOW_PADDING + TOOLBAR_HEIGHT + vertical_offset;
+                    let text_y = point.y() + vertical_offset;
 
                     if self
                         .window
@@ -545,6 +700,29 @@ impl WasabiUI {
                     {
                         return Err(Error::InvalidUI(format!("failed to draw input text: '{}'", display_text)));
                     }
+
+                    // Draw the caret of the focused input as a vertical bar
+                    if let Some(caret) = caret {
+                        let char_width = match style.font_size() {
+                            FontSize::Medium => CHAR_WIDTH,
+                            FontSize::XLarge => CHAR_WIDTH * 2,
+                            FontSize::XXLarge => CHAR_WIDTH * 3,
+                        };
+                        let caret_x = text_x + char_width * *caret as i64;
+                        if self
+                            .window
+                            .draw_line(
+                                BLACK,
+                                caret_x,
+                                point.y() + 2,
+                                caret_x,
+                                point.y() + layout_size.height() - 3,
+                            )
+                            .is_err()
+                        {
+                            return Err(Error::InvalidUI("failed to draw a caret".to_string()));
+                        }
+                    }
                 }
             }
         }
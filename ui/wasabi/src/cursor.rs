@@ -1,19 +1,23 @@
 use noli::bitmap::bitmap_draw_rect;
 use noli::rect::Rect;
 use noli::sheet::Sheet;
+use saba_core::renderer::layout::computed_style::Cursor as CursorShape;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Cursor {
     sheet: Sheet,
+    shape: CursorShape,
 }
 
 impl Cursor {
     pub fn new() -> Self {
-        let mut sheet = Sheet::new(Rect::new(0, 0, 10, 10).unwrap());
-        let bitmap = sheet.bitmap();
-        bitmap_draw_rect(bitmap, 0xff0000, 0, 0, 10, 10).expect("failed to draw a cursor");
-
-        Self { sheet }
+        let sheet = Sheet::new(Rect::new(0, 0, 10, 10).unwrap());
+        let mut cursor = Self {
+            sheet,
+            shape: CursorShape::Default,
+        };
+        cursor.draw();
+        cursor
     }
 
     pub fn rect(&self) -> Rect {
@@ -24,6 +28,32 @@ impl Cursor {
         self.sheet.set_position(x, y);
     }
 
+    /// Changes the drawn shape. `auto` is drawn as the default cursor.
+    pub fn set_shape(&mut self, shape: CursorShape) {
+        if self.shape == shape {
+            return;
+        }
+        self.shape = shape;
+        self.draw();
+    }
+
+    fn draw(&mut self) {
+        match self.shape {
+            // A pointer over a link is drawn as a cross.
+            CursorShape::Pointer => {
+                self.fill_rect(0xffffff, 0, 0, 10, 10);
+                self.fill_rect(0xff0000, 3, 0, 4, 10);
+                self.fill_rect(0xff0000, 0, 3, 10, 4);
+            }
+            CursorShape::Auto | CursorShape::Default => self.fill_rect(0xff0000, 0, 0, 10, 10),
+        }
+    }
+
+    fn fill_rect(&mut self, color: u32, x: i64, y: i64, width: i64, height: i64) {
+        bitmap_draw_rect(self.sheet.bitmap(), color, x, y, width, height)
+            .expect("failed to draw a cursor");
+    }
+
     pub fn flush(&mut self) {
         self.sheet.flush();
     }